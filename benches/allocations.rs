@@ -0,0 +1,76 @@
+//! Counts the heap allocations of a parse, comparing the arguments
+//! against the previous layout where every key was its own `String`.
+//! Run with `cargo bench --bench allocations`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use multimap::MultiMap;
+use simple_args::{Arguments, Source};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T, F: FnOnce() -> T>(run: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let kept = run();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    drop(kept);
+    count
+}
+
+/// Stores the keys and values of the tokens the way `Arguments` did
+/// before keys were stored inline
+fn previous_layout(tokens: &[String]) -> impl Drop {
+    let mut arg_map: MultiMap<String, Option<String>> = MultiMap::new();
+    let mut sources: MultiMap<String, (Source, usize)> = MultiMap::new();
+    for (ordinal, pair) in tokens.chunks(2).enumerate() {
+        let key = pair[0].trim_start_matches('-');
+        match (arg_map.get_vec_mut(key), sources.get_vec_mut(key)) {
+            (Some(vals), Some(keyed)) => {
+                vals.push(Some(pair[1].clone()));
+                keyed.push((Source::CommandLine, ordinal));
+            }
+            _ => {
+                arg_map.insert(key.to_string(), Some(pair[1].clone()));
+                sources.insert(key.to_string(), (Source::CommandLine, ordinal));
+            }
+        }
+    }
+    Box::new((arg_map, sources))
+}
+
+fn compare(name: &str, tokens: &[String]) {
+    let previous = allocations(|| previous_layout(tokens));
+    let current = allocations(|| Arguments::parse(tokens));
+    println!(
+        "{:<16} previous layout {:>5}  arguments {:>5}",
+        name, previous, current
+    );
+}
+
+fn main() {
+    let distinct: Vec<String> = (0..20)
+        .flat_map(|i| vec![format!("-flag{}", i), format!("{}", i)])
+        .collect();
+    compare("distinct keys", &distinct);
+    let repeated: Vec<String> = (0..100)
+        .flat_map(|i| vec!["-include".to_string(), format!("dir{}", i)])
+        .collect();
+    compare("repeated keys", &repeated);
+}
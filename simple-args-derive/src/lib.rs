@@ -0,0 +1,399 @@
+//! Derive macro for `simple-args`. Use it through the `derive` feature
+//! of `simple-args`, which re-exports it as `simple_args::SimpleArgs`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DataStruct, DeriveInput, Expr, ExprLit, Field,
+    Fields, Lit, LitStr, Meta, Type,
+};
+
+/// Derives `simple_args::SimpleArgs`.
+///
+/// On a struct with named fields, each field maps to the argument named
+/// after it in kebab case: `bool` fields are flags, `Option<T>` fields
+/// are optional, `Vec<T>` fields collect every instance of the argument
+/// and any other field is required, with values converted through
+/// `FromStr`. A field annotated with `#[command(subcommand)]`
+/// holds the subcommand invoked after the struct's own arguments, and a
+/// field annotated with `#[args(flatten)]` embeds the arguments of
+/// another `SimpleArgs` struct, so shared option groups can be reused.
+/// `#[args(default = "8080", env = "PORT")]` declares the fallbacks
+/// used when an argument is absent from the command line, resolved by
+/// `Spec::apply_fallbacks`. Doc comments on the struct, its fields and
+/// enum variants become the descriptions shown in help output.
+///
+/// On an enum, each variant is a subcommand named after it in kebab case
+/// and is either a unit variant or holds a single `SimpleArgs` struct
+/// with the subcommand's arguments
+#[proc_macro_derive(SimpleArgs, attributes(args, command))]
+pub fn derive_simple_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let expanded = match &input.data {
+        Data::Struct(data) => expand_struct(&input, data),
+        Data::Enum(data) => expand_enum(&input, data),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input,
+            "SimpleArgs cannot be derived for unions",
+        )),
+    };
+    expanded
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a struct field is filled from the arguments
+enum Kind {
+    Flag,
+    Optional,
+    Multiple,
+    Required,
+    Subcommand,
+    Flatten,
+}
+
+/// Converts a `snake_case` identifier to `kebab-case`
+fn kebab_from_snake(ident: &str) -> String {
+    ident.trim_start_matches("r#").replace('_', "-")
+}
+
+/// Converts a `PascalCase` identifier to `kebab-case`
+fn kebab_from_pascal(ident: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('-');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Returns the last path segment of a type, if it is a path
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Collects the first paragraph of `///` doc comments into one line,
+/// used as the description in help output
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+        if let Meta::NameValue(meta) = &attr.meta {
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(doc), ..
+            }) = &meta.value
+            {
+                let line = doc.value().trim().to_string();
+                if line.is_empty() && !lines.is_empty() {
+                    break;
+                }
+                if !line.is_empty() {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+/// Attributes declared on a struct field
+#[derive(Default)]
+struct FieldAttrs {
+    subcommand: bool,
+    flatten: bool,
+    default: Option<LitStr>,
+    env: Option<LitStr>,
+}
+
+impl FieldAttrs {
+    /// Parses the `#[command(...)]` and `#[args(...)]` attributes of a field
+    fn parse(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+        let mut out = FieldAttrs::default();
+        for attr in attrs {
+            if attr.path().is_ident("command") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("subcommand") {
+                        out.subcommand = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown command attribute"))
+                    }
+                })?;
+            } else if attr.path().is_ident("args") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("flatten") {
+                        out.flatten = true;
+                        Ok(())
+                    } else if meta.path.is_ident("default") {
+                        out.default = Some(meta.value()?.parse()?);
+                        Ok(())
+                    } else if meta.path.is_ident("env") {
+                        out.env = Some(meta.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown args attribute"))
+                    }
+                })?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn field_kind(field: &Field, attrs: &FieldAttrs) -> syn::Result<Kind> {
+    if attrs.subcommand && attrs.flatten {
+        return Err(syn::Error::new_spanned(
+            field,
+            "a field cannot be both a subcommand and flattened",
+        ));
+    }
+    if (attrs.subcommand || attrs.flatten) && (attrs.default.is_some() || attrs.env.is_some()) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "subcommand and flattened fields cannot have a default or env fallback",
+        ));
+    }
+    if attrs.subcommand {
+        return Ok(Kind::Subcommand);
+    }
+    if attrs.flatten {
+        return Ok(Kind::Flatten);
+    }
+    Ok(match type_name(&field.ty).as_deref() {
+        Some("bool") => Kind::Flag,
+        Some("Option") => Kind::Optional,
+        Some("Vec") => Kind::Multiple,
+        _ => Kind::Required,
+    })
+}
+
+fn expand_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<TokenStream2> {
+    let fields = match &data.fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "SimpleArgs can only be derived for structs with named fields",
+            ))
+        }
+    };
+    let mut specs = Vec::new();
+    let mut inits = Vec::new();
+    let mut subcommand = None;
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let key = kebab_from_snake(&ident.to_string());
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let kind = field_kind(field, &attrs)?;
+        let mut arg = quote!(::simple_args::ArgSpec::new(#key));
+        if let Kind::Flag = kind {
+            arg = quote!(#arg.flag());
+        }
+        if let Some(default) = &attrs.default {
+            arg = quote!(#arg.default_value(#default));
+        }
+        if let Some(env) = &attrs.env {
+            arg = quote!(#arg.env(#env));
+        }
+        if let Some(doc) = doc_comment(&field.attrs) {
+            arg = quote!(#arg.description(#doc));
+        }
+        match kind {
+            Kind::Flag => {
+                specs.push(quote!(.arg(#arg)));
+                inits.push(quote!(#ident: ::simple_args::__private::flag(&parsed, #key)));
+            }
+            Kind::Optional => {
+                specs.push(quote!(.arg(#arg)));
+                inits.push(quote!(#ident: ::simple_args::__private::optional(&parsed, #key)?));
+            }
+            Kind::Multiple => {
+                specs.push(quote!(.arg(#arg)));
+                inits.push(quote!(#ident: ::simple_args::__private::multiple(&parsed, #key)?));
+            }
+            Kind::Required => {
+                specs.push(quote!(.arg(#arg)));
+                inits.push(quote!(#ident: ::simple_args::__private::required(&parsed, #key)?));
+            }
+            Kind::Flatten => {
+                specs.push(quote! {
+                    .args_from(&<#ty as ::simple_args::SimpleArgs>::spec())
+                });
+                inits.push(quote! {
+                    #ident: <#ty as ::simple_args::SimpleArgs>::from_args_with(args, &lookup)?
+                });
+            }
+            Kind::Subcommand => {
+                if subcommand.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "only one field can hold the subcommand",
+                    ));
+                }
+                inits.push(quote! {
+                    #ident: <#ty as ::simple_args::SimpleArgs>::from_args_with(rest, &lookup)?
+                });
+                subcommand = Some(ty);
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (spec, split) = match subcommand {
+        Some(ty) => (
+            quote! {
+                ::simple_args::__private::with_subcommands(
+                    spec,
+                    <#ty as ::simple_args::SimpleArgs>::spec(),
+                )
+            },
+            quote! {
+                let (args, rest) = spec.split_at_subcommand(args);
+            },
+        ),
+        None => (quote!(spec), quote!()),
+    };
+    if let Some(doc) = doc_comment(&input.attrs) {
+        specs.insert(0, quote!(.description(#doc)));
+    }
+    Ok(quote! {
+        impl #impl_generics ::simple_args::SimpleArgs for #name #ty_generics #where_clause {
+            fn spec() -> ::simple_args::Spec {
+                let spec = ::simple_args::Spec::new() #(#specs)*;
+                #spec
+            }
+
+            fn from_args_with<S, F>(
+                args: &[S],
+                lookup: F,
+            ) -> ::std::result::Result<Self, ::simple_args::FromArgsError>
+            where
+                S: ::std::convert::AsRef<str>,
+                F: ::std::ops::Fn(&str) -> ::std::option::Option<::std::string::String>,
+            {
+                let spec = <Self as ::simple_args::SimpleArgs>::spec();
+                #split
+                let mut parsed = ::simple_args::Arguments::parse(args);
+                spec.apply_fallbacks_with(&mut parsed, &lookup);
+                ::std::result::Result::Ok(#name { #(#inits,)* })
+            }
+        }
+    })
+}
+
+fn expand_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let mut subcommands = Vec::new();
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let sub = kebab_from_pascal(&ident.to_string());
+        let description = doc_comment(&variant.attrs).map(|doc| quote!(.description(#doc)));
+        match &variant.fields {
+            Fields::Unit => {
+                subcommands.push(quote! {
+                    .subcommand(::simple_args::Subcommand::new(#sub) #description)
+                });
+                arms.push(quote!(#sub => ::std::result::Result::Ok(#name::#ident)));
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                subcommands.push(quote! {
+                    .subcommand(
+                        ::simple_args::Subcommand::new(#sub)
+                            .spec(<#ty as ::simple_args::SimpleArgs>::spec())
+                            #description,
+                    )
+                });
+                arms.push(quote! {
+                    #sub => ::std::result::Result::Ok(#name::#ident(
+                        <#ty as ::simple_args::SimpleArgs>::from_args_with(rest, &lookup)?,
+                    ))
+                });
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "subcommand variants must be unit variants or hold a single SimpleArgs struct",
+                ))
+            }
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::simple_args::SimpleArgs for #name #ty_generics #where_clause {
+            fn spec() -> ::simple_args::Spec {
+                ::simple_args::Spec::new() #(#subcommands)*
+            }
+
+            fn from_args_with<S, F>(
+                args: &[S],
+                lookup: F,
+            ) -> ::std::result::Result<Self, ::simple_args::FromArgsError>
+            where
+                S: ::std::convert::AsRef<str>,
+                F: ::std::ops::Fn(&str) -> ::std::option::Option<::std::string::String>,
+            {
+                let spec = <Self as ::simple_args::SimpleArgs>::spec();
+                let (sub, rest) = spec.resolve_subcommand(args)?;
+                match sub.name() {
+                    #(#arms,)*
+                    _ => ::std::unreachable!(),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{doc_comment, kebab_from_pascal, kebab_from_snake};
+    use syn::{parse_quote, DeriveInput};
+
+    #[test]
+    fn kebab_case() {
+        assert_eq!(kebab_from_snake("dry_run"), "dry-run");
+        assert_eq!(kebab_from_snake("r#type"), "type");
+        assert_eq!(kebab_from_pascal("Status"), "status");
+        assert_eq!(kebab_from_pascal("DryRun"), "dry-run");
+    }
+
+    #[test]
+    fn doc_paragraph() {
+        let input: DeriveInput = parse_quote! {
+            /// Port to listen on,
+            ///   defaulting to 8080
+            ///
+            /// Details that stay out of help
+            struct Opts;
+        };
+        assert_eq!(
+            doc_comment(&input.attrs).as_deref(),
+            Some("Port to listen on, defaulting to 8080")
+        );
+        let input: DeriveInput = parse_quote!(
+            struct Opts;
+        );
+        assert!(doc_comment(&input.attrs).is_none());
+    }
+}
@@ -0,0 +1,211 @@
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::{slice, str};
+
+use crate::Arguments;
+
+/// Version of the layout of `ArgsVTable`, bumped on any change so
+/// plugins can refuse handles they do not understand
+pub const ABI_VERSION: u32 = 1;
+
+/// The key or argument is absent
+const ABSENT: u8 = 0;
+/// The argument is present without a value
+const NO_VALUE: u8 = 1;
+/// The argument is present with a value
+const VALUE: u8 = 2;
+
+/// A borrowed UTF-8 string with a C-compatible layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StrRef {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl StrRef {
+    fn new(s: &str) -> StrRef {
+        StrRef {
+            ptr: s.as_ptr(),
+            len: s.len(),
+        }
+    }
+
+    /// Borrows the string back
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and point to `len` initialized bytes of
+    /// valid UTF-8 in a single allocation, which are neither freed nor
+    /// mutated for `'a`. This holds when the reference was created by
+    /// `StrRef::new` from a `&'a str`
+    unsafe fn as_str<'a>(self) -> &'a str {
+        // SAFETY: the caller guarantees `ptr` and `len` describe live,
+        // valid UTF-8 for `'a`
+        str::from_utf8_unchecked(slice::from_raw_parts(self.ptr, self.len))
+    }
+}
+
+/// Table of functions implementing the read API behind `ArgsHandle`,
+/// with a C-compatible layout and calling convention
+#[repr(C)]
+pub struct ArgsVTable {
+    /// The layout version, equal to `ABI_VERSION`
+    pub version: u32,
+    count: unsafe extern "C" fn(*const c_void, StrRef) -> usize,
+    value: unsafe extern "C" fn(*const c_void, StrRef, usize, *mut StrRef) -> u8,
+}
+
+/// Counts the occurrences of an argument
+///
+/// # Safety
+///
+/// `ctx` must point to an `Arguments` that stays alive and unmodified
+/// for the call, as `Arguments::as_handle` guarantees, and `key` must
+/// satisfy `StrRef::as_str` for the call
+unsafe extern "C" fn count(ctx: *const c_void, key: StrRef) -> usize {
+    // SAFETY: the caller guarantees `ctx` points to a live `Arguments`
+    let args = &*(ctx as *const Arguments);
+    // SAFETY: the caller guarantees `key` is valid for the call, and it
+    // is not kept past it
+    args.get_vec(key.as_str()).map_or(0, Vec::len)
+}
+
+/// Writes the value of an occurrence of an argument to `out` and
+/// returns whether it is absent, present without a value or present
+/// with one
+///
+/// # Safety
+///
+/// `ctx` and `key` must satisfy the requirements of `count`, and `out`
+/// must be non-null, aligned and valid for writes. A value written to
+/// `out` borrows from the `Arguments` and is only valid as long as the
+/// `Arguments` are alive and unmodified
+unsafe extern "C" fn value(ctx: *const c_void, key: StrRef, index: usize, out: *mut StrRef) -> u8 {
+    // SAFETY: the caller guarantees `ctx` points to a live `Arguments`
+    let args = &*(ctx as *const Arguments);
+    // SAFETY: the caller guarantees `key` is valid for the call, and it
+    // is not kept past it
+    match args.get_vec(key.as_str()).and_then(|vals| vals.get(index)) {
+        None => ABSENT,
+        Some(None) => NO_VALUE,
+        Some(Some(val)) => {
+            // SAFETY: the caller guarantees `out` is valid for writes,
+            // and `StrRef` is `Copy`, so nothing is dropped
+            *out = StrRef::new(val);
+            VALUE
+        }
+    }
+}
+
+static VTABLE: ArgsVTable = ArgsVTable {
+    version: ABI_VERSION,
+    count,
+    value,
+};
+
+/// An opaque handle to arguments with a C-compatible layout, for
+/// applications handing arguments to plugins loaded as separately
+/// compiled `cdylib`s. It only relies on its own `repr(C)` layout, so
+/// it stays valid across compiler versions, and borrows the arguments
+/// for its lifetime
+//
+// Handles can only be created by `Arguments::as_handle`, so `ctx`
+// always points to `Arguments` borrowed for `'a` and `vtable` to
+// `VTABLE`, which the unsafe calls of the methods rely on
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ArgsHandle<'a> {
+    ctx: *const c_void,
+    vtable: &'a ArgsVTable,
+    marker: PhantomData<&'a Arguments>,
+}
+
+impl<'a> ArgsHandle<'a> {
+    /// Returns the layout version of the table behind the handle
+    pub fn version(&self) -> u32 {
+        self.vtable.version
+    }
+
+    /// Returns how many times an argument is present
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to count
+    pub fn count(&self, key: &str) -> usize {
+        // SAFETY: handles are only created by `Arguments::as_handle`, so
+        // `ctx` points to `Arguments` borrowed for `'a` and `vtable` is
+        // `VTABLE`, and `key` is a live `&str` for the call
+        unsafe { (self.vtable.count)(self.ctx, StrRef::new(key)) }
+    }
+
+    /// Checks whether or not an argument is present
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn contains(&self, key: &str) -> bool {
+        self.count(key) > 0
+    }
+
+    /// Gets the value of an occurrence of an argument, like indexing
+    /// into `Arguments::get_vec`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    ///
+    /// `index`: The occurrence to fetch
+    pub fn get_nth(&self, key: &str, index: usize) -> Option<Option<&'a str>> {
+        let mut out = StrRef::new("");
+        // SAFETY: `ctx`, `vtable` and `key` are valid as in `count`, and
+        // `out` is a local the call may write to
+        match unsafe { (self.vtable.value)(self.ctx, StrRef::new(key), index, &mut out) } {
+            NO_VALUE => Some(None),
+            // SAFETY: `value` set `out` to a value of the `Arguments`,
+            // which are borrowed immutably for `'a`
+            VALUE => Some(Some(unsafe { out.as_str() })),
+            _ => None,
+        }
+    }
+
+    /// Gets the first value with the given key, like `Arguments::get`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get(&self, key: &str) -> Option<Option<&'a str>> {
+        self.get_nth(key, 0)
+    }
+}
+
+impl Arguments {
+    /// Creates an opaque handle to the arguments for plugins compiled
+    /// separately from the application
+    pub fn as_handle(&self) -> ArgsHandle<'_> {
+        ArgsHandle {
+            ctx: self as *const Arguments as *const c_void,
+            vtable: &VTABLE,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ABI_VERSION;
+    use crate::Arguments;
+
+    #[test]
+    fn handle() {
+        let args = Arguments::parse(&["-name", "a", "-name", "-verbose"]);
+        let handle = args.as_handle();
+        assert_eq!(handle.version(), ABI_VERSION);
+        assert_eq!(handle.count("name"), 2);
+        assert_eq!(handle.get("name"), Some(Some("a")));
+        assert_eq!(handle.get_nth("name", 1), Some(None));
+        assert_eq!(handle.get_nth("name", 2), None);
+        assert!(handle.contains("verbose"));
+        assert!(!handle.contains("missing"));
+    }
+}
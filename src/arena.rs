@@ -0,0 +1,166 @@
+use bumpalo::collections::Vec as ArenaVec;
+use bumpalo::Bump;
+
+use crate::{strip_key, Arguments, Source};
+
+/// Parsed arguments whose keys and values live in an arena, so that
+/// many short-lived parses, such as when replaying logs of command
+/// lines, are freed at once by resetting the arena instead of string by
+/// string. Parsing follows `Arguments::parse`, including stopping at
+/// `--`, except that positionals are not kept
+pub struct ArenaArguments<'a> {
+    entries: ArenaVec<'a, (&'a str, Option<&'a str>)>,
+    trailing: ArenaVec<'a, &'a str>,
+}
+
+impl<'a> ArenaArguments<'a> {
+    /// Checks whether or not an argument is present in the list
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.iter().any(|&(k, _)| k == key)
+    }
+
+    /// Checks whether or not an argument is present in the list
+    /// with a non-empty value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn contains_val(&self, key: &str) -> bool {
+        self.get_all(key).any(|val| val.is_some())
+    }
+
+    /// Checks whether or not the arguments are empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Gets the first value with the given key
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get(&self, key: &str) -> Option<Option<&'a str>> {
+        self.get_all(key).next()
+    }
+
+    /// Gets all values with the given key in order, like
+    /// `Arguments::get_vec`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_all<'k>(&'k self, key: &'k str) -> impl Iterator<Item = Option<&'a str>> + 'k {
+        self.entries
+            .iter()
+            .filter(move |&&(k, _)| k == key)
+            .map(|&(_, val)| val)
+    }
+
+    /// Returns the number of distinct keys that were parsed
+    pub fn len(&self) -> usize {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|&(i, &(key, _))| !self.entries[..i].iter().any(|&(k, _)| k == key))
+            .count()
+    }
+
+    /// Returns the arguments following `--`, like `Arguments::trailing`
+    pub fn trailing(&self) -> &[&'a str] {
+        &self.trailing
+    }
+
+    /// Copies the arguments out of the arena
+    pub fn to_arguments(&self) -> Arguments {
+        let mut args = Arguments::empty();
+        for &(key, val) in self.entries.iter() {
+            args.insert(key, val.map(str::to_string), Source::CommandLine);
+        }
+        args.trailing = self.trailing.iter().map(|arg| arg.to_string()).collect();
+        args
+    }
+}
+
+impl Arguments {
+    /// Parse arguments like `parse`, allocating every key, value and
+    /// the list holding them in an arena
+    ///
+    /// # Arguments
+    ///
+    /// `arena`: The arena to allocate in
+    ///
+    /// `args`: The arguments
+    pub fn parse_in<'a, S: AsRef<str>>(arena: &'a Bump, args: &[S]) -> ArenaArguments<'a> {
+        let mut entries = ArenaVec::new_in(arena);
+        let mut trailing = ArenaVec::new_in(arena);
+        for (i, key) in args.iter().map(|s| s.as_ref()).enumerate() {
+            if key == "--" {
+                for arg in &args[i + 1..] {
+                    trailing.push(&*arena.alloc_str(arg.as_ref()));
+                }
+                break;
+            }
+            let key = match strip_key(key) {
+                Some(key) => key,
+                None => continue,
+            };
+            let (key, val) = match key.split_once('=') {
+                Some((key, attached)) => (key, Some(attached)),
+                None => (
+                    key,
+                    args.get(i + 1)
+                        .map(|val| val.as_ref())
+                        .filter(|val| !val.is_empty() && !val.starts_with('-')),
+                ),
+            };
+            let val = val.map(|val| &*arena.alloc_str(val));
+            entries.push((&*arena.alloc_str(key), val));
+        }
+        ArenaArguments { entries, trailing }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Arena, Arguments};
+
+    #[test]
+    fn arena_parse() {
+        let mut arena = Arena::new();
+        for _ in 0..3 {
+            let args = Arguments::parse_in(&arena, &["-key", "val", "-key", "-flag"]);
+            assert_eq!(args.get("key"), Some(Some("val")));
+            assert_eq!(args.get_all("key").collect::<Vec<_>>(), [Some("val"), None]);
+            assert!(args.contains("flag"));
+            assert!(!args.contains_val("flag"));
+            assert_eq!(args.len(), 2);
+            assert_eq!(args.to_arguments().get_vec("key").unwrap().len(), 2);
+            drop(args);
+            arena.reset();
+        }
+    }
+
+    #[test]
+    fn agrees_with_parse() {
+        let arena = Arena::new();
+        let tokens = ["-a", "1", "-b=2", "-a", "--", "-c", "x"];
+        let args = Arguments::parse(&tokens);
+        let in_arena = Arguments::parse_in(&arena, &tokens);
+        for key in ["a", "b", "c", "-"] {
+            let vals: Vec<Option<&str>> = args
+                .get_vec(key)
+                .into_iter()
+                .flatten()
+                .map(Option::as_deref)
+                .collect();
+            assert_eq!(in_arena.get_all(key).collect::<Vec<_>>(), vals);
+        }
+        assert_eq!(in_arena.len(), args.len());
+        assert_eq!(in_arena.trailing(), args.trailing());
+        assert_eq!(in_arena.to_arguments().trailing(), ["-c", "x"]);
+    }
+}
@@ -0,0 +1,185 @@
+use std::borrow::Cow;
+
+use crate::Arguments;
+
+/// What `Arguments::audit_line` hides and how long the line may get
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditPolicy {
+    redacted: Vec<Cow<'static, str>>,
+    max_len: usize,
+}
+
+impl AuditPolicy {
+    /// Creates a policy that redacts nothing and allows lines of any
+    /// length
+    pub fn new() -> AuditPolicy {
+        AuditPolicy {
+            redacted: Vec::new(),
+            max_len: usize::MAX,
+        }
+    }
+
+    /// Hides the values of an argument, such as a password, behind
+    /// `***`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    pub fn redact<K: Into<Cow<'static, str>>>(mut self, key: K) -> AuditPolicy {
+        self.redacted.push(key.into());
+        self
+    }
+
+    /// Sets the longest line in characters, beyond which it is cut off
+    /// and ends with `...`
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The longest line
+    pub fn max_len(mut self, max: usize) -> AuditPolicy {
+        self.max_len = max;
+        self
+    }
+
+    /// Checks whether the values of an argument are hidden
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    pub fn is_redacted(&self, key: &str) -> bool {
+        self.redacted.iter().any(|redacted| redacted == key)
+    }
+
+    /// Checks whether the values of an argument are hidden, resolving
+    /// the key and the redacted keys to their declared names so an
+    /// alias is redacted along with its argument
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `canonical`: Returns the declared name of a key or alias
+    pub(crate) fn is_redacted_as<F: Fn(&str) -> &str>(&self, key: &str, canonical: F) -> bool {
+        let key = canonical(key);
+        self.redacted
+            .iter()
+            .any(|redacted| canonical(redacted) == key)
+    }
+
+    /// Returns the longest line in characters
+    pub fn get_max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl Default for AuditPolicy {
+    fn default() -> AuditPolicy {
+        AuditPolicy::new()
+    }
+}
+
+/// Quotes a token for an audit line when it is empty or contains
+/// whitespace, quotes or control characters, escaping the latter so the
+/// line never breaks
+fn audit_token(out: &mut String, token: &str) {
+    if !token.is_empty()
+        && !token
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '"' || c == '\\')
+    {
+        out.push_str(token);
+        return;
+    }
+    out.push('"');
+    for c in token.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => out.extend(c.escape_default()),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl Arguments {
+    /// Renders the arguments as a single line for security audit logs,
+    /// with keys sorted, each occurrence written as `-key` or
+    /// `-key=value`, and the positionals at the end. Values are quoted
+    /// and escaped when needed, redacted values are written as `***`,
+    /// and the line is cut off at the longest length of the policy
+    ///
+    /// # Arguments
+    ///
+    /// `policy`: What to redact and how long the line may get
+    pub fn audit_line(&self, policy: &AuditPolicy) -> String {
+        let mut keys: Vec<&str> = self.arg_map.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        let mut line = String::new();
+        for key in keys {
+            for val in self.get_vec(key).into_iter().flatten() {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push('-');
+                audit_token(&mut line, key);
+                match val {
+                    Some(_) if policy.is_redacted_as(key, |key| self.canonical(key)) => {
+                        line.push_str("=***")
+                    }
+                    Some(val) => {
+                        line.push('=');
+                        audit_token(&mut line, val);
+                    }
+                    None => {}
+                }
+            }
+        }
+        for positional in self.positionals() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            audit_token(&mut line, positional);
+        }
+        if line.chars().count() > policy.get_max_len() {
+            let keep = policy.get_max_len().saturating_sub(3);
+            line = line.chars().take(keep).collect();
+            line.push_str("...");
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AuditPolicy;
+    use crate::{ArgSpec, Spec};
+
+    #[test]
+    fn audit_line() {
+        let args = Spec::new().parse(&[
+            "-user",
+            "alice",
+            "-password",
+            "hunter2",
+            "-note",
+            "two\nlines",
+            "-force",
+            "target",
+        ]);
+        let policy = AuditPolicy::new().redact("password");
+        assert_eq!(
+            args.audit_line(&policy),
+            "-force=target -note=\"two\\nlines\" -password=*** -user=alice"
+        );
+        assert_eq!(args.audit_line(&policy.max_len(16)), "-force=target...");
+        let spec = Spec::new().arg(ArgSpec::new("password").aliases(&["p"]));
+        let args = spec.parse(&["-p", "secret"]);
+        assert_eq!(
+            args.audit_line(&AuditPolicy::new().redact("p")),
+            "-password=***"
+        );
+    }
+}
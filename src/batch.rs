@@ -0,0 +1,154 @@
+use std::io::{self, BufRead};
+
+use crate::{Arguments, Spec};
+
+/// Splits a line into words at whitespace, keeping whitespace inside
+/// single or double quotes and after a backslash
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', q) if q != Some('\'') => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.push(c),
+            ('"', None) | ('\'', None) => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (c, None) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (c, None) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Accumulates arguments parsed from many commands, one per line, as
+/// read from a batch file or standard input. Every line is kept as its
+/// own `Arguments`, and an aggregate holds the values of every line in
+/// order
+pub struct ArgumentsAccumulator {
+    spec: Spec,
+    lines: Vec<Arguments>,
+    aggregate: Arguments,
+}
+
+impl ArgumentsAccumulator {
+    /// Creates an accumulator parsing lines with an empty specification
+    pub fn new() -> ArgumentsAccumulator {
+        ArgumentsAccumulator::with_spec(Spec::new())
+    }
+
+    /// Creates an accumulator parsing lines with a specification
+    ///
+    /// # Arguments
+    ///
+    /// `spec`: The specification lines are parsed with
+    pub fn with_spec(spec: Spec) -> ArgumentsAccumulator {
+        ArgumentsAccumulator {
+            spec,
+            lines: Vec::new(),
+            aggregate: Arguments::empty(),
+        }
+    }
+
+    /// Parses a line and adds it, returning its arguments. Words are
+    /// split at whitespace, honoring quotes and backslashes. Blank lines
+    /// and lines starting with `#` are skipped and return `None`
+    ///
+    /// # Arguments
+    ///
+    /// `line`: The command line to add
+    pub fn push_line(&mut self, line: &str) -> Option<&Arguments> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let args = self.spec.parse(&split_words(line));
+        for (key, _) in args.arg_map.iter() {
+            for (val, source) in args.values_with_sources(key) {
+                self.aggregate
+                    .insert(key, val.map(str::to_string), source.clone());
+            }
+        }
+        self.aggregate
+            .positionals
+            .extend(args.positionals().iter().cloned());
+        self.lines.push(args);
+        self.lines.last()
+    }
+
+    /// Reads and adds every line of a reader
+    ///
+    /// # Arguments
+    ///
+    /// `reader`: The reader to read lines from
+    pub fn read_lines<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        for line in reader.lines() {
+            self.push_line(&line?);
+        }
+        Ok(())
+    }
+
+    /// Returns the arguments of every added line, in order
+    pub fn lines(&self) -> &[Arguments] {
+        &self.lines
+    }
+
+    /// Returns the arguments of every added line combined, with values
+    /// in the order of their lines
+    pub fn aggregate(&self) -> &Arguments {
+        &self.aggregate
+    }
+}
+
+impl Default for ArgumentsAccumulator {
+    fn default() -> ArgumentsAccumulator {
+        ArgumentsAccumulator::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_words, ArgumentsAccumulator};
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            split_words(r#"-name "John Smith" -path a\ b 'it''s' """#),
+            ["-name", "John Smith", "-path", "a b", "its", ""]
+        );
+    }
+
+    #[test]
+    fn accumulate() {
+        let mut batch = ArgumentsAccumulator::new();
+        let input = "-user alice -id 1\n\n# comment\n-user bob file.txt\n";
+        batch.read_lines(input.as_bytes()).unwrap();
+        assert_eq!(batch.lines().len(), 2);
+        assert_eq!(batch.lines()[1].get("user"), Some(Some("bob")));
+        assert!(!batch.lines()[1].contains("id"));
+        assert_eq!(
+            batch.aggregate().get_vec("user").unwrap(),
+            &vec![Some("alice".to_string()), Some("bob".to_string())]
+        );
+        assert_eq!(batch.aggregate().positionals(), ["file.txt"]);
+    }
+}
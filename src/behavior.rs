@@ -0,0 +1,69 @@
+use crate::{Arguments, Spec};
+
+/// Versions of the parsing behavior, so improvements that change how
+/// arguments are read can ship without breaking existing programs.
+/// Programs opt into newer behavior explicitly. Versioning starts with
+/// `V1`, and changes made to `Arguments::parse` before later versions
+/// existed, such as recording positionals and stopping at `--`, are
+/// part of `V1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Behavior {
+    /// The behavior of `Arguments::parse`, where operands are kept as
+    /// positionals and the arguments following `--` are only available
+    /// from `Arguments::trailing`. This is not the behavior of releases
+    /// before versioning, which read `--` as the key `-` and every
+    /// token after it as keys and values
+    #[default]
+    V1,
+    /// The arguments following `--` are also kept as positionals
+    V2,
+    /// Negative numbers such as `-5` following a key are read as its
+    /// value rather than as keys
+    V3,
+}
+
+impl Arguments {
+    /// Parse arguments following a version of the parsing behavior.
+    /// `Behavior::V1` is the same as `parse` as it is now, and later
+    /// versions add their changes on top of it
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    ///
+    /// `behavior`: The version of the parsing behavior
+    pub fn parse_with_behavior<S: AsRef<str>>(args: &[S], behavior: Behavior) -> Arguments {
+        match behavior {
+            Behavior::V1 => Arguments::parse(args),
+            _ => Spec::new().behavior(behavior).parse(args),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Behavior;
+    use crate::Arguments;
+
+    #[test]
+    fn versions() {
+        let tokens = ["-level", "1", "in.txt", "--", "-x"];
+        let v1 = Arguments::parse_with_behavior(&tokens, Behavior::V1);
+        assert_eq!(v1.positionals(), ["in.txt"]);
+        assert_eq!(v1.trailing(), ["-x"]);
+        assert!(!v1.contains("x"));
+        let v2 = Arguments::parse_with_behavior(&tokens, Behavior::V2);
+        assert_eq!(v2.get("level"), Some(Some("1")));
+        assert_eq!(v2.positionals(), ["in.txt", "-x"]);
+        assert!(!v2.contains("x"));
+        assert!(!v2.contains("-"));
+        let tokens = ["-offset", "-5", "-scale", "-0.5"];
+        let v2 = Arguments::parse_with_behavior(&tokens, Behavior::V2);
+        assert_eq!(v2.get("offset"), Some(None));
+        assert!(v2.contains("5"));
+        let v3 = Arguments::parse_with_behavior(&tokens, Behavior::V3);
+        assert_eq!(v3.get("offset"), Some(Some("-5")));
+        assert_eq!(v3.get("scale"), Some(Some("-0.5")));
+        assert_eq!(v3.len(), 2);
+    }
+}
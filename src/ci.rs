@@ -0,0 +1,83 @@
+use crate::{Arguments, Environment, Spec};
+
+/// The key of the switch activating the continuous integration profile
+/// outside of continuous integration
+pub const CI_SWITCH: &str = "ci";
+
+impl Spec {
+    /// Checks whether the continuous integration profile is active,
+    /// which is when the environment is detected as continuous
+    /// integration or `-ci` is given
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    ///
+    /// `environment`: The environment the program runs under
+    pub fn is_ci(&self, args: &Arguments, environment: &Environment) -> bool {
+        environment.ci || args.contains(CI_SWITCH)
+    }
+
+    /// Checks whether the specification declares continuous integration
+    /// defaults, in which case the `-ci` switch is implicitly declared
+    pub(crate) fn has_ci_defaults(&self) -> bool {
+        self.args().iter().any(|arg| arg.get_ci_default().is_some())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Environment, Spec};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(
+                ArgSpec::new("color")
+                    .default_value("auto")
+                    .ci_default("never"),
+            )
+            .arg(
+                ArgSpec::new("prompt")
+                    .flag()
+                    .default_value("1")
+                    .ci_default("0"),
+            )
+            .arg(
+                ArgSpec::new("format")
+                    .default_value("text")
+                    .ci_default("json"),
+            )
+            .arg(ArgSpec::new("jobs").default_value("4"))
+    }
+
+    #[test]
+    fn profile() {
+        let spec = spec();
+        let ci = Environment {
+            ci: true,
+            ..Environment::default()
+        };
+        let mut args = spec.parse(&["-format", "text"]);
+        assert!(spec.is_ci(&args, &ci));
+        spec.apply_fallbacks_in(&mut args, &ci);
+        assert_eq!(args.get("color"), Some(Some("never")));
+        assert!(!args.contains("prompt"));
+        assert_eq!(args.get("format"), Some(Some("text")));
+        assert_eq!(args.get("jobs"), Some(Some("4")));
+        let local = Environment::default();
+        let mut args = spec.parse::<&str>(&[]);
+        assert!(!spec.is_ci(&args, &local));
+        spec.apply_fallbacks_in(&mut args, &local);
+        assert_eq!(args.get("color"), Some(Some("auto")));
+        assert!(args.contains("prompt"));
+        let mut args = spec.parse(&["-ci"]);
+        assert!(spec.is_ci(&args, &local));
+        spec.apply_fallbacks_in(&mut args, &local);
+        assert_eq!(args.get("format"), Some(Some("json")));
+        assert!(spec.validate(&args).is_ok());
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec.apply_defaults(&mut args);
+        assert_eq!(args.get("color"), Some(Some("auto")));
+        assert!(Spec::new().validate(&Spec::new().parse(&["-ci"])).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{Arguments, Drift, Spec};
+
+/// Error returned when a sticky argument changed since a previously
+/// persisted run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncompatibleError {
+    /// The key of the argument that changed
+    pub key: String,
+    /// The values of the argument in the persisted run
+    pub saved: Vec<Option<String>>,
+    /// The values of the argument in the current run
+    pub current: Vec<Option<String>>,
+}
+
+/// Formats values as a quoted, comma-separated list, or `nothing` when
+/// the argument was absent
+fn describe(vals: &[Option<String>]) -> String {
+    if vals.is_empty() {
+        return String::from("nothing");
+    }
+    let vals: Vec<String> = vals
+        .iter()
+        .map(|val| format!("'{}'", val.as_deref().unwrap_or("")))
+        .collect();
+    vals.join(", ")
+}
+
+impl fmt::Display for IncompatibleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "-{} changed from {} to {} since the previous run",
+            self.key,
+            describe(&self.saved),
+            describe(&self.current)
+        )
+    }
+}
+
+impl Error for IncompatibleError {}
+
+/// A change to a specification that can break existing invocations
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakingChange {
+    /// An argument was removed or renamed, so invocations using it are
+    /// no longer understood
+    Removed(String),
+    /// An argument changed between a flag and taking a value
+    Kind { name: String, was_flag: bool },
+    /// The declared type of the value changed, so values accepted
+    /// before may be rejected
+    Type {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// The default value changed, so invocations omitting the argument
+    /// behave differently
+    Default {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let drift = match self.clone() {
+            BreakingChange::Removed(name) => Drift::Removed(name),
+            BreakingChange::Kind { name, was_flag } => Drift::Kind { name, was_flag },
+            BreakingChange::Type { name, from, to } => Drift::Type { name, from, to },
+            BreakingChange::Default { name, from, to } => Drift::Default { name, from, to },
+        };
+        drift.fmt(f)
+    }
+}
+
+impl Spec {
+    /// Lists the changes from an older version of this specification
+    /// that can break existing invocations, so releases can be gated on
+    /// command line compatibility. Added arguments are compatible, while
+    /// renamed arguments count as removed
+    ///
+    /// # Arguments
+    ///
+    /// `old`: The older version of the specification
+    pub fn check_compat(&self, old: &Spec) -> Vec<BreakingChange> {
+        old.diff(self)
+            .into_iter()
+            .filter_map(|drift| match drift {
+                Drift::Added(_) => None,
+                Drift::Removed(name) | Drift::Renamed { from: name, .. } => {
+                    Some(BreakingChange::Removed(name))
+                }
+                Drift::Kind { name, was_flag } => Some(BreakingChange::Kind { name, was_flag }),
+                Drift::Type { name, from, to } => Some(BreakingChange::Type { name, from, to }),
+                Drift::Default { name, from, to } => {
+                    Some(BreakingChange::Default { name, from, to })
+                }
+            })
+            .collect()
+    }
+}
+
+impl Arguments {
+    /// Checks the arguments against those persisted from a previous run,
+    /// failing on the first sticky argument of the specification whose
+    /// values differ. Values are compared in order, and an argument
+    /// present in only one of the runs counts as changed
+    ///
+    /// # Arguments
+    ///
+    /// `saved`: The arguments of the previous run
+    ///
+    /// `spec`: The specification declaring which arguments are sticky
+    pub fn check_compatible(
+        &self,
+        saved: &Arguments,
+        spec: &Spec,
+    ) -> Result<(), IncompatibleError> {
+        for arg in spec.args().iter().filter(|arg| arg.is_sticky()) {
+            let saved = saved.get_vec(arg.name()).cloned().unwrap_or_default();
+            let current = self.get_vec(arg.name()).cloned().unwrap_or_default();
+            if saved != current {
+                return Err(IncompatibleError {
+                    key: arg.name().to_string(),
+                    saved,
+                    current,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, BreakingChange, Spec};
+
+    #[test]
+    fn sticky_keys() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("db").sticky())
+            .arg(ArgSpec::new("threads"));
+        let saved = Arguments::parse(&["-db", "data.db", "-threads", "4"]);
+        let current = Arguments::parse(&["-db", "data.db", "-threads", "8"]);
+        assert!(current.check_compatible(&saved, &spec).is_ok());
+        let current = Arguments::parse(&["-db", "other.db"]);
+        let err = current.check_compatible(&saved, &spec).unwrap_err();
+        assert_eq!(err.key, "db");
+        assert_eq!(
+            err.to_string(),
+            "-db changed from 'data.db' to 'other.db' since the previous run"
+        );
+        let err = Arguments::parse::<&str>(&[])
+            .check_compatible(&saved, &spec)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "-db changed from 'data.db' to nothing since the previous run"
+        );
+    }
+
+    #[test]
+    fn breaking_changes() {
+        let old = Spec::new()
+            .arg(ArgSpec::new("port").value_type("u16"))
+            .arg(ArgSpec::new("jobs").default_value("4"))
+            .arg(ArgSpec::new("color").flag());
+        let new = Spec::new()
+            .arg(ArgSpec::new("port").value_type("u16"))
+            .arg(ArgSpec::new("jobs").default_value("8"))
+            .arg(ArgSpec::new("colour").flag())
+            .arg(ArgSpec::new("quiet").flag());
+        let changes = new.check_compat(&old);
+        assert_eq!(
+            changes,
+            [
+                BreakingChange::Default {
+                    name: "jobs".to_string(),
+                    from: Some("4".to_string()),
+                    to: Some("8".to_string()),
+                },
+                BreakingChange::Removed("color".to_string()),
+            ]
+        );
+        assert_eq!(changes[0].to_string(), "-jobs changed default from 4 to 8");
+        assert!(new.check_compat(&new).is_empty());
+        assert!(new
+            .clone()
+            .arg(ArgSpec::new("extra"))
+            .check_compat(&new)
+            .is_empty());
+    }
+}
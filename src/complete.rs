@@ -0,0 +1,113 @@
+use crate::{strip_key, Spec};
+
+/// The first argument requesting completions instead of a normal run
+pub const COMPLETE_REQUEST: &str = "__complete";
+
+impl Spec {
+    /// Lists the candidates completing the last word of a partial
+    /// command line, which may be empty. After a subcommand, found with
+    /// `split_at_subcommand`, candidates come from its scoped
+    /// specification. A word starting with `-`
+    /// completes to declared keys, a word following a key that takes a
+    /// value completes through the completer of that key, and any other
+    /// word completes to subcommand names
+    ///
+    /// # Arguments
+    ///
+    /// `words`: The words typed after the program name
+    pub fn complete<S: AsRef<str>>(&self, words: &[S]) -> Vec<String> {
+        let (current, before) = match words.split_last() {
+            Some((current, before)) => (current.as_ref(), before),
+            None => ("", &[][..]),
+        };
+        let (globals, rest) = self.split_at_subcommand(before);
+        if let Some((first, _)) = rest.split_first() {
+            if let Some(sub) = self.find_subcommand(first.as_ref()) {
+                return self.scoped(sub).complete(&words[globals.len() + 1..]);
+            }
+        }
+        if let Some(prefix) = strip_key(current) {
+            let dashes = &current[..current.len() - prefix.len()];
+            return self
+                .visible_args()
+                .filter(|arg| arg.name().starts_with(prefix))
+                .map(|arg| format!("{}{}", dashes, arg.name()))
+                .collect();
+        }
+        let previous = before
+            .last()
+            .and_then(|word| strip_key(word.as_ref()))
+            .and_then(|key| self.get(key))
+            .filter(|arg| !arg.is_flag());
+        if let Some(arg) = previous {
+            return arg
+                .get_completer()
+                .map_or_else(Vec::new, |completer| completer(current));
+        }
+        self.subcommands()
+            .iter()
+            .filter(|sub| sub.name().starts_with(current))
+            .map(|sub| sub.name().to_string())
+            .collect()
+    }
+
+    /// Answers a completion request, where shell scripts run the
+    /// program with `__complete` followed by the words typed so far.
+    /// Returns the candidates one per line, or `None` for a normal run
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The raw arguments, excluding the program name
+    pub fn complete_for<S: AsRef<str>>(&self, args: &[S]) -> Option<String> {
+        let (first, words) = args.split_first()?;
+        if first.as_ref() != COMPLETE_REQUEST {
+            return None;
+        }
+        let mut out = String::new();
+        for candidate in self.complete(words) {
+            out.push_str(&candidate);
+            out.push('\n');
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Spec, Subcommand};
+
+    fn profiles(prefix: &str) -> Vec<String> {
+        ["dev", "default", "prod"]
+            .iter()
+            .filter(|profile| profile.starts_with(prefix))
+            .map(|profile| profile.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn completions() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("profile").complete_with(profiles).global())
+            .arg(ArgSpec::new("verbose").flag())
+            .subcommand(Subcommand::new("deploy").arg(ArgSpec::new("region")))
+            .subcommand(Subcommand::new("destroy"));
+        assert_eq!(spec.complete(&["-pro"]), ["-profile"]);
+        assert_eq!(spec.complete(&["-profile", "de"]), ["dev", "default"]);
+        assert_eq!(spec.complete(&["de"]), ["deploy", "destroy"]);
+        assert_eq!(spec.complete(&["deploy", "-"]), ["-region", "-profile"]);
+        assert_eq!(spec.complete(&["deploy", "-profile", "p"]), ["prod"]);
+        assert_eq!(
+            spec.complete(&["-verbose", "deploy", "-"]),
+            ["-region", "-profile"]
+        );
+        assert_eq!(
+            spec.complete(&["-profile", "dev", "deploy", "-r"]),
+            ["-region"]
+        );
+        assert_eq!(
+            spec.complete_for(&["__complete", "-v"]).unwrap(),
+            "-verbose\n"
+        );
+        assert!(spec.complete_for(&["deploy"]).is_none());
+    }
+}
@@ -0,0 +1,375 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+use crate::{Arguments, FileValue, Source};
+
+/// Error returned when a configuration file is not valid TOML or JSON,
+/// wrapped in an `InvalidData` error
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The path of the configuration file
+    pub path: PathBuf,
+    /// The line of the error, starting at 1
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, line {}: {}",
+            self.path.display(),
+            self.line,
+            self.message
+        )
+    }
+}
+
+impl Error for ConfigError {}
+
+/// A value read from a configuration file
+enum Value {
+    Text(String),
+    Bool(bool),
+    Null,
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Arguments {
+    /// Reads the table of a TOML or JSON configuration file, chosen by
+    /// the `.toml` or `.json` extension, attributing every value to the
+    /// file. Nested tables become dotted keys as in `server.port`,
+    /// arrays become repeated values, `true` becomes a flag and `false`
+    /// and `null` are left out. Arrays of tables and duplicate keys are
+    /// rejected. Invalid files fail with an `InvalidData` error
+    /// wrapping `ConfigError`
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the configuration file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Arguments> {
+        let path = path.as_ref();
+        let is_toml = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => true,
+            Some("json") => false,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is neither a .toml nor a .json file", path.display()),
+                ))
+            }
+        };
+        let file = FileValue::read(path)?;
+        let text = file.trimmed();
+        let table = if is_toml { toml(text) } else { json(text) }.map_err(|(line, message)| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                ConfigError {
+                    path: path.to_path_buf(),
+                    line,
+                    message,
+                },
+            )
+        })?;
+        let mut args = Arguments::empty();
+        let source = Source::File(path.to_path_buf());
+        for (key, value) in table {
+            flatten(&mut args, key, value, &source);
+        }
+        Ok(args)
+    }
+
+    /// Merges the values of a configuration file read by `from_file`
+    /// below the arguments, skipping every key already present so keys
+    /// given on the command line shadow the file
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the configuration file
+    pub fn merge_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let file = Arguments::from_file(path)?;
+        self.merge_where(file, |_, present| !present);
+        Ok(())
+    }
+}
+
+/// Adds a value under a key, with nested tables under dotted keys
+fn flatten(args: &mut Arguments, key: String, value: Value, source: &Source) {
+    match value {
+        Value::Text(text) => args.insert(&key, Some(text), source.clone()),
+        Value::Bool(true) => args.insert(&key, None, source.clone()),
+        Value::Bool(false) | Value::Null => {}
+        Value::Array(values) => {
+            for value in values {
+                flatten(args, key.clone(), value, source);
+            }
+        }
+        Value::Table(entries) => {
+            for (inner, value) in entries {
+                flatten(args, format!("{}.{}", key, inner), value, source);
+            }
+        }
+    }
+}
+
+/// An error and the line it is on
+type ReadError = (usize, String);
+
+/// The message for an array holding a table, which has no flat form
+const ARRAY_OF_TABLES: &str = "arrays of tables are not supported";
+
+/// Reads the top-level table of a TOML document
+fn toml(text: &str) -> Result<Vec<(String, Value)>, ReadError> {
+    let line = |offset: usize| text[..offset].matches('\n').count() + 1;
+    let table = DeTable::parse(text).map_err(|err| {
+        let offset = err.span().map_or(0, |span| span.start);
+        (line(offset), err.message().to_string())
+    })?;
+    toml_table(table.into_inner(), &line)
+}
+
+fn toml_table(
+    table: DeTable<'_>,
+    line: &dyn Fn(usize) -> usize,
+) -> Result<Vec<(String, Value)>, ReadError> {
+    table
+        .into_iter()
+        .map(|(key, value)| Ok((key.into_inner().into_owned(), toml_value(value, line)?)))
+        .collect()
+}
+
+fn toml_value(
+    value: Spanned<DeValue<'_>>,
+    line: &dyn Fn(usize) -> usize,
+) -> Result<Value, ReadError> {
+    let start = value.span().start;
+    Ok(match value.into_inner() {
+        DeValue::String(text) => Value::Text(text.into_owned()),
+        DeValue::Integer(number) => match i128::from_str_radix(number.as_str(), number.radix()) {
+            Ok(number) => Value::Text(number.to_string()),
+            Err(_) => return Err((line(start), "integer number overflowed".to_string())),
+        },
+        DeValue::Float(number) => match number.as_str().parse::<f64>() {
+            Ok(number) => Value::Text(number.to_string()),
+            Err(_) => return Err((line(start), "invalid floating-point number".to_string())),
+        },
+        DeValue::Boolean(flag) => Value::Bool(flag),
+        DeValue::Datetime(datetime) => Value::Text(datetime.to_string()),
+        DeValue::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| match value.get_ref() {
+                    DeValue::Table(_) => Err((line(value.span().start), ARRAY_OF_TABLES.into())),
+                    _ => toml_value(value, line),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        DeValue::Table(table) => Value::Table(toml_table(table, line)?),
+    })
+}
+
+/// Reads the top-level object of a JSON document
+fn json(text: &str) -> Result<Vec<(String, Value)>, ReadError> {
+    match serde_json::from_str(text) {
+        Ok(Value::Table(entries)) => Ok(entries),
+        Ok(_) => Err((1, "expected an object".to_string())),
+        Err(err) => {
+            // the message repeats the position after " at line "
+            let message = err.to_string();
+            let message = message
+                .rsplit_once(" at line ")
+                .map_or(&*message, |(m, _)| m);
+            Err((err.line().max(1), message.to_string()))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Builds a `Value` from a JSON document, rejecting what `flatten`
+/// cannot represent while the position is still known
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, flag: bool) -> Result<Value, E> {
+        Ok(Value::Bool(flag))
+    }
+
+    fn visit_i64<E>(self, number: i64) -> Result<Value, E> {
+        Ok(Value::Text(number.to_string()))
+    }
+
+    fn visit_u64<E>(self, number: u64) -> Result<Value, E> {
+        Ok(Value::Text(number.to_string()))
+    }
+
+    fn visit_f64<E>(self, number: f64) -> Result<Value, E> {
+        Ok(Value::Text(number.to_string()))
+    }
+
+    fn visit_str<E>(self, text: &str) -> Result<Value, E> {
+        Ok(Value::Text(text.to_string()))
+    }
+
+    fn visit_string<E>(self, text: String) -> Result<Value, E> {
+        Ok(Value::Text(text))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            if let Value::Table(_) = value {
+                return Err(de::Error::custom(ARRAY_OF_TABLES));
+            }
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if entries.iter().any(|(present, _)| *present == key) {
+                return Err(de::Error::custom(format!("duplicate key `{}`", key)));
+            }
+            let value = map.next_value()?;
+            entries.push((key, value));
+        }
+        Ok(Value::Table(entries))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConfigError;
+    use crate::test_dir::TestDir;
+    use crate::{Arguments, Source};
+    use std::fs;
+
+    #[test]
+    fn toml() {
+        let dir = TestDir::new("config-toml");
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            "# server settings\n\
+             name = \"app\\tone\"\n\
+             verbose = true\n\
+             quiet = false\n\
+             tags = ['a', \"b\",\n  'c'] # trailing\n\
+             [server]\n\
+             port = 8080\n\
+             limits = { max = 10, \"ratio\" = 0.5 }\n",
+        )
+        .unwrap();
+        let args = Arguments::from_file(&path).unwrap();
+        assert_eq!(args.get("name"), Some(Some("app\tone")));
+        assert_eq!(args.get("verbose"), Some(None));
+        assert!(!args.contains("quiet"));
+        assert_eq!(args.get_vec("tags").unwrap().len(), 3);
+        assert_eq!(args.get("server.port"), Some(Some("8080")));
+        assert_eq!(args.get("server.limits.ratio"), Some(Some("0.5")));
+        assert_eq!(
+            args.values_with_sources("server.port").next().unwrap().1,
+            &Source::File(path.clone())
+        );
+        let mut cli = Arguments::parse(&["-server.port", "9000"]);
+        cli.merge_file(&path).unwrap();
+        assert_eq!(cli.get_vec("server.port").unwrap().len(), 1);
+        assert_eq!(cli.get("server.port"), Some(Some("9000")));
+        assert_eq!(cli.get("name"), Some(Some("app\tone")));
+        fs::write(&path, "motd = \"\"\"\nfirst\nsecond\"\"\"\n").unwrap();
+        let args = Arguments::from_file(&path).unwrap();
+        assert_eq!(args.get("motd"), Some(Some("first\nsecond")));
+        for (text, line, message) in [
+            (
+                "port = 1\nname = \"unterminated\n",
+                2,
+                "invalid basic string, expected `\"`",
+            ),
+            ("port = 1\nport = 2\n", 2, "duplicate key"),
+            (
+                "port = 1\n[[server]]\nport = 2\n",
+                2,
+                super::ARRAY_OF_TABLES,
+            ),
+            ("hosts = [{ name = \"a\" }]\n", 1, super::ARRAY_OF_TABLES),
+        ] {
+            fs::write(&path, text).unwrap();
+            let err = error(Arguments::from_file(&path).err().unwrap());
+            assert_eq!((err.line, err.message.as_str()), (line, message));
+        }
+    }
+
+    #[test]
+    fn json() {
+        let dir = TestDir::new("config-json");
+        let path = dir.join("config.json");
+        fs::write(
+            &path,
+            "{\n  \"name\": \"caf\\u00e9\",\n  \"debug\": true,\n  \"proxy\": null,\n  \
+             \"server\": {\"port\": 8080, \"hosts\": [\"a\", \"b\"]}\n}\n",
+        )
+        .unwrap();
+        let args = Arguments::from_file(&path).unwrap();
+        assert_eq!(args.get("name"), Some(Some("café")));
+        assert_eq!(args.get("debug"), Some(None));
+        assert!(!args.contains("proxy"));
+        assert_eq!(args.get("server.port"), Some(Some("8080")));
+        assert_eq!(
+            args.get_vec("server.hosts").unwrap(),
+            &[Some("a".to_string()), Some("b".to_string())]
+        );
+        let err = Arguments::from_file("config.yaml").err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        for (text, line, message) in [
+            (
+                "{\n  \"port\": 1,\n  \"port\": 2\n}\n",
+                3,
+                "duplicate key `port`",
+            ),
+            (
+                "{\n  \"port\": 1,\n  \"hosts\": [{\"name\": \"a\"}]\n}\n",
+                3,
+                super::ARRAY_OF_TABLES,
+            ),
+            (
+                &format!("{{\"a\": {}{}}}", "[".repeat(200), "]".repeat(200)),
+                1,
+                "recursion limit exceeded",
+            ),
+        ] {
+            fs::write(&path, text).unwrap();
+            let err = error(Arguments::from_file(&path).err().unwrap());
+            assert_eq!((err.line, err.message.as_str()), (line, message));
+        }
+    }
+
+    fn error(err: std::io::Error) -> ConfigError {
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        *err.into_inner().unwrap().downcast::<ConfigError>().unwrap()
+    }
+}
@@ -0,0 +1,443 @@
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::fuzzy::{levenshtein, max_distance};
+use crate::Spec;
+
+/// The first argument requesting the exported specification instead of
+/// a normal run, so tooling can collect the specification of every
+/// binary in a workspace
+pub const SPEC_REQUEST: &str = "__spec";
+
+/// An argument of an exported specification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportedArg {
+    /// The key of the argument
+    pub name: String,
+    /// Whether the argument never takes a value
+    pub flag: bool,
+    /// The name of the type of the value, if declared
+    pub value_type: Option<String>,
+    /// The default value, if declared
+    pub default_value: Option<String>,
+    /// The environment variable the argument falls back to, if declared
+    pub env: Option<String>,
+}
+
+/// A specification exported to plain data, which can be written out as
+/// text by one program and read back by tooling with `parse`. The text
+/// has one line per argument with tab-separated fields
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportedSpec {
+    /// The program name, if declared
+    pub program: Option<String>,
+    /// The declared arguments in declaration order
+    pub args: Vec<ExportedArg>,
+}
+
+/// Error reading an exported specification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportParseError {
+    /// The line the error is on, starting at 1
+    pub line: usize,
+    /// What is wrong with the line
+    pub message: String,
+}
+
+impl fmt::Display for ExportParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid exported specification on line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl Error for ExportParseError {}
+
+/// A difference between two specifications of the same argument
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Drift {
+    /// The argument is only in the first specification
+    Removed(String),
+    /// The argument is only in the second specification
+    Added(String),
+    /// The argument is only in the first specification, and a similar
+    /// one is only in the second, which is likely a rename or a typo
+    Renamed { from: String, to: String },
+    /// The argument is a flag in one specification and takes a value
+    /// in the other
+    Kind { name: String, was_flag: bool },
+    /// The declared type of the value changed
+    Type {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// The default value changed
+    Default {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+/// Renders an optional field for drift messages
+fn or_none(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("none")
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::Removed(name) => write!(f, "-{} was removed", name),
+            Drift::Added(name) => write!(f, "-{} was added", name),
+            Drift::Renamed { from, to } => write!(f, "-{} appears to be renamed to -{}", from, to),
+            Drift::Kind {
+                name,
+                was_flag: true,
+            } => write!(f, "-{} changed from a flag to taking a value", name),
+            Drift::Kind { name, .. } => {
+                write!(f, "-{} changed from taking a value to a flag", name)
+            }
+            Drift::Type { name, from, to } => write!(
+                f,
+                "-{} changed type from {} to {}",
+                name,
+                or_none(from),
+                or_none(to)
+            ),
+            Drift::Default { name, from, to } => write!(
+                f,
+                "-{} changed default from {} to {}",
+                name,
+                or_none(from),
+                or_none(to)
+            ),
+        }
+    }
+}
+
+/// Escapes tabs, line breaks, carriage returns and backslashes in a
+/// field
+pub(crate) fn escape(out: &mut String, field: &str) {
+    for c in field.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Reverses `escape`
+pub(crate) fn unescape(field: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            _ => return Err(format!("invalid escape in '{}'", field)),
+        }
+    }
+    Ok(out)
+}
+
+impl fmt::Display for ExportedSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        if let Some(program) = &self.program {
+            out.push_str("program\t");
+            escape(&mut out, program);
+            out.push('\n');
+        }
+        for arg in &self.args {
+            out.push_str("arg\t");
+            escape(&mut out, &arg.name);
+            out.push_str(if arg.flag { "\tflag" } else { "\tvalue" });
+            let fields = [
+                ("type", &arg.value_type),
+                ("default", &arg.default_value),
+                ("env", &arg.env),
+            ];
+            for (label, field) in fields.iter() {
+                if let Some(field) = field {
+                    let _ = write!(out, "\t{}=", label);
+                    escape(&mut out, field);
+                }
+            }
+            out.push('\n');
+        }
+        f.write_str(&out)
+    }
+}
+
+impl FromStr for ExportedSpec {
+    type Err = ExportParseError;
+
+    fn from_str(text: &str) -> Result<ExportedSpec, ExportParseError> {
+        let mut spec = ExportedSpec::default();
+        for (i, line) in text.lines().enumerate() {
+            let error = |message: String| ExportParseError {
+                line: i + 1,
+                message,
+            };
+            let mut fields = line.split('\t');
+            match fields.next() {
+                Some("") | None => {}
+                Some("program") => {
+                    let program = fields
+                        .next()
+                        .ok_or_else(|| error("missing program".into()))?;
+                    spec.program = Some(unescape(program).map_err(error)?);
+                }
+                Some("arg") => {
+                    let name = fields.next().ok_or_else(|| error("missing name".into()))?;
+                    let flag = match fields.next() {
+                        Some("flag") => true,
+                        Some("value") => false,
+                        _ => return Err(error("expected flag or value".into())),
+                    };
+                    let mut arg = ExportedArg {
+                        name: unescape(name).map_err(error)?,
+                        flag,
+                        value_type: None,
+                        default_value: None,
+                        env: None,
+                    };
+                    for field in fields {
+                        let (label, value) = field.split_once('=').ok_or_else(|| {
+                            error(format!("expected label=value, got '{}'", field))
+                        })?;
+                        let slot = match label {
+                            "type" => &mut arg.value_type,
+                            "default" => &mut arg.default_value,
+                            "env" => &mut arg.env,
+                            _ => return Err(error(format!("unknown field '{}'", label))),
+                        };
+                        *slot = Some(unescape(value).map_err(error)?);
+                    }
+                    spec.args.push(arg);
+                }
+                Some(other) => return Err(error(format!("unknown entry '{}'", other))),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+impl ExportedSpec {
+    /// Lists how another specification drifted from this one: arguments
+    /// removed, added or likely renamed, and shared arguments whose
+    /// kind, type or default changed, in declaration order
+    ///
+    /// # Arguments
+    ///
+    /// `other`: The specification to compare with
+    pub fn diff(&self, other: &ExportedSpec) -> Vec<Drift> {
+        let find = |spec: &'_ ExportedSpec, name: &str| -> bool {
+            spec.args.iter().any(|arg| arg.name == name)
+        };
+        let added: Vec<&str> = other
+            .args
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .filter(|name| !find(self, name))
+            .collect();
+        let mut renamed = Vec::new();
+        let mut drifts = Vec::new();
+        for arg in &self.args {
+            let new = match other.args.iter().find(|new| new.name == arg.name) {
+                Some(new) => new,
+                None => {
+                    let mut close = added
+                        .iter()
+                        .filter(|name| !renamed.contains(*name))
+                        .filter(|name| levenshtein(&arg.name, name) <= max_distance(&arg.name));
+                    match (close.next(), close.next()) {
+                        (Some(&to), None) => {
+                            renamed.push(to);
+                            drifts.push(Drift::Renamed {
+                                from: arg.name.clone(),
+                                to: to.to_string(),
+                            });
+                        }
+                        _ => drifts.push(Drift::Removed(arg.name.clone())),
+                    }
+                    continue;
+                }
+            };
+            if arg.flag != new.flag {
+                drifts.push(Drift::Kind {
+                    name: arg.name.clone(),
+                    was_flag: arg.flag,
+                });
+            }
+            if arg.value_type != new.value_type {
+                drifts.push(Drift::Type {
+                    name: arg.name.clone(),
+                    from: arg.value_type.clone(),
+                    to: new.value_type.clone(),
+                });
+            }
+            if arg.default_value != new.default_value {
+                drifts.push(Drift::Default {
+                    name: arg.name.clone(),
+                    from: arg.default_value.clone(),
+                    to: new.default_value.clone(),
+                });
+            }
+        }
+        for name in added {
+            if !renamed.contains(&name) {
+                drifts.push(Drift::Added(name.to_string()));
+            }
+        }
+        drifts
+    }
+}
+
+impl Spec {
+    /// Exports the declared arguments to plain data for tooling
+    pub fn export(&self) -> ExportedSpec {
+        ExportedSpec {
+            program: self.get_program().map(str::to_string),
+            args: self
+                .args()
+                .iter()
+                .map(|arg| ExportedArg {
+                    name: arg.name().to_string(),
+                    flag: arg.is_flag(),
+                    value_type: arg.get_value_type().map(str::to_string),
+                    default_value: arg.get_default_value().map(str::to_string),
+                    env: self.env_var(arg).map(String::from),
+                })
+                .collect(),
+        }
+    }
+
+    /// Lists how another specification drifted from this one, as for
+    /// flags shared by related programs. See `ExportedSpec::diff`
+    ///
+    /// # Arguments
+    ///
+    /// `other`: The specification to compare with
+    pub fn diff(&self, other: &Spec) -> Vec<Drift> {
+        self.export().diff(&other.export())
+    }
+
+    /// Answers a request for the exported specification, where tooling
+    /// runs the program with `__spec`. Returns the exported text, or
+    /// `None` for a normal run
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The raw arguments, excluding the program name
+    pub fn export_for<S: AsRef<str>>(&self, args: &[S]) -> Option<String> {
+        match args.first() {
+            Some(first) if first.as_ref() == SPEC_REQUEST => Some(self.export().to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Drift, ExportedArg, ExportedSpec, Spec};
+
+    fn server() -> Spec {
+        Spec::new()
+            .program("server")
+            .arg(ArgSpec::new("port").value_type("u16").default_value("8080"))
+            .arg(ArgSpec::new("verbose").flag())
+            .arg(ArgSpec::new("log-level").env("LOG\tLEVEL"))
+            .arg(ArgSpec::new("config"))
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let text = server().export_for(&["__spec"]).unwrap();
+        assert_eq!(
+            text,
+            "program\tserver\n\
+             arg\tport\tvalue\ttype=u16\tdefault=8080\n\
+             arg\tverbose\tflag\n\
+             arg\tlog-level\tvalue\tenv=LOG\\tLEVEL\n\
+             arg\tconfig\tvalue\n"
+        );
+        assert_eq!(text.parse::<ExportedSpec>(), Ok(server().export()));
+        assert!(server().export_for(&["-port"]).is_none());
+        let exported = ExportedSpec {
+            program: Some("tool\r".to_string()),
+            args: vec![ExportedArg {
+                name: "eol".to_string(),
+                flag: false,
+                value_type: None,
+                default_value: Some("\r\n\r".to_string()),
+                env: None,
+            }],
+        };
+        let text = exported.to_string();
+        assert_eq!(
+            text,
+            "program\ttool\\r\narg\teol\tvalue\tdefault=\\r\\n\\r\n"
+        );
+        assert_eq!(text.parse::<ExportedSpec>(), Ok(exported));
+        let err = "arg\tx\tmaybe".parse::<ExportedSpec>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid exported specification on line 1: expected flag or value"
+        );
+    }
+
+    #[test]
+    fn drift() {
+        let worker = Spec::new()
+            .arg(ArgSpec::new("port").value_type("u32").default_value("9090"))
+            .arg(ArgSpec::new("verbose"))
+            .arg(ArgSpec::new("log-levels"))
+            .arg(ArgSpec::new("threads"));
+        let drifts = server().diff(&worker);
+        assert_eq!(
+            drifts,
+            [
+                Drift::Type {
+                    name: "port".to_string(),
+                    from: Some("u16".to_string()),
+                    to: Some("u32".to_string()),
+                },
+                Drift::Default {
+                    name: "port".to_string(),
+                    from: Some("8080".to_string()),
+                    to: Some("9090".to_string()),
+                },
+                Drift::Kind {
+                    name: "verbose".to_string(),
+                    was_flag: true,
+                },
+                Drift::Renamed {
+                    from: "log-level".to_string(),
+                    to: "log-levels".to_string(),
+                },
+                Drift::Removed("config".to_string()),
+                Drift::Added("threads".to_string()),
+            ]
+        );
+        assert_eq!(
+            drifts[3].to_string(),
+            "-log-level appears to be renamed to -log-levels"
+        );
+        assert!(server().diff(&server()).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::fallback::is_truthy;
+use crate::{ArgSpec, Arguments, Spec};
+
+/// Environment variables set by common continuous integration services
+const CI_VARS: [&str; 10] = [
+    "CI",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TRAVIS",
+    "TF_BUILD",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "BITBUCKET_BUILD_NUMBER",
+];
+
+/// What the program runs under, probed once so that conditional
+/// defaults declared with `ArgSpec::default_with` all see the same
+/// answers
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// Whether standard output is a terminal
+    pub terminal: bool,
+    /// Whether a continuous integration service is running the program
+    pub ci: bool,
+    /// Whether `NO_COLOR` asks for output without color
+    pub no_color: bool,
+    /// The width of the terminal in columns, from `COLUMNS`
+    pub width: Option<usize>,
+}
+
+impl Environment {
+    /// Probes the process: whether standard output is a terminal, and
+    /// the variables of `detect_with`
+    pub fn detect() -> Environment {
+        Environment::detect_with(|var| env::var(var).ok(), io::stdout().is_terminal())
+    }
+
+    /// Probes the environment through a lookup function instead of the
+    /// process environment. Continuous integration is detected from
+    /// variables such as `CI` or `GITHUB_ACTIONS` that are set and not
+    /// `0`, `false`, `no` or `off`, color is turned off by a non-empty
+    /// `NO_COLOR`, and the width is read from a positive `COLUMNS`
+    ///
+    /// # Arguments
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    ///
+    /// `terminal`: Whether standard output is a terminal
+    pub fn detect_with<F: Fn(&str) -> Option<String>>(lookup: F, terminal: bool) -> Environment {
+        Environment {
+            terminal,
+            ci: CI_VARS
+                .iter()
+                .any(|var| lookup(var).is_some_and(|value| is_truthy(&value))),
+            no_color: lookup("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            width: lookup("COLUMNS")
+                .and_then(|value| value.trim().parse().ok())
+                .filter(|&width| width > 0),
+        }
+    }
+
+    /// Checks whether output should be colored, which is when it goes
+    /// to a terminal and `NO_COLOR` is not set
+    pub fn is_colored(&self) -> bool {
+        self.terminal && !self.no_color
+    }
+}
+
+impl Spec {
+    /// Returns the default value of a declared argument in an
+    /// environment: its continuous integration default under continuous
+    /// integration, then the one its conditional default picks, then
+    /// its default value
+    ///
+    /// # Arguments
+    ///
+    /// `arg`: The declared argument
+    ///
+    /// `environment`: The environment the program runs under
+    pub fn default_in(&self, arg: &ArgSpec, environment: &Environment) -> Option<&'static str> {
+        arg.get_ci_default()
+            .filter(|_| environment.ci)
+            .or_else(|| arg.get_default_with()?(environment))
+            .or_else(|| arg.get_default_value())
+    }
+
+    /// Fills in declared arguments absent from the command line like
+    /// `apply_fallbacks`, resolving defaults in an environment with
+    /// `default_in`. Giving `-ci` activates the continuous integration
+    /// profile as if the environment was detected as such
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments to fill in
+    ///
+    /// `environment`: The environment the program runs under
+    pub fn apply_fallbacks_in(&self, args: &mut Arguments, environment: &Environment) {
+        let environment = Environment {
+            ci: self.is_ci(args, environment),
+            ..environment.clone()
+        };
+        self.fill_fallbacks(args, |var| env::var(var).ok(), Some(&environment))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Environment, Spec};
+
+    fn color(environment: &Environment) -> Option<&'static str> {
+        if environment.is_colored() && !environment.ci {
+            None
+        } else {
+            Some("never")
+        }
+    }
+
+    #[test]
+    fn detection() {
+        let lookup = |var: &str| match var {
+            "GITHUB_ACTIONS" => Some("true".to_string()),
+            "CI" => Some("false".to_string()),
+            "NO_COLOR" => Some(String::new()),
+            "COLUMNS" => Some("120".to_string()),
+            _ => None,
+        };
+        let environment = Environment::detect_with(lookup, true);
+        assert_eq!(
+            environment,
+            Environment {
+                terminal: true,
+                ci: true,
+                no_color: false,
+                width: Some(120),
+            }
+        );
+        assert!(environment.is_colored());
+        assert_eq!(
+            Environment::detect_with(|_| None, false),
+            Environment::default()
+        );
+        let spec = Spec::new().arg(
+            ArgSpec::new("color")
+                .default_value("auto")
+                .default_with(color),
+        );
+        let interactive = Environment {
+            terminal: true,
+            ..Environment::default()
+        };
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec.apply_fallbacks_in(&mut args, &environment);
+        assert_eq!(args.get("color"), Some(Some("never")));
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec.apply_fallbacks_in(&mut args, &interactive);
+        assert_eq!(args.get("color"), Some(Some("auto")));
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec.apply_defaults(&mut args);
+        assert_eq!(args.get("color"), Some(Some("auto")));
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt::Write;
+
+use crate::{Arguments, Source};
+
+/// Quotes a string as a JSON string literal
+#[cfg(feature = "dump")]
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Quotes a CSV field when it contains a delimiter, quote or line break
+#[cfg(feature = "dump")]
+fn csv_field(out: &mut String, s: &str) {
+    if s.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&s.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(s);
+    }
+}
+
+/// Converts a key to an environment variable name segment: uppercase,
+/// with every character other than ASCII letters and digits replaced
+/// by `_`
+pub(crate) fn env_segment(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl Arguments {
+    /// Converts the arguments to `PREFIX_KEY=value` environment variable
+    /// pairs for child processes that only read their environment, with
+    /// multiple values joined by `,`. See `to_env_vars_with`
+    ///
+    /// # Arguments
+    ///
+    /// `prefix`: The prefix of every variable name, such as `MYAPP`
+    pub fn to_env_vars(&self, prefix: &str) -> Vec<(String, String)> {
+        self.to_env_vars_with(prefix, ",")
+    }
+
+    /// Converts the arguments to `PREFIX_KEY=value` environment variable
+    /// pairs, sorted by name. Keys are uppercased with characters other
+    /// than letters and digits replaced by `_`, and an empty prefix
+    /// leaves the key alone. Multiple values are joined by the delimiter,
+    /// and an argument without any value becomes `1`
+    ///
+    /// # Arguments
+    ///
+    /// `prefix`: The prefix of every variable name, such as `MYAPP`
+    ///
+    /// `delimiter`: The separator placed between multiple values
+    pub fn to_env_vars_with(&self, prefix: &str, delimiter: &str) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = self
+            .arg_map
+            .iter_all()
+            .map(|(key, vals)| {
+                let name = if prefix.is_empty() {
+                    env_segment(key)
+                } else {
+                    format!("{}_{}", prefix, env_segment(key))
+                };
+                let vals: Vec<&str> = vals.iter().filter_map(Option::as_deref).collect();
+                let value = if vals.is_empty() {
+                    String::from("1")
+                } else {
+                    vals.join(delimiter)
+                };
+                (name, value)
+            })
+            .collect();
+        vars.sort();
+        vars
+    }
+
+    /// Reconstructs an argument list for forwarding to another tool,
+    /// with the given keys first, in the order given, followed by `--`
+    /// and then every other key in the order they were given, the
+    /// positionals and the trailing arguments. Values are attached as
+    /// in `-key=value`, so a value starting with `-` is not read as a
+    /// key. The tool receiving the list then treats everything after
+    /// `--` as operands instead of its own flags, which makes forwarding
+    /// user input to programs like `cargo` or `ssh` safe
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The keys meant for the receiving tool itself, or their
+    /// aliases
+    pub fn forward_after(&self, keys: &[&str]) -> Vec<String> {
+        let token = |key: &str, val: Option<&str>| match val {
+            Some(val) => format!("-{}={}", key, val),
+            None => format!("-{}", key),
+        };
+        let mut own: Vec<&str> = Vec::new();
+        for key in keys {
+            let key = self.canonical(key);
+            if !own.contains(&key) {
+                own.push(key);
+            }
+        }
+        let mut argv: Vec<String> = own
+            .iter()
+            .flat_map(|key| {
+                self.get_vec(key)
+                    .into_iter()
+                    .flatten()
+                    .map(move |val| token(key, val.as_deref()))
+            })
+            .collect();
+        argv.push(String::from("--"));
+        let rest: Vec<&str> = self
+            .arg_map
+            .keys()
+            .map(|key| key.as_str())
+            .filter(|key| !own.contains(key))
+            .collect();
+        argv.extend(self.pairs_in_order(&rest).map(|(key, val)| token(key, val)));
+        argv.extend(self.positionals().iter().cloned());
+        argv.extend(self.trailing().iter().cloned());
+        argv
+    }
+
+    /// Renders the arguments as an aligned plain-text table with one
+    /// row per key, sorted by key, listing how many times it occurred,
+    /// its values (`-` for an occurrence without one) and their distinct
+    /// sources. Useful for `-debug-args` style output
+    pub fn to_table(&self) -> String {
+        let mut rows: Vec<[String; 4]> = self
+            .arg_map
+            .iter_all()
+            .map(|(key, vals)| {
+                let values: Vec<&str> = vals
+                    .iter()
+                    .map(|val| val.as_deref().unwrap_or("-"))
+                    .collect();
+                let mut sources: Vec<&Source> = Vec::new();
+                for (_, source) in self.values_with_sources(key) {
+                    if !sources.contains(&source) {
+                        sources.push(source);
+                    }
+                }
+                let sources: Vec<String> = sources.iter().map(ToString::to_string).collect();
+                [
+                    key.to_string(),
+                    vals.len().to_string(),
+                    values.join(", "),
+                    sources.join(", "),
+                ]
+            })
+            .collect();
+        rows.sort();
+        let header = [
+            "KEY".to_string(),
+            "COUNT".to_string(),
+            "VALUES".to_string(),
+            "SOURCE".to_string(),
+        ];
+        let mut widths = [0; 4];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&rows) {
+            let _ = writeln!(
+                out,
+                "{:kw$}  {:cw$}  {:vw$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                kw = widths[0],
+                cw = widths[1],
+                vw = widths[2]
+            );
+        }
+        out
+    }
+
+    /// Returns every key, sorted
+    #[cfg(feature = "dump")]
+    fn sorted_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.arg_map.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Renders the arguments as a JSON object mapping each key, sorted,
+    /// to an array of its occurrences in order. Each occurrence is an
+    /// object with a `value`, which is `null` when absent, and a `source`
+    /// as displayed by `Source`. Useful for `-dump-args` style output
+    /// consumed by scripts
+    #[cfg(feature = "dump")]
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{");
+        for (i, key) in self.sorted_keys().into_iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            json_string(&mut out, key);
+            out.push_str(":[");
+            for (j, (value, source)) in self.values_with_sources(key).enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                out.push_str("{\"value\":");
+                match value {
+                    Some(value) => json_string(&mut out, value),
+                    None => out.push_str("null"),
+                }
+                out.push_str(",\"source\":");
+                json_string(&mut out, &source.to_string());
+                out.push('}');
+            }
+            out.push(']');
+        }
+        out.push('}');
+        out
+    }
+
+    /// Renders the arguments as CSV with a `key,index,value,source`
+    /// header and one row per occurrence, sorted by key and then by the
+    /// order of occurrence. A missing value is an empty field
+    #[cfg(feature = "dump")]
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("key,index,value,source\n");
+        for key in self.sorted_keys() {
+            for (index, (value, source)) in self.values_with_sources(key).enumerate() {
+                csv_field(&mut out, key);
+                let _ = write!(out, ",{},", index);
+                csv_field(&mut out, value.unwrap_or(""));
+                out.push(',');
+                csv_field(&mut out, &source.to_string());
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Source, Spec};
+
+    #[test]
+    fn env_vars() {
+        let args = Arguments::parse(&[
+            "-log-level",
+            "debug",
+            "-include",
+            "a",
+            "-include",
+            "b",
+            "-verbose",
+        ]);
+        assert_eq!(
+            args.to_env_vars("MYAPP"),
+            vec![
+                ("MYAPP_INCLUDE".to_string(), "a,b".to_string()),
+                ("MYAPP_LOG_LEVEL".to_string(), "debug".to_string()),
+                ("MYAPP_VERBOSE".to_string(), "1".to_string()),
+            ]
+        );
+        assert_eq!(
+            args.to_env_vars_with("", ":")[0],
+            ("INCLUDE".to_string(), "a:b".to_string())
+        );
+    }
+
+    #[test]
+    fn forward() {
+        let args = Spec::new().parse(&["-release", "-target", "x", "-v", "a", "file"]);
+        assert_eq!(
+            args.forward_after(&["target", "release", "missing"]),
+            ["-target=x", "-release", "--", "-v=a", "file"]
+        );
+        let args = Arguments::parse(&["-offset=-5", "-x", "1", "-b", "--", "rest"]);
+        assert_eq!(
+            args.forward_after(&["x"]),
+            ["-x=1", "--", "-offset=-5", "-b", "rest"]
+        );
+        let spec = Spec::new()
+            .arg(ArgSpec::new("target").aliases(&["t"]))
+            .arg(ArgSpec::new("zeta"))
+            .arg(ArgSpec::new("alpha"));
+        let args = spec.parse(&["-zeta", "1", "-t", "x", "-alpha", "2"]);
+        assert_eq!(
+            args.forward_after(&["t"]),
+            ["-target=x", "--", "-zeta=1", "-alpha=2"]
+        );
+    }
+
+    #[test]
+    fn table() {
+        let mut args = Arguments::parse(&["-include", "a", "-include", "b", "-verbose"]);
+        args.merge(Arguments::parse_with_source(
+            &["-include", "c"],
+            Source::Env("INCLUDE".to_string()),
+        ));
+        assert_eq!(
+            args.to_table(),
+            "KEY      COUNT  VALUES   SOURCE\n\
+             include  3      a, b, c  command line, environment variable INCLUDE\n\
+             verbose  1      -        command line\n"
+        );
+    }
+
+    #[cfg(feature = "dump")]
+    #[test]
+    fn json_and_csv() {
+        let mut args = Arguments::parse(&["-name", "a \"b\"", "-verbose"]);
+        args.merge(Arguments::parse_with_source(
+            &["-name", "c,d"],
+            Source::Env("NAME".to_string()),
+        ));
+        assert_eq!(
+            args.to_json(),
+            "{\"name\":[{\"value\":\"a \\\"b\\\"\",\"source\":\"command line\"},\
+             {\"value\":\"c,d\",\"source\":\"environment variable NAME\"}],\
+             \"verbose\":[{\"value\":null,\"source\":\"command line\"}]}"
+        );
+        assert_eq!(
+            args.to_csv(),
+            "key,index,value,source\n\
+             name,0,\"a \"\"b\"\"\",command line\n\
+             name,1,\"c,d\",environment variable NAME\n\
+             verbose,0,,command line\n"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use std::env;
+use std::time::{Duration, Instant};
+
+use crate::{Arguments, Environment, FileValue, Phase, Source, Spec};
+
+/// Checks whether an environment value enables a flag
+pub(crate) fn is_truthy(value: &str) -> bool {
+    !matches!(
+        value.to_ascii_lowercase().as_str(),
+        "" | "0" | "false" | "no" | "off"
+    )
+}
+
+impl Spec {
+    /// Fills in declared arguments absent from the command line, first
+    /// from their environment variable, then from the file named by the
+    /// same variable suffixed with `_FILE`, as is common for secrets, and
+    /// then from their default value. Empty environment variables and
+    /// unreadable files are treated as unset, and file contents are
+    /// trimmed like `FileValue::trimmed`. A flag is only added when its
+    /// fallback is not `0`, `false`, `no` or `off`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments to fill in
+    pub fn apply_fallbacks(&self, args: &mut Arguments) {
+        self.apply_fallbacks_with(args, |var| env::var(var).ok())
+    }
+
+    /// Fills in declared arguments absent from the command line with
+    /// their default value only, ignoring the environment, so every call
+    /// site sees the same defaults through `get`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments to fill in
+    pub fn apply_defaults(&self, args: &mut Arguments) {
+        self.apply_fallbacks_with(args, |_| None)
+    }
+
+    /// Fills in declared arguments absent from the command line like
+    /// `apply_fallbacks`, reading environment variables through a lookup
+    /// function instead of the process environment
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments to fill in
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub fn apply_fallbacks_with<F: Fn(&str) -> Option<String>>(
+        &self,
+        args: &mut Arguments,
+        lookup: F,
+    ) {
+        self.fill_fallbacks(args, lookup, None)
+    }
+
+    /// Fills in declared arguments absent from the command line like
+    /// `apply_fallbacks_with`, resolving defaults in the environment
+    /// when one is given
+    pub(crate) fn fill_fallbacks<F: Fn(&str) -> Option<String>>(
+        &self,
+        args: &mut Arguments,
+        lookup: F,
+        environment: Option<&Environment>,
+    ) {
+        let start = Instant::now();
+        let mut file_io = Duration::ZERO;
+        for arg in self.args() {
+            if args.contains(arg.name()) {
+                continue;
+            }
+            let value = self
+                .env_var(arg)
+                .and_then(|var| {
+                    lookup(&var)
+                        .filter(|value| !value.is_empty())
+                        .map(|value| (value, Source::Env(var.to_string())))
+                        .or_else(|| {
+                            let path = lookup(&format!("{}_FILE", var))?;
+                            let read = Instant::now();
+                            let file = FileValue::read(&path);
+                            file_io += read.elapsed();
+                            args.record(Phase::FileIo, read.elapsed());
+                            let file = file.ok()?;
+                            Some((file.trimmed().to_string(), Source::File(path.into())))
+                        })
+                })
+                .or_else(|| {
+                    environment
+                        .map_or(arg.get_default_value(), |environment| {
+                            self.default_in(arg, environment)
+                        })
+                        .map(|value| (value.to_string(), Source::Default))
+                });
+            match value {
+                Some((value, source)) if arg.is_flag() && is_truthy(&value) => {
+                    args.insert(arg.name(), None, source)
+                }
+                Some((value, source)) if !arg.is_flag() => {
+                    args.insert(arg.name(), Some(value), source)
+                }
+                _ => {}
+            }
+        }
+        args.record(Phase::Fallbacks, start.elapsed() - file_io);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_dir::TestDir;
+    use crate::{ArgSpec, Arguments, Source, Spec};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(ArgSpec::new("port").env("PORT").default_value("8080"))
+            .arg(ArgSpec::new("host").default_value("localhost"))
+            .arg(ArgSpec::new("debug").flag().env("DEBUG"))
+            .arg(ArgSpec::new("user"))
+    }
+
+    #[test]
+    fn precedence() {
+        let env = |var: &str| match var {
+            "PORT" => Some("9000".to_string()),
+            "DEBUG" => Some("1".to_string()),
+            _ => None,
+        };
+        let mut args = Arguments::parse(&["-host", "example.com"]);
+        spec().apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get("port"), Some(Some("9000")));
+        assert_eq!(
+            args.values_with_sources("port").next().unwrap().1,
+            &Source::Env("PORT".to_string())
+        );
+        assert_eq!(args.get("host"), Some(Some("example.com")));
+        assert_eq!(args.get("debug"), Some(None));
+        assert!(!args.contains("user"));
+
+        let mut args = Arguments::parse(&["-port", "1"]);
+        spec().apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get_vec("port").unwrap().len(), 1);
+        assert_eq!(args.get("port"), Some(Some("1")));
+    }
+
+    #[test]
+    fn defaults() {
+        let spec = Spec::new()
+            .arg(
+                ArgSpec::new("port")
+                    .env("SIMPLE_ARGS_DEFAULTS_PORT")
+                    .default_value("8080"),
+            )
+            .arg(ArgSpec::new("host").default_value("localhost"));
+        let mut args = Arguments::parse(&["-host", "example.com"]);
+        spec.apply_defaults(&mut args);
+        assert_eq!(args.get("port"), Some(Some("8080")));
+        assert_eq!(
+            args.values_with_sources("port").next().unwrap().1,
+            &Source::Default
+        );
+        assert_eq!(args.get("host"), Some(Some("example.com")));
+        assert_eq!(args.get_or("user", "nobody"), "nobody");
+        let mut args = Arguments::parse(&["-host", "example.com"]);
+        spec.apply_fallbacks_with(&mut args, |var| {
+            (var == "SIMPLE_ARGS_DEFAULTS_PORT").then(|| "9000".to_string())
+        });
+        assert_eq!(args.get("port"), Some(Some("9000")));
+    }
+
+    #[test]
+    fn prefixed_env() {
+        let spec = Spec::new()
+            .env_prefix("MYAPP")
+            .arg(ArgSpec::new("port"))
+            .arg(ArgSpec::new("log-level"))
+            .arg(ArgSpec::new("user").env("USER_NAME"));
+        let env = |var: &str| match var {
+            "MYAPP_PORT" => Some("9000".to_string()),
+            "MYAPP_LOG_LEVEL" => Some("debug".to_string()),
+            "USER_NAME" => Some("alice".to_string()),
+            _ => None,
+        };
+        let mut args = Arguments::parse(&["-port", "1"]);
+        spec.apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get("port"), Some(Some("1")));
+        assert_eq!(args.get("log-level"), Some(Some("debug")));
+        assert_eq!(args.get("user"), Some(Some("alice")));
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec.apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get("port"), Some(Some("9000")));
+        assert_eq!(
+            args.values_with_sources("port").next().unwrap().1,
+            &Source::Env("MYAPP_PORT".to_string())
+        );
+    }
+
+    #[test]
+    fn unset_env() {
+        let env = |var: &str| match var {
+            "PORT" => Some(String::new()),
+            "DEBUG" => Some("false".to_string()),
+            _ => None,
+        };
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec().apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get("port"), Some(Some("8080")));
+        assert!(!args.contains("debug"));
+    }
+
+    #[test]
+    fn env_file() {
+        let dir = TestDir::new("env-file");
+        let path = dir.join("port");
+        std::fs::write(&path, "9001\n").unwrap();
+        let env = |var: &str| match var {
+            "PORT_FILE" => Some(path.to_string_lossy().into_owned()),
+            _ => None,
+        };
+        let mut args = Arguments::parse::<&str>(&[]);
+        spec().apply_fallbacks_with(&mut args, env);
+        assert_eq!(args.get("port"), Some(Some("9001")));
+        assert_eq!(
+            args.values_with_sources("port").next().unwrap().1,
+            &Source::File(path)
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::{Arguments, LimitsExceeded, Source, Spec};
+
+/// The byte order mark some editors write at the start of UTF-8 files
+const BOM: char = '\u{feff}';
+
+/// The contents of a file holding argument values, such as a response
+/// file or a `_FILE` secret. Values read from files are trimmed the same
+/// way everywhere, while the untouched contents stay available
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileValue {
+    raw: String,
+}
+
+impl FileValue {
+    /// Reads a file as UTF-8
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the file
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<FileValue> {
+        fs::read_to_string(path).map(FileValue::from)
+    }
+
+    /// Returns the contents exactly as read
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Returns the contents without a leading byte order mark and
+    /// without trailing line breaks, so a secret saved with a final
+    /// newline does not carry it into the value
+    pub fn trimmed(&self) -> &str {
+        self.raw
+            .strip_prefix(BOM)
+            .unwrap_or(&self.raw)
+            .trim_end_matches(['\r', '\n'])
+    }
+
+    /// Returns every non-empty line without a leading byte order mark
+    /// and without `\r` line ending remnants
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.trimmed()
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.is_empty())
+    }
+}
+
+impl From<String> for FileValue {
+    fn from(raw: String) -> FileValue {
+        FileValue { raw }
+    }
+}
+
+impl Arguments {
+    /// Parse arguments from a response file holding one argument per
+    /// line, like `parse`, attributing every value to the file. Lines
+    /// are trimmed like `FileValue::lines`, so values keep inner spaces
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the response file
+    pub fn parse_file<P: AsRef<Path>>(path: P) -> io::Result<Arguments> {
+        let path = path.as_ref();
+        let file = FileValue::read(path)?;
+        let lines: Vec<&str> = file.lines().collect();
+        Ok(Arguments::parse_with_source(
+            &lines,
+            Source::File(path.to_path_buf()),
+        ))
+    }
+}
+
+impl Spec {
+    /// Replaces every `@path` argument with the arguments of the
+    /// response file at that path, one per line as in
+    /// `Arguments::parse_file`. Response files may include other
+    /// response files, within the depth and total size allowed by the
+    /// limits of the specification. Exceeding a limit fails with an
+    /// `InvalidData` error wrapping `LimitsExceeded`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn expand_response_files<S: AsRef<str>>(&self, args: &[S]) -> io::Result<Vec<String>> {
+        let mut expanded = Vec::new();
+        let mut size = 0;
+        self.expand_into(&mut expanded, args, 0, &mut size)?;
+        Ok(expanded)
+    }
+
+    /// Expands response files into the list of arguments, tracking the
+    /// nesting depth and the total size read so far
+    fn expand_into<S: AsRef<str>>(
+        &self,
+        expanded: &mut Vec<String>,
+        args: &[S],
+        depth: usize,
+        size: &mut u64,
+    ) -> io::Result<()> {
+        let limits = self.get_limits();
+        for arg in args {
+            let path = match arg.as_ref().strip_prefix('@') {
+                Some(path) if !path.is_empty() => Path::new(path),
+                _ => {
+                    expanded.push(arg.as_ref().to_string());
+                    continue;
+                }
+            };
+            if depth >= limits.get_max_file_depth() {
+                let exceeded = LimitsExceeded::FileDepth {
+                    path: path.to_path_buf(),
+                    max: limits.get_max_file_depth(),
+                };
+                return Err(io::Error::new(io::ErrorKind::InvalidData, exceeded));
+            }
+            let remaining = limits.get_max_file_size().saturating_sub(*size);
+            let mut contents = Vec::new();
+            File::open(path)?
+                .take(remaining.saturating_add(1))
+                .read_to_end(&mut contents)?;
+            *size = size.saturating_add(contents.len() as u64);
+            if *size > limits.get_max_file_size() {
+                let exceeded = LimitsExceeded::FileSize {
+                    path: path.to_path_buf(),
+                    max: limits.get_max_file_size(),
+                };
+                return Err(io::Error::new(io::ErrorKind::InvalidData, exceeded));
+            }
+            let contents = String::from_utf8(contents)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let file = FileValue::from(contents);
+            let lines: Vec<&str> = file.lines().collect();
+            self.expand_into(expanded, &lines, depth + 1, size)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FileValue;
+    use crate::test_dir::TestDir;
+    use crate::{Arguments, Limits, LimitsExceeded, Source, Spec};
+    use std::fs;
+
+    #[test]
+    fn trimming() {
+        let file = FileValue::from("\u{feff}hunter2\r\n".to_string());
+        assert_eq!(file.raw(), "\u{feff}hunter2\r\n");
+        assert_eq!(file.trimmed(), "hunter2");
+        assert_eq!(FileValue::from(" a b \n\n".to_string()).trimmed(), " a b ");
+    }
+
+    #[test]
+    fn response_file() {
+        let dir = TestDir::new("response-file");
+        let path = dir.join("args");
+        fs::write(&path, "\u{feff}-name\r\nJohn Smith\r\n\r\n-verbose\n").unwrap();
+        let args = Arguments::parse_file(&path).unwrap();
+        assert_eq!(args.get("name"), Some(Some("John Smith")));
+        assert_eq!(args.get("verbose"), Some(None));
+        assert_eq!(
+            args.values_with_sources("name").next().unwrap().1,
+            &Source::File(path)
+        );
+    }
+
+    #[test]
+    fn expansion_limits() {
+        let dir = TestDir::new("expansion-limits");
+        let outer = dir.join("outer");
+        let inner = dir.join("inner");
+        fs::write(&outer, format!("-a\n@{}\n", inner.display())).unwrap();
+        fs::write(&inner, "-b\n1\n").unwrap();
+        let outer_arg = format!("@{}", outer.display());
+        let spec = Spec::new();
+        assert_eq!(
+            spec.expand_response_files(&["-x", &outer_arg]).unwrap(),
+            ["-x", "-a", "-b", "1"]
+        );
+        let exceeded = |spec: Spec| {
+            let err = spec.expand_response_files(&[&outer_arg]).unwrap_err();
+            err.into_inner()
+                .unwrap()
+                .downcast::<LimitsExceeded>()
+                .map(|err| *err)
+                .unwrap()
+        };
+        assert_eq!(
+            exceeded(Spec::new().limits(Limits::new().max_file_depth(1))),
+            LimitsExceeded::FileDepth {
+                path: inner.clone(),
+                max: 1
+            }
+        );
+        assert_eq!(
+            exceeded(Spec::new().limits(Limits::new().max_file_size(8))),
+            LimitsExceeded::FileSize {
+                path: outer.clone(),
+                max: 8
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unbounded_file() {
+        let spec = Spec::new().limits(Limits::new().max_file_size(1024));
+        let err = spec.expand_response_files(&["@/dev/zero"]).unwrap_err();
+        assert_eq!(
+            err.into_inner()
+                .unwrap()
+                .downcast::<LimitsExceeded>()
+                .map(|err| *err)
+                .unwrap(),
+            LimitsExceeded::FileSize {
+                path: "/dev/zero".into(),
+                max: 1024
+            }
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::Arguments;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Builds a 64-bit FNV-1a hash, which unlike the standard hasher is
+/// stable across Rust versions and platforms
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Writes a length-prefixed field so adjacent fields cannot run
+    /// into each other
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+}
+
+impl Arguments {
+    /// Computes a fingerprint of the effective configuration, for
+    /// detecting whether a run uses the same configuration as an
+    /// earlier one. See `fingerprint_excluding`
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint_excluding(&[])
+    }
+
+    /// Computes a fingerprint of the effective configuration over the
+    /// keys, sorted, their values in order and the positionals. Where
+    /// values came from is ignored, and the result is stable across
+    /// platforms and releases of this crate
+    ///
+    /// # Arguments
+    ///
+    /// `ignored`: Keys left out, such as secrets or output paths
+    pub fn fingerprint_excluding(&self, ignored: &[&str]) -> u64 {
+        let mut keys: Vec<&str> = self
+            .arg_map
+            .keys()
+            .map(|key| key.as_str())
+            .filter(|key| !ignored.contains(key))
+            .collect();
+        keys.sort_unstable();
+        let mut hash = Fnv(FNV_OFFSET);
+        for key in keys {
+            hash.write_field(key.as_bytes());
+            let vals = self.get_vec(key).map(Vec::as_slice).unwrap_or(&[]);
+            hash.write(&(vals.len() as u64).to_le_bytes());
+            for val in vals {
+                match val {
+                    Some(val) => {
+                        hash.write(&[1]);
+                        hash.write_field(val.as_bytes());
+                    }
+                    None => hash.write(&[0]),
+                }
+            }
+        }
+        hash.write(&[0xff]);
+        for positional in self.positionals() {
+            hash.write_field(positional.as_bytes());
+        }
+        hash.0
+    }
+
+    /// Returns `fingerprint_excluding` as 16 lowercase hex digits
+    ///
+    /// # Arguments
+    ///
+    /// `ignored`: Keys left out, such as secrets or output paths
+    pub fn fingerprint_hex(&self, ignored: &[&str]) -> String {
+        format!("{:016x}", self.fingerprint_excluding(ignored))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Arguments, Source};
+
+    #[test]
+    fn fingerprints() {
+        let args = Arguments::parse(&["-level", "2", "-verbose", "-token", "a"]);
+        let same = Arguments::parse_with_source(
+            &["-verbose", "-token", "b", "-level", "2"],
+            Source::Env("ARGS".to_string()),
+        );
+        assert_ne!(args.fingerprint(), same.fingerprint());
+        assert_eq!(
+            args.fingerprint_excluding(&["token"]),
+            same.fingerprint_excluding(&["token"])
+        );
+        assert_ne!(
+            Arguments::parse(&["-a", "bc"]).fingerprint(),
+            Arguments::parse(&["-ab", "c"]).fingerprint()
+        );
+        assert_eq!(args.fingerprint_hex(&["token"]).len(), 16);
+        assert_eq!(
+            Arguments::parse::<&str>(&[]).fingerprint_hex(&[]),
+            "af64724c8602eb6e"
+        );
+    }
+}
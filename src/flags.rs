@@ -0,0 +1,97 @@
+use std::error::Error;
+use std::fmt;
+
+use bitflags::Flags;
+
+use crate::Arguments;
+
+/// Error returned when a value names flags that are not defined
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownFlags {
+    /// The key of the argument
+    pub key: String,
+    /// The unknown names, in the order they were given
+    pub names: Vec<String>,
+}
+
+impl fmt::Display for UnknownFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown flags for -{}: {}",
+            self.key,
+            self.names.join(", ")
+        )
+    }
+}
+
+impl Error for UnknownFlags {}
+
+impl Arguments {
+    /// Gets the flags named by every value with the given key, which
+    /// may each list several names separated by `|`, as in
+    /// `-features a|b -features c`. Names are matched ignoring ASCII
+    /// case, and every unknown name is reported. Returns `None` when the
+    /// argument is absent
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_flags<F: Flags>(&self, key: &str) -> Option<Result<F, UnknownFlags>> {
+        let mut flags = F::empty();
+        let mut unknown = Vec::new();
+        let names = self
+            .get_vec(key)?
+            .iter()
+            .filter_map(Option::as_deref)
+            .flat_map(|val| val.split('|'))
+            .map(str::trim)
+            .filter(|name| !name.is_empty());
+        for name in names {
+            match F::FLAGS
+                .iter()
+                .find(|flag| flag.name().eq_ignore_ascii_case(name))
+            {
+                Some(flag) => flags.insert(F::from_bits_retain(flag.value().bits())),
+                None => unknown.push(name.to_string()),
+            }
+        }
+        Some(if unknown.is_empty() {
+            Ok(flags)
+        } else {
+            Err(UnknownFlags {
+                key: key.to_string(),
+                names: unknown,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Arguments;
+
+    bitflags::bitflags! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct Features: u8 {
+            const TLS = 1;
+            const GZIP = 2;
+            const HTTP2 = 4;
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let args = Arguments::parse(&["-features", "tls|gzip", "-features", "HTTP2", "-x"]);
+        assert_eq!(
+            args.get_flags::<Features>("features"),
+            Some(Ok(Features::all()))
+        );
+        assert_eq!(args.get_flags::<Features>("x"), Some(Ok(Features::empty())));
+        assert_eq!(args.get_flags::<Features>("missing"), None);
+        let args = Arguments::parse(&["-features", "tls|brotli|zstd"]);
+        let err = args.get_flags::<Features>("features").unwrap().unwrap_err();
+        assert_eq!(err.names, ["brotli", "zstd"]);
+        assert_eq!(err.to_string(), "unknown flags for -features: brotli, zstd");
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::{Mutex, PoisonError};
+
+use crate::Arguments;
+
+/// Parsed arguments that can no longer change, recording every access
+/// along with a tag naming the component that made it. At shutdown the
+/// recorded accesses tell which components consumed which arguments,
+/// for audits and for documenting a program's configuration
+pub struct Frozen {
+    args: Arguments,
+    accesses: Mutex<Vec<(String, String)>>,
+}
+
+impl Arguments {
+    /// Freezes the arguments, so they can only be read through accesses
+    /// that are recorded
+    pub fn freeze(self) -> Frozen {
+        Frozen {
+            args: self,
+            accesses: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Frozen {
+    /// Records an access to a key by a component
+    fn access(&self, tag: &str, key: &str) {
+        self.accesses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((key.to_string(), tag.to_string()));
+    }
+
+    /// Checks whether an argument is present like `Arguments::contains`,
+    /// recording the access
+    ///
+    /// # Arguments
+    ///
+    /// `tag`: The component making the access
+    ///
+    /// `key`: The key of the argument
+    pub fn contains(&self, tag: &str, key: &str) -> bool {
+        self.access(tag, key);
+        self.args.contains(key)
+    }
+
+    /// Gets the first value of an argument like `Arguments::get`,
+    /// recording the access
+    ///
+    /// # Arguments
+    ///
+    /// `tag`: The component making the access
+    ///
+    /// `key`: The key of the argument
+    pub fn get(&self, tag: &str, key: &str) -> Option<Option<&str>> {
+        self.access(tag, key);
+        self.args.get(key)
+    }
+
+    /// Gets every value of an argument like `Arguments::get_vec`,
+    /// recording the access
+    ///
+    /// # Arguments
+    ///
+    /// `tag`: The component making the access
+    ///
+    /// `key`: The key of the argument
+    pub fn get_vec(&self, tag: &str, key: &str) -> Option<&Vec<Option<String>>> {
+        self.access(tag, key);
+        self.args.get_vec(key)
+    }
+
+    /// Returns the positional arguments, which are not recorded
+    pub fn positionals(&self) -> &[String] {
+        self.args.positionals()
+    }
+
+    /// Returns every recorded access as the key and the tag of the
+    /// component, in the order they were made
+    pub fn accesses(&self) -> Vec<(String, String)> {
+        self.accesses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns every key that was accessed with the components that
+    /// accessed it, both sorted and without duplicates, including keys
+    /// that were accessed but absent
+    pub fn consumers(&self) -> Vec<(String, Vec<String>)> {
+        let mut consumers: Vec<(String, Vec<String>)> = Vec::new();
+        for (key, tag) in self.accesses() {
+            match consumers.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, tags)) => tags.push(tag),
+                None => consumers.push((key, vec![tag])),
+            }
+        }
+        for (_, tags) in &mut consumers {
+            tags.sort_unstable();
+            tags.dedup();
+        }
+        consumers.sort_unstable();
+        consumers
+    }
+
+    /// Returns the keys that were given but never accessed, sorted
+    pub fn unconsumed(&self) -> Vec<String> {
+        let accesses = self.accesses();
+        let mut keys: Vec<String> = self
+            .args
+            .arg_map
+            .keys()
+            .filter(|key| {
+                !accesses
+                    .iter()
+                    .any(|(accessed, _)| accessed == key.as_str())
+            })
+            .map(ToString::to_string)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Arguments;
+
+    #[test]
+    fn recorded_accesses() {
+        let frozen = Arguments::parse(&["-port", "80", "-host", "a", "-debug"]).freeze();
+        assert_eq!(frozen.get("server", "port"), Some(Some("80")));
+        assert_eq!(frozen.get("client", "port"), Some(Some("80")));
+        assert!(frozen.contains("server", "host"));
+        assert!(frozen.get_vec("server", "port").is_some());
+        assert_eq!(frozen.get("client", "timeout"), None);
+        assert_eq!(
+            frozen.accesses()[..2],
+            [
+                ("port".to_string(), "server".to_string()),
+                ("port".to_string(), "client".to_string())
+            ]
+        );
+        assert_eq!(
+            frozen.consumers(),
+            [
+                ("host".to_string(), vec!["server".to_string()]),
+                (
+                    "port".to_string(),
+                    vec!["client".to_string(), "server".to_string()]
+                ),
+                ("timeout".to_string(), vec!["client".to_string()]),
+            ]
+        );
+        assert_eq!(frozen.unconsumed(), ["debug"]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::{Arguments, Source, Spec, SubcommandError};
+
+/// Types that can be built from raw arguments according to a
+/// specification. This is usually implemented with
+/// `#[derive(SimpleArgs)]` from the `derive` feature
+pub trait SimpleArgs: Sized {
+    /// Returns the specification of the accepted arguments
+    fn spec() -> Spec;
+
+    /// Builds the value from raw arguments, excluding the program name
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    fn from_args<S: AsRef<str>>(args: &[S]) -> Result<Self, FromArgsError> {
+        Self::from_args_with(args, |var| env::var(var).ok())
+    }
+
+    /// Builds the value from raw arguments like `from_args`, reading
+    /// environment fallbacks through a lookup function instead of the
+    /// process environment
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    fn from_args_with<S, F>(args: &[S], lookup: F) -> Result<Self, FromArgsError>
+    where
+        S: AsRef<str>,
+        F: Fn(&str) -> Option<String>;
+
+    /// Builds the value from a full command line whose first argument
+    /// is the program name, as collected from `std::env::args`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The command line
+    fn parse<S: AsRef<str>>(args: &[S]) -> Result<Self, FromArgsError> {
+        Self::from_args(args.get(1..).unwrap_or(&[]))
+    }
+}
+
+/// Error building a value from arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FromArgsError {
+    /// A required argument is not present
+    Missing(String),
+    /// An argument that needs a value is present without one
+    MissingValue(String),
+    /// A value could not be converted to the type of its argument. The
+    /// source tells whether the value came from the command line or a
+    /// fallback such as an environment variable
+    Invalid {
+        key: String,
+        value: String,
+        source: Source,
+        message: String,
+    },
+    /// The subcommand could not be resolved
+    Subcommand(SubcommandError),
+}
+
+impl fmt::Display for FromArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromArgsError::Missing(key) => write!(f, "missing required argument -{}", key),
+            FromArgsError::MissingValue(key) => write!(f, "missing value for -{}", key),
+            FromArgsError::Invalid {
+                key,
+                value,
+                source: Source::CommandLine,
+                message,
+            } => write!(f, "invalid value '{}' for -{}: {}", value, key, message),
+            FromArgsError::Invalid {
+                key,
+                value,
+                source,
+                message,
+            } => write!(
+                f,
+                "invalid value '{}' for -{} from {}: {}",
+                value, key, source, message
+            ),
+            FromArgsError::Subcommand(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for FromArgsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FromArgsError::Subcommand(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SubcommandError> for FromArgsError {
+    fn from(err: SubcommandError) -> FromArgsError {
+        FromArgsError::Subcommand(err)
+    }
+}
+
+/// Converts the first value with the given key after rewriting it with
+/// a normalization function. Errors name the value as it was written
+pub(crate) fn convert_first<T: FromStr, F: Fn(&str) -> Cow<'_, str>>(
+    args: &Arguments,
+    key: &str,
+    normalize: F,
+) -> Result<Option<T>, FromArgsError>
+where
+    T::Err: Display,
+{
+    match args.values_with_sources(key).next() {
+        None => Ok(None),
+        Some((None, _)) => Err(FromArgsError::MissingValue(key.to_string())),
+        Some((Some(value), source)) => {
+            normalize(value)
+                .parse()
+                .map(Some)
+                .map_err(|err: T::Err| FromArgsError::Invalid {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    source: source.clone(),
+                    message: err.to_string(),
+                })
+        }
+    }
+}
+
+/// Support functions called by code generated by the derive macro
+#[doc(hidden)]
+pub mod __private {
+    use std::borrow::Cow;
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use super::FromArgsError;
+    use crate::{Arguments, Spec};
+
+    pub fn flag(args: &Arguments, key: &str) -> bool {
+        args.get_bool(key).unwrap_or(false)
+    }
+
+    pub fn optional<T: FromStr>(args: &Arguments, key: &str) -> Result<Option<T>, FromArgsError>
+    where
+        T::Err: Display,
+    {
+        super::convert_first(args, key, |value| Cow::Borrowed(value))
+    }
+
+    pub fn multiple<T: FromStr>(args: &Arguments, key: &str) -> Result<Vec<T>, FromArgsError>
+    where
+        T::Err: Display,
+    {
+        args.values_with_sources(key)
+            .map(|(value, source)| {
+                let value = value.ok_or_else(|| FromArgsError::MissingValue(key.to_string()))?;
+                value.parse().map_err(|err: T::Err| FromArgsError::Invalid {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    source: source.clone(),
+                    message: err.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn required<T: FromStr>(args: &Arguments, key: &str) -> Result<T, FromArgsError>
+    where
+        T::Err: Display,
+    {
+        optional(args, key)?.ok_or_else(|| FromArgsError::Missing(key.to_string()))
+    }
+
+    pub fn with_subcommands(spec: Spec, from: Spec) -> Spec {
+        let spec = from
+            .subcommands()
+            .iter()
+            .cloned()
+            .fold(spec, |spec, sub| spec.subcommand(sub));
+        match from.get_default_subcommand() {
+            Some(name) => spec.default_subcommand(name.to_string()),
+            None => spec,
+        }
+    }
+}
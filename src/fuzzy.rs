@@ -0,0 +1,91 @@
+/// Computes the Levenshtein edit distance between two strings,
+/// counting characters rather than bytes
+///
+/// # Arguments
+///
+/// `a`: The first string
+///
+/// `b`: The second string
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// Returns the largest edit distance at which a candidate is still a
+/// plausible typo of the query, allowing roughly one typo per three
+/// characters
+///
+/// # Arguments
+///
+/// `query`: The text that was typed
+pub(crate) fn max_distance(query: &str) -> usize {
+    (query.chars().count() / 3).max(1)
+}
+
+/// Ranks candidates that are plausible typos of the query, closest
+/// first, with ties kept in candidate order. Each candidate is paired
+/// with its value so several spellings can suggest the same value
+///
+/// # Arguments
+///
+/// `query`: The text that was typed
+///
+/// `candidates`: Pairs of spellings and the value they suggest
+pub(crate) fn rank<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(
+    query: &str,
+    candidates: I,
+) -> Vec<(usize, &'a str)> {
+    let max = max_distance(query);
+    let mut ranked: Vec<(usize, &'a str)> = Vec::new();
+    for (spelling, value) in candidates {
+        let distance = levenshtein(query, spelling);
+        if distance > max {
+            continue;
+        }
+        match ranked.iter_mut().find(|(_, existing)| *existing == value) {
+            Some(entry) => entry.0 = entry.0.min(distance),
+            None => ranked.push((distance, value)),
+        }
+    }
+    ranked.sort_by_key(|&(distance, _)| distance);
+    ranked
+}
+
+#[cfg(test)]
+mod test {
+    use super::{levenshtein, rank};
+
+    #[test]
+    fn distances() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("proxy", "proxy"), 0);
+        assert_eq!(levenshtein("proxy", "prxy"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn ranking() {
+        let candidates = vec![("stage", "stage"), ("status", "status"), ("stat", "status")];
+        assert_eq!(
+            rank("stagus", candidates.clone()),
+            vec![(1, "status"), (2, "stage")]
+        );
+        assert_eq!(rank("sta", candidates.clone()), vec![(1, "status")]);
+        assert!(rank("commit", candidates).is_empty());
+    }
+}
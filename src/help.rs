@@ -0,0 +1,544 @@
+#[cfg(feature = "help")]
+use std::fmt::Write;
+
+use crate::{ArgSpec, Arguments, PositionalSpec, Spec, ValidationError, Violation};
+
+/// Expands to the version of the crate it is used in, from
+/// `CARGO_PKG_VERSION`, for `Spec::version`
+#[macro_export]
+macro_rules! crate_version {
+    () => {
+        env!("CARGO_PKG_VERSION")
+    };
+}
+
+/// Renders an operand as shown in usage lines, where a greedy operand
+/// is followed by `...`
+fn operand_usage(positional: &PositionalSpec) -> String {
+    if positional.is_greedy() {
+        format!("{}...", positional.name())
+    } else {
+        positional.name().to_string()
+    }
+}
+
+/// Renders an argument with the placeholder of its value, as in
+/// `-port <PORT>`, where an optional value is shown attached and
+/// multiple values are marked with `...`
+fn arg_usage(arg: &ArgSpec) -> String {
+    if arg.is_flag() {
+        format!("-{}", arg.name())
+    } else if arg.is_multi_value() {
+        format!("-{} <{}>...", arg.name(), arg.get_value_name())
+    } else if arg.is_glued_value() {
+        format!("-{}<{}>", arg.name(), arg.get_value_name())
+    } else if arg.is_optional_value() {
+        format!("-{}[=<{}>]", arg.name(), arg.get_value_name())
+    } else {
+        format!("-{} <{}>", arg.name(), arg.get_value_name())
+    }
+}
+
+/// Renders the synopsis of an argument like `arg_usage`, with its
+/// aliases following its name as in `-verbose, -v`
+#[cfg(feature = "help")]
+fn aliased_usage(arg: &ArgSpec) -> String {
+    let usage = arg_usage(arg);
+    let (name, value) = usage.split_at(1 + arg.name().len());
+    let mut out = name.to_string();
+    for alias in arg.get_aliases() {
+        let _ = write!(out, ", -{}", alias);
+    }
+    out.push_str(value);
+    out
+}
+
+/// Renders the description of an argument followed by its details in
+/// parentheses: the type of its value when asked for, its choices, its
+/// default and whether it is experimental
+fn arg_details(arg: &ArgSpec, with_type: bool) -> String {
+    let mut details = Vec::new();
+    if let Some(value_type) = arg.get_value_type().filter(|_| with_type) {
+        details.push(format!("type: {}", value_type));
+    }
+    if !arg.get_choices().is_empty() {
+        details.push(format!("choices: {}", arg.get_choices().join(", ")));
+    }
+    if let Some(default) = arg.get_default_value() {
+        details.push(format!("default: {}", default));
+    }
+    if arg.is_experimental() {
+        details.push(String::from("experimental"));
+    }
+    match (arg.get_description(), details.is_empty()) {
+        (description, true) => description.to_string(),
+        ("", false) => format!("({})", details.join(", ")),
+        (description, false) => format!("{} ({})", description, details.join(", ")),
+    }
+}
+
+/// Renders aligned `name  description` rows under a heading, skipping
+/// the section entirely when it has no rows
+#[cfg(feature = "help")]
+fn render_rows<D: AsRef<str>>(out: &mut String, heading: &str, rows: &[(String, D)]) {
+    if rows.is_empty() {
+        return;
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let _ = writeln!(out, "{}:", heading);
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, description) in rows {
+        let description = description.as_ref();
+        if description.is_empty() {
+            let _ = writeln!(out, "  {}", name);
+        } else {
+            let _ = writeln!(out, "  {:width$}  {}", name, description, width = width);
+        }
+    }
+}
+
+/// Renders a list of arguments as aligned `-name <value>  description`
+/// rows, where descriptions end with the default value if there is one
+#[cfg(feature = "help")]
+fn render_args<'a, I: IntoIterator<Item = &'a ArgSpec>>(out: &mut String, heading: &str, args: I) {
+    let rows: Vec<(String, String)> = args
+        .into_iter()
+        .map(|arg| (aliased_usage(arg), arg_details(arg, false)))
+        .collect();
+    render_rows(out, heading, &rows);
+}
+
+impl Spec {
+    /// Renders a single usage line listing every argument, subcommand
+    /// and operand, without descriptions, alignment or examples. This
+    /// formatter is always available and is what `help` falls back to
+    /// when the `help` feature is disabled to keep binaries small
+    pub fn minimal_help(&self) -> String {
+        let mut out = String::from("usage:");
+        if let Some(program) = self.get_program() {
+            out.push(' ');
+            out.push_str(program);
+        }
+        for arg in self.visible_args() {
+            out.push_str(" [");
+            out.push_str(&arg_usage(arg));
+            out.push(']');
+        }
+        for (i, sub) in self.subcommands().iter().enumerate() {
+            out.push_str(if i == 0 { " <" } else { "|" });
+            out.push_str(sub.name());
+        }
+        if !self.subcommands().is_empty() {
+            out.push('>');
+        }
+        for positional in self.positionals() {
+            out.push(' ');
+            out.push_str(&operand_usage(positional));
+        }
+        out.push('\n');
+        out
+    }
+
+    /// Renders the version line answering `-version`, naming the program
+    /// when it is declared, or `None` when no version is declared
+    pub fn version_line(&self) -> Option<String> {
+        let version = self.get_version()?;
+        Some(match self.get_program() {
+            Some(program) => format!("{} {}\n", program, version),
+            None => format!("{}\n", version),
+        })
+    }
+
+    /// Renders the help entry of a single argument for error messages:
+    /// its synopsis, description, type, choices and default, or `None`
+    /// when it is not declared
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    pub fn usage_entry(&self, key: &str) -> Option<String> {
+        let arg = self.get(key)?;
+        let details = arg_details(arg, true);
+        Some(if details.is_empty() {
+            arg_usage(arg)
+        } else {
+            format!("{}  {}", arg_usage(arg), details)
+        })
+    }
+
+    /// Renders a validation error with the help entry of the argument
+    /// each violation is about indented below it, rather than the full
+    /// usage. An unknown key shows the entry of its suggestion
+    ///
+    /// # Arguments
+    ///
+    /// `err`: The validation error
+    pub fn explain(&self, err: &ValidationError) -> String {
+        let mut out = String::new();
+        for violation in &err.violations {
+            out.push_str(&violation.to_string());
+            out.push('\n');
+            let key = match violation {
+                Violation::Unknown { suggestion, .. } => suggestion.as_deref(),
+                violation => Some(violation.key()),
+            };
+            if let Some(entry) = key.and_then(|key| self.usage_entry(key)) {
+                out.push_str("  ");
+                out.push_str(&entry);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Renders help text using the minimal formatter, as the `help`
+    /// feature is disabled
+    #[cfg(not(feature = "help"))]
+    pub fn help(&self) -> String {
+        self.minimal_help()
+    }
+
+    /// Renders help text listing the version, usage, subcommands,
+    /// declared arguments and examples. Global arguments are listed in
+    /// their own section, which is how subcommands show the flags they
+    /// inherit
+    #[cfg(feature = "help")]
+    pub fn help(&self) -> String {
+        let mut out = self.version_line().unwrap_or_default();
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if let Some(program) = self.get_program() {
+            let subcommand = if self.subcommands().is_empty() {
+                ""
+            } else {
+                " [<subcommand>]"
+            };
+            let _ = write!(out, "Usage: {}{} [arguments]", program, subcommand);
+            for positional in self.positionals() {
+                let _ = write!(out, " {}", operand_usage(positional));
+            }
+            out.push('\n');
+        }
+        if !self.get_description().is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "{}", self.get_description());
+        }
+        let subcommands: Vec<(String, &str)> = self
+            .subcommands()
+            .iter()
+            .map(|sub| {
+                let names: Vec<&str> = std::iter::once(sub.name()).chain(sub.aliases()).collect();
+                (names.join(", "), sub.get_description())
+            })
+            .collect();
+        render_rows(&mut out, "Subcommands", &subcommands);
+        let operands: Vec<(String, &str)> = self
+            .positionals()
+            .iter()
+            .map(|positional| (operand_usage(positional), positional.get_description()))
+            .collect();
+        render_rows(&mut out, "Operands", &operands);
+        render_args(
+            &mut out,
+            "Arguments",
+            self.visible_args().filter(|arg| !arg.is_global()),
+        );
+        render_args(
+            &mut out,
+            "Global arguments",
+            self.visible_args().filter(|arg| arg.is_global()),
+        );
+        let examples: Vec<(String, &str)> = self
+            .examples()
+            .map(|example| (example.to_string(), ""))
+            .collect();
+        render_rows(&mut out, "Examples", &examples);
+        out
+    }
+
+    /// Renders help text listing only the arguments matching a query,
+    /// as found by `search`
+    ///
+    /// # Arguments
+    ///
+    /// `query`: The text to search for
+    #[cfg(feature = "help")]
+    pub fn help_search(&self, query: &str) -> String {
+        let matches = self.search(query);
+        if matches.is_empty() {
+            return format!("No arguments matching '{}'\n", query);
+        }
+        let mut out = String::new();
+        render_args(
+            &mut out,
+            &format!("Arguments matching '{}'", query),
+            matches,
+        );
+        out
+    }
+
+    /// Renders the help text requested by the parsed arguments, if any,
+    /// after which the program is expected to exit. A bare `-help`
+    /// produces the full help, while `-help <query>` produces only the
+    /// arguments matching the query. When a version is declared,
+    /// `-version` produces the version line unless help is requested
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    #[cfg(feature = "help")]
+    pub fn help_for(&self, args: &Arguments) -> Option<String> {
+        match args.get("help") {
+            Some(Some(query)) => Some(self.help_search(query)),
+            Some(None) => Some(self.help()),
+            None => self.version_for(args),
+        }
+    }
+
+    /// Renders the help text requested by the parsed arguments, if any,
+    /// after which the program is expected to exit. Search queries are
+    /// ignored as the `help` feature is disabled, and `-version` is
+    /// answered when a version is declared
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    #[cfg(not(feature = "help"))]
+    pub fn help_for(&self, args: &Arguments) -> Option<String> {
+        match args.get("help") {
+            Some(_) => Some(self.help()),
+            None => self.version_for(args),
+        }
+    }
+
+    /// Renders the version line when the parsed arguments request it
+    fn version_for(&self, args: &Arguments) -> Option<String> {
+        if args.contains("version") {
+            self.version_line()
+        } else {
+            None
+        }
+    }
+
+    /// Renders the help text requested by raw arguments, scoped to the
+    /// subcommand they invoke, found with `split_at_subcommand`.
+    /// `tool -verbose <sub> -help` produces the help of the subcommand
+    /// including the global arguments it inherits, while `tool -help`
+    /// produces the help of the whole program
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The raw arguments, excluding the program name
+    pub fn scoped_help_for<S: AsRef<str>>(&self, args: &[S]) -> Option<String> {
+        let (globals, rest) = self.split_at_subcommand(args);
+        if let Some((first, rest)) = rest.split_first() {
+            if let Some(sub) = self.find_subcommand(first.as_ref()) {
+                let args: Vec<&str> = globals
+                    .iter()
+                    .map(AsRef::as_ref)
+                    .filter(|&arg| arg != "--")
+                    .chain(rest.iter().map(AsRef::as_ref))
+                    .collect();
+                return self.scoped(sub).help_for(&Arguments::parse(&args));
+            }
+        }
+        self.help_for(&Arguments::parse(args))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Spec, Subcommand};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(ArgSpec::new("proxy").description("Proxy server"))
+            .arg(ArgSpec::new("no-proxy").description("Hosts that bypass the proxy"))
+            .arg(ArgSpec::new("quiet"))
+    }
+
+    #[test]
+    fn minimal_help() {
+        let spec = spec()
+            .program("tool")
+            .arg(ArgSpec::new("debug").flag())
+            .subcommand(Subcommand::new("status"))
+            .subcommand(Subcommand::new("remove"));
+        assert_eq!(
+            spec.minimal_help(),
+            "usage: tool [-proxy <value>] [-no-proxy <value>] [-quiet <value>] [-debug] <status|remove>\n"
+        );
+        assert_eq!(Spec::new().minimal_help(), "usage:\n");
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn full_help() {
+        let args = Arguments::parse(&["-help"]);
+        assert_eq!(
+            spec().help_for(&args).unwrap(),
+            "Arguments:\n  -proxy <value>     Proxy server\n  -no-proxy <value>  Hosts that bypass the proxy\n  -quiet <value>\n"
+        );
+        let spec = Spec::new()
+            .arg(
+                ArgSpec::new("port")
+                    .value_name("PORT")
+                    .default_value("8080")
+                    .description("Port to listen on"),
+            )
+            .arg(ArgSpec::new("color").optional_value().default_value("auto"))
+            .arg(ArgSpec::new("debug").flag().description("Log everything"))
+            .arg(ArgSpec::new("output").aliases(&["o"]));
+        assert_eq!(
+            spec.help(),
+            "Arguments:\n\
+             \x20 -port <PORT>         Port to listen on (default: 8080)\n\
+             \x20 -color[=<value>]     (default: auto)\n\
+             \x20 -debug               Log everything\n\
+             \x20 -output, -o <value>\n"
+        );
+        assert_eq!(
+            spec.minimal_help(),
+            "usage: [-port <PORT>] [-color[=<value>]] [-debug] [-output <value>]\n"
+        );
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn search_help() {
+        let args = Arguments::parse(&["-help", "proxy"]);
+        assert_eq!(
+            spec().help_for(&args).unwrap(),
+            "Arguments matching 'proxy':\n  -proxy <value>     Proxy server\n  -no-proxy <value>  Hosts that bypass the proxy\n"
+        );
+        let args = Arguments::parse(&["-help", "color"]);
+        assert_eq!(
+            spec().help_for(&args).unwrap(),
+            "No arguments matching 'color'\n"
+        );
+        assert!(spec().help_for(&Arguments::parse(&["-proxy"])).is_none());
+    }
+
+    #[cfg(feature = "help")]
+    #[test]
+    fn subcommand_help() {
+        let spec = Spec::new()
+            .program("tool")
+            .arg(ArgSpec::new("verbose").description("Print more").global())
+            .arg(ArgSpec::new("config").description("Config file"))
+            .subcommand(
+                Subcommand::new("remove")
+                    .alias("rm")
+                    .description("Remove files")
+                    .arg(ArgSpec::new("force").description("Ignore missing files"))
+                    .example("tool remove -force a.txt"),
+            );
+        assert_eq!(
+            spec.scoped_help_for(&["-help"]).unwrap(),
+            "Usage: tool [<subcommand>] [arguments]\n\n\
+             Subcommands:\n  remove, rm  Remove files\n\n\
+             Arguments:\n  -config <value>  Config file\n\n\
+             Global arguments:\n  -verbose <value>  Print more\n"
+        );
+        assert_eq!(
+            spec.scoped_help_for(&["rm", "-help"]).unwrap(),
+            "Usage: tool remove [arguments]\n\n\
+             Remove files\n\n\
+             Arguments:\n  -force <value>  Ignore missing files\n\n\
+             Global arguments:\n  -verbose <value>  Print more\n\n\
+             Examples:\n  tool remove -force a.txt\n"
+        );
+        assert!(spec.scoped_help_for(&["remove", "-force"]).is_none());
+        let scoped = spec.scoped_help_for(&["rm", "-help"]);
+        assert_eq!(
+            spec.scoped_help_for(&["-verbose", "1", "rm", "-help"]),
+            scoped
+        );
+        assert_eq!(
+            spec.scoped_help_for(&["-config=a.toml", "rm", "-help"]),
+            scoped
+        );
+        assert_eq!(
+            spec.scoped_help_for(&["-verbose", "--", "rm", "-help"]),
+            scoped
+        );
+        assert!(spec
+            .scoped_help_for(&["-verbose", "1", "rm", "-force"])
+            .is_none());
+    }
+
+    #[test]
+    fn usage_excerpt() {
+        let spec = Spec::new()
+            .arg(
+                ArgSpec::new("port")
+                    .value_name("PORT")
+                    .value_type("u16")
+                    .default_value("8080")
+                    .description("Port to listen on")
+                    .max_occurrences(1),
+            )
+            .arg(ArgSpec::new("verbose").flag())
+            .arg(
+                ArgSpec::new("color")
+                    .choices(&["auto", "never"])
+                    .default_value("auto"),
+            );
+        assert_eq!(
+            spec.usage_entry("port").unwrap(),
+            "-port <PORT>  Port to listen on (type: u16, default: 8080)"
+        );
+        assert_eq!(spec.usage_entry("verbose").unwrap(), "-verbose");
+        let err = spec
+            .validate(&spec.parse(&[
+                "-port", "1", "-port", "2", "-color", "blue", "-verbos", "-x",
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            spec.explain(&err),
+            "-port may appear at most 1 time, but appears 2 times\n\
+             \x20 -port <PORT>  Port to listen on (type: u16, default: 8080)\n\
+             'blue' is not a valid value for -color; expected one of auto, never\n\
+             \x20 -color <value>  (choices: auto, never, default: auto)\n\
+             unknown argument -verbos; did you mean -verbose?\n\
+             \x20 -verbose\n\
+             unknown argument -x\n"
+        );
+    }
+
+    #[test]
+    fn version() {
+        let versioned = spec().program("tool").version("1.2.0");
+        assert_eq!(
+            versioned
+                .help_for(&Arguments::parse(&["--version"]))
+                .unwrap(),
+            "tool 1.2.0\n"
+        );
+        assert!(spec().help_for(&Arguments::parse(&["-version"])).is_none());
+        assert_eq!(
+            Spec::new()
+                .version(crate_version!())
+                .version_line()
+                .unwrap(),
+            format!("{}\n", env!("CARGO_PKG_VERSION"))
+        );
+        let sub = Spec::new()
+            .subcommand(Subcommand::new("status"))
+            .version("2.0");
+        assert_eq!(
+            sub.scoped_help_for(&["status", "-version"]).unwrap(),
+            "2.0\n"
+        );
+        #[cfg(feature = "help")]
+        assert!(versioned
+            .help_for(&Arguments::parse(&["-help", "-version"]))
+            .unwrap()
+            .starts_with("tool 1.2.0\n\nUsage: tool [arguments]\n"));
+    }
+}
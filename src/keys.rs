@@ -0,0 +1,99 @@
+/// Declares an enum of the known keys of a program along with their
+/// specifications, so a `match` over the keys stops compiling when the
+/// specification grows an argument that is not handled. Each variant is
+/// paired with a constant `ArgSpec` expression, and the generated enum
+/// provides:
+///
+/// `ALL`: Every key, in declaration order
+///
+/// `ARGS`: The specification of every key, in declaration order
+///
+/// `spec()`: A `Spec` declaring every key
+///
+/// `name()`: The key of a variant, without the leading `-`
+///
+/// `from_key()`: The variant of a parsed key, if it is known
+///
+/// `present()`: The variants present in parsed arguments, in
+/// declaration order
+#[macro_export]
+macro_rules! keys {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $arg:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant),*
+        }
+
+        impl $name {
+            /// Every key, in declaration order
+            pub const ALL: &'static [$name] = &[$($name::$variant),*];
+
+            /// The specification of every key, in declaration order
+            pub const ARGS: &'static [$crate::ArgSpec] = &[$($arg),*];
+
+            /// Creates a specification declaring every key
+            pub fn spec() -> $crate::Spec {
+                $crate::Spec::from_static(Self::ARGS)
+            }
+
+            /// Returns the key, without the leading `-`
+            pub fn name(self) -> &'static str {
+                Self::ARGS[self as usize].name()
+            }
+
+            /// Returns the variant of a parsed key, if it is known
+            pub fn from_key(key: &str) -> Option<$name> {
+                Self::ALL.iter().copied().find(|known| known.name() == key)
+            }
+
+            /// Returns the keys present in the arguments, in
+            /// declaration order
+            pub fn present(args: &$crate::Arguments) -> Vec<$name> {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .filter(|known| args.contains(known.name()))
+                    .collect()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments};
+
+    keys! {
+        enum Key {
+            Verbose => ArgSpec::new("verbose").flag(),
+            Port => ArgSpec::new("port").default_value("80"),
+            Log => ArgSpec::new("log-level"),
+        }
+    }
+
+    #[test]
+    fn exhaustive_keys() {
+        let spec = Key::spec();
+        assert_eq!(spec.args().len(), 3);
+        assert!(spec.get("log-level").is_some());
+        assert_eq!(Key::Log.name(), "log-level");
+        assert_eq!(Key::from_key("port"), Some(Key::Port));
+        assert_eq!(Key::from_key("missing"), None);
+        let args = Arguments::parse(&["-log-level", "debug", "-verbose"]);
+        let handled: Vec<&str> = Key::present(&args)
+            .into_iter()
+            .map(|key| match key {
+                Key::Verbose => "verbose",
+                Key::Port => "port",
+                Key::Log => "log",
+            })
+            .collect();
+        assert_eq!(handled, ["verbose", "log"]);
+    }
+}
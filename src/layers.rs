@@ -0,0 +1,220 @@
+use std::env;
+use std::io;
+use std::path::Path;
+
+use crate::{Arguments, Decision, MergePolicy, Precedence, Source, Spec};
+
+/// Combines defaults, configuration files, the environment and the
+/// command line into a single set of arguments, resolved by an explicit
+/// `Precedence`. Layers can be added in any order, since the precedence
+/// of their sources decides which values take effect
+pub struct Layers<'s> {
+    spec: &'s Spec,
+    precedence: Precedence,
+    args: Arguments,
+}
+
+impl Spec {
+    /// Starts combining sources of arguments for the specification with
+    /// the usual precedence of `Precedence::new`
+    pub fn layers(&self) -> Layers<'_> {
+        Layers {
+            spec: self,
+            precedence: Precedence::new(),
+            args: Arguments::empty(),
+        }
+    }
+}
+
+impl Layers<'_> {
+    /// Sets the precedence deciding which source takes effect
+    ///
+    /// # Arguments
+    ///
+    /// `precedence`: The precedence of the sources
+    pub fn precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Adds already parsed arguments, such as those injected by an
+    /// embedding front-end, keeping their sources
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn layer(mut self, args: Arguments) -> Self {
+        self.args.merge(args);
+        self
+    }
+
+    /// Adds the command line, parsed according to the specification
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, excluding the program name
+    pub fn cli<S: AsRef<str>>(self, args: &[S]) -> Self {
+        let args = self.spec.parse(args);
+        self.layer(args)
+    }
+
+    /// Adds a file, read with `Arguments::from_file` when it is a
+    /// `.toml` or `.json` file and the `config` feature is enabled, and
+    /// as a response file with `Arguments::parse_file` otherwise
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the file
+    pub fn file<P: AsRef<Path>>(self, path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        #[cfg(feature = "config")]
+        if matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("toml" | "json")
+        ) {
+            return Arguments::from_file(path).map(|args| self.layer(args));
+        }
+        Arguments::parse_file(path).map(|args| self.layer(args))
+    }
+
+    /// Adds the environment variables of the declared arguments, and
+    /// the files named by their `_FILE` variables, as read by
+    /// `Spec::apply_fallbacks`
+    pub fn env(self) -> Self {
+        self.env_with(|var| env::var(var).ok())
+    }
+
+    /// Adds the environment variables of the declared arguments like
+    /// `env`, reading them through a lookup function instead of the
+    /// process environment
+    ///
+    /// # Arguments
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub fn env_with<F: Fn(&str) -> Option<String>>(self, lookup: F) -> Self {
+        let mut fallbacks = Arguments::empty();
+        self.spec.apply_fallbacks_with(&mut fallbacks, lookup);
+        let mut env = Arguments::empty();
+        let mut keys: Vec<&str> = fallbacks.arg_map.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        for key in keys {
+            let vals = fallbacks.arg_map.get_vec(key).into_iter().flatten();
+            let sources = fallbacks.sources.get_vec(key).into_iter().flatten();
+            for (val, (source, _)) in vals.zip(sources) {
+                if *source != Source::Default {
+                    env.insert(key, val.clone(), source.clone());
+                }
+            }
+        }
+        self.layer(env)
+    }
+
+    /// Adds the default values of the declared arguments
+    pub fn defaults(self) -> Self {
+        let mut defaults = Arguments::empty();
+        self.spec.apply_defaults(&mut defaults);
+        self.layer(defaults)
+    }
+
+    /// Resolves the layers into a single set of arguments, where `get`
+    /// returns the value of the heaviest source. See `trace`
+    pub fn resolve(self) -> Arguments {
+        self.trace().0
+    }
+
+    /// Resolves the layers like `resolve`, also returning how the value
+    /// of each key was decided. Declared arguments with the `Replace`
+    /// merge policy only keep the values of their heaviest source, while
+    /// other arguments keep every value from the heaviest source to the
+    /// lightest
+    pub fn trace(self) -> (Arguments, Vec<Decision>) {
+        let mut args = self.args;
+        let decisions = self.precedence.apply(&mut args);
+        for decision in &decisions {
+            let replaced = self
+                .spec
+                .get(&decision.key)
+                .is_some_and(|arg| arg.get_merge_policy() == MergePolicy::Replace);
+            if !replaced {
+                continue;
+            }
+            let heaviest = decision.candidates[0].1;
+            let kept = decision
+                .candidates
+                .iter()
+                .take_while(|(_, weight)| *weight == heaviest)
+                .count();
+            if let Some(vals) = args.arg_map.get_vec_mut(decision.key.as_str()) {
+                vals.truncate(kept);
+            }
+            if let Some(sources) = args.sources.get_vec_mut(decision.key.as_str()) {
+                sources.truncate(kept);
+            }
+        }
+        (args, decisions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_dir::TestDir;
+    use crate::{ArgSpec, MergePolicy, Precedence, Source, SourceKind, Spec};
+    use std::fs;
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(ArgSpec::new("port").env("SIMPLE_ARGS_LAYERS_PORT"))
+            .arg(ArgSpec::new("host").default_value("localhost"))
+            .arg(ArgSpec::new("level").env("SIMPLE_ARGS_LAYERS_LEVEL"))
+            .arg(ArgSpec::new("include").merge_policy(MergePolicy::Append))
+    }
+
+    fn lookup(var: &str) -> Option<String> {
+        match var {
+            "SIMPLE_ARGS_LAYERS_PORT" => Some("9000".to_string()),
+            "SIMPLE_ARGS_LAYERS_LEVEL" => Some("debug".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn layered() {
+        let dir = TestDir::new("layered");
+        let path = dir.join("layers");
+        fs::write(&path, "-port\n80\n-level\nwarn\n-include\nb\n").unwrap();
+        let spec = spec();
+        let args = spec
+            .layers()
+            .defaults()
+            .file(&path)
+            .unwrap()
+            .env_with(lookup)
+            .cli(&["-port", "8080", "-include", "a"])
+            .resolve();
+        assert_eq!(args.get("port"), Some(Some("8080")));
+        assert_eq!(args.get_vec("port").unwrap().len(), 1);
+        assert_eq!(args.get("level"), Some(Some("debug")));
+        assert_eq!(
+            args.values_with_sources("level").next().unwrap().1,
+            &Source::Env("SIMPLE_ARGS_LAYERS_LEVEL".to_string())
+        );
+        assert_eq!(args.get("host"), Some(Some("localhost")));
+        assert_eq!(
+            args.get_vec("include").unwrap(),
+            &[Some("a".to_string()), Some("b".to_string())]
+        );
+        let (args, decisions) = spec
+            .layers()
+            .precedence(Precedence::new().source(SourceKind::File, 50))
+            .env_with(lookup)
+            .file(&path)
+            .unwrap()
+            .cli(&["-port", "8080"])
+            .trace();
+        assert_eq!(args.get("port"), Some(Some("80")));
+        assert_eq!(args.get("level"), Some(Some("warn")));
+        let port = decisions.iter().find(|d| d.key == "port").unwrap();
+        assert_eq!(port.candidates.len(), 3);
+        assert_eq!(port.candidates[0], (Source::File(path), 50));
+    }
+}
@@ -1,48 +1,243 @@
+use std::str::FromStr;
+
 use multimap::MultiMap;
+use small::SmallStr;
+
+use timing::Timings;
+
+#[cfg(feature = "abi")]
+mod abi;
+#[cfg(feature = "arena")]
+mod arena;
+mod audit;
+mod batch;
+mod behavior;
+mod ci;
+mod compat;
+mod complete;
+#[cfg(feature = "config")]
+mod config;
+mod drift;
+mod environment;
+mod export;
+mod fallback;
+mod file;
+mod fingerprint;
+#[cfg(feature = "bitflags")]
+mod flags;
+mod freeze;
+mod from_args;
+mod fuzzy;
+mod help;
+mod keys;
+mod layers;
+mod lifecycle;
+mod limits;
+mod lint;
+mod locale;
+mod manifest;
+mod negate;
+mod operand;
+mod origin;
+mod pair;
+mod parse;
+mod pipeline;
+mod plugin;
+mod precedence;
+mod register;
+#[cfg(feature = "tokio")]
+mod remote;
+mod replay;
+mod resolve;
+mod restricted;
+mod sample;
+mod sanitize;
+mod small;
+mod snapshot;
+mod source;
+mod spec;
+mod subcommand;
+#[cfg(test)]
+mod test_dir;
+mod timing;
+mod typed;
+mod unstable;
+mod validate;
+
+#[cfg(feature = "abi")]
+pub use abi::{ArgsHandle, ArgsVTable, StrRef, ABI_VERSION};
+#[cfg(feature = "arena")]
+pub use arena::ArenaArguments;
+pub use audit::AuditPolicy;
+pub use batch::ArgumentsAccumulator;
+pub use behavior::Behavior;
+pub use ci::CI_SWITCH;
+pub use compat::{BreakingChange, IncompatibleError};
+pub use complete::COMPLETE_REQUEST;
+#[cfg(feature = "config")]
+pub use config::ConfigError;
+pub use drift::{Drift, ExportParseError, ExportedArg, ExportedSpec, SPEC_REQUEST};
+pub use environment::Environment;
+pub use file::FileValue;
+#[cfg(feature = "bitflags")]
+pub use flags::UnknownFlags;
+pub use freeze::Frozen;
+#[doc(hidden)]
+pub use from_args::__private;
+pub use from_args::{FromArgsError, SimpleArgs};
+pub use layers::Layers;
+pub use lifecycle::{Deprecation, Lifecycle};
+pub use limits::{Limits, LimitsExceeded};
+pub use lint::Lint;
+pub use locale::Locale;
+pub use manifest::{Manifest, ManifestEntry};
+pub use negate::NEGATION_PREFIX;
+pub use operand::OperandError;
+pub use parse::{Correction, ParseError};
+pub use pipeline::{PipelineError, Stage};
+pub use plugin::{Plugin, Plugins};
+pub use precedence::{Decision, Precedence};
+pub use register::RegisterArgs;
+#[cfg(feature = "tokio")]
+pub use remote::{Degradation, Layered};
+pub use replay::Recording;
+pub use resolve::{Resolve, Resolved};
+pub use restricted::Restricted;
+pub use sample::{FuzzCase, FuzzKind};
+pub use sanitize::{SanitizeError, Sanitizer};
+pub use snapshot::{ArcArguments, ArgsCell};
+pub use source::{Source, SourceKind};
+pub use spec::{ArgSpec, MergePolicy, PositionalSpec, Spec};
+pub use subcommand::{Subcommand, SubcommandError};
+pub use timing::{Phase, StartupReport};
+pub use typed::{Optional, Required};
+pub use unstable::UNSTABLE_SWITCH;
+pub use validate::{MissingArgs, ValidationError, Violation};
+
+#[cfg(feature = "arena")]
+pub use bumpalo::Bump as Arena;
+#[cfg(feature = "derive")]
+pub use simple_args_derive::SimpleArgs;
+
+/// Checks whether a token is a negative number such as `-5`, `-0.5` or
+/// `-1e3`, which can be read as a value rather than a key
+pub(crate) fn is_negative_number(token: &str) -> bool {
+    token.strip_prefix('-').is_some_and(|number| {
+        number.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+            && number.parse::<f64>().is_ok()
+    })
+}
+
+/// Strips the `--` or `-` prefix from a key, returning `None` when the
+/// token is not a key. A lone `--` only has its first `-` stripped
+pub(crate) fn strip_key(token: &str) -> Option<&str> {
+    match token.strip_prefix("--") {
+        Some(long) if !long.is_empty() => Some(long),
+        _ => token.strip_prefix('-'),
+    }
+}
 
 /// Parsed Arguments
 pub struct Arguments {
-    arg_map: MultiMap<String, Option<String>>,
+    arg_map: MultiMap<SmallStr, Option<String>>,
+    sources: MultiMap<SmallStr, (Source, usize)>,
+    positionals: Vec<String>,
+    captures: MultiMap<String, Vec<String>>,
+    raw_values: MultiMap<String, Option<String>>,
+    raw_regions: Vec<Vec<String>>,
+    corrections: Vec<Correction>,
+    operands: Vec<PositionalSpec>,
+    aliases: Vec<(&'static str, &'static str)>,
+    trailing: Vec<String>,
+    inserted: usize,
+    timings: Option<Timings>,
 }
 
 impl Arguments {
     /// Parse arguments. This cannot fail. Arguments are simply
-    /// denoted by a single `-` followed by the argument,
-    /// and the value is immediately after. Multiple instances
-    /// can be contained, and arguments can contain no value
+    /// denoted by a single `-` or a double `--` followed by the
+    /// argument, and the value is immediately after or attached with
+    /// the first `=`, as in `-output=/tmp/foo`. Multiple instances
+    /// can be contained, and arguments can contain no value. Everything
+    /// after `--` is left unparsed and available from `trailing`. A
+    /// token starting with `-` is always a key, so `-t -5` is the key `t`
+    /// without a value followed by the key `5`. Negative numbers are read
+    /// as values with `parse_with_behavior` from `Behavior::V3` on, with
+    /// `-t=-5`, or by a `Spec` declaring the key
     ///
     /// # Arguments
     ///
     /// `args`: The arguments
     pub fn parse<S: AsRef<str>>(args: &[S]) -> Arguments {
-        let mut arg_map = MultiMap::new();
-        for (key, val) in args.iter().map(|s| s.as_ref()).zip(
-            args.iter()
-                .map(|s| s.as_ref())
-                .skip(1)
-                .chain(std::iter::once("")),
-        ) {
-            if let Some(stripped) = key.strip_prefix('-') {
-                arg_map.insert(
-                    stripped.to_string(),
-                    if val.is_empty() || val.starts_with('-') {
-                        None
-                    } else {
-                        Some(val.to_string())
-                    },
-                );
+        Arguments::parse_with_source(args, Source::CommandLine)
+    }
+
+    /// Parse arguments like `parse`, failing on malformed tokens: a lone
+    /// `-`, or a key that is empty once its dashes are stripped
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn try_parse<S: AsRef<str>>(args: &[S]) -> Result<Arguments, ParseError> {
+        let arguments = Arguments::parse(args);
+        parse::check_keys(args, &arguments)?;
+        Ok(arguments)
+    }
+
+    /// Parse arguments like `parse`, attributing every value to the
+    /// given source rather than the command line
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    ///
+    /// `source`: Where the arguments came from
+    pub fn parse_with_source<S: AsRef<str>>(args: &[S], source: Source) -> Arguments {
+        let mut arguments = Arguments::empty();
+        let mut is_value = false;
+        for (i, (key, val)) in args
+            .iter()
+            .map(|s| s.as_ref())
+            .zip(
+                args.iter()
+                    .map(|s| s.as_ref())
+                    .skip(1)
+                    .chain(std::iter::once("")),
+            )
+            .enumerate()
+        {
+            if key == "--" {
+                arguments.trailing = args[i + 1..]
+                    .iter()
+                    .map(|s| s.as_ref().to_string())
+                    .collect();
+                break;
+            }
+            if let Some(stripped) = strip_key(key) {
+                let (stripped, val) = match stripped.split_once('=') {
+                    Some((stripped, attached)) => (stripped, Some(attached.to_string())),
+                    None if val.is_empty() || val.starts_with('-') => (stripped, None),
+                    None => {
+                        is_value = true;
+                        (stripped, Some(val.to_string()))
+                    }
+                };
+                arguments.insert(stripped, val, source.clone());
+            } else if !std::mem::take(&mut is_value) {
+                arguments.positionals.push(key.to_string());
             }
         }
-        Arguments { arg_map }
+        arguments
     }
 
-    /// Checks whether or not an argument is present in the list
+    /// Checks whether or not an argument is present in the list.
+    /// Like every lookup, this borrows the key and never allocates
     ///
     /// # Arguments
     ///
     /// `key`: The key to check
-    pub fn contains(&self, key: &str) -> bool {
-        self.arg_map.contains_key(key)
+    pub fn contains<K: AsRef<str>>(&self, key: K) -> bool {
+        self.arg_map.contains_key(self.canonical(key.as_ref()))
     }
 
     /// Checks whether or not an argument is present in the list
@@ -51,13 +246,50 @@ impl Arguments {
     /// # Arguments
     ///
     /// `key`: The key to check
-    pub fn contains_val(&self, key: &str) -> bool {
+    pub fn contains_val<K: AsRef<str>>(&self, key: K) -> bool {
         self.arg_map
-            .get_vec(key)
+            .get_vec(self.canonical(key.as_ref()))
             .and_then(|vals| vals.iter().find(|&val| val.is_some()))
             .is_some()
     }
 
+    /// Checks whether the occurrence of an argument at an index, in the
+    /// same order as `get_vec`, was given a value. Returns false when
+    /// the argument has fewer occurrences
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    ///
+    /// `index`: The index of the occurrence
+    pub fn has_value_at<K: AsRef<str>>(&self, key: K, index: usize) -> bool {
+        self.get_vec(key)
+            .and_then(|vals| vals.get(index))
+            .is_some_and(Option::is_some)
+    }
+
+    /// Gets the index of the first occurrence of an argument that was
+    /// given a value, in the same order as `get_vec`, so
+    /// `get_vec(key)[index]` is the value `contains_val` found
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn first_value_index<K: AsRef<str>>(&self, key: K) -> Option<usize> {
+        self.get_vec(key)?.iter().position(Option::is_some)
+    }
+
+    /// Counts how many times an argument is present, such as `3` for
+    /// `-v -v -v` or, in a clustered specification, `-vvv`. Absent
+    /// arguments count as `0`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to count
+    pub fn count<K: AsRef<str>>(&self, key: K) -> usize {
+        self.get_vec(key).map_or(0, Vec::len)
+    }
+
     /// Checks whether or not the arguments are empty
     pub fn is_empty(&self) -> bool {
         self.arg_map.is_empty()
@@ -68,28 +300,293 @@ impl Arguments {
     /// # Arguments
     ///
     /// `key`: The key to fetch
-    pub fn get(&self, key: &str) -> Option<Option<&str>> {
-        Some(self.arg_map.get(key)?.as_ref().map(String::as_ref))
+    pub fn get<K: AsRef<str>>(&self, key: K) -> Option<Option<&str>> {
+        Some(
+            self.arg_map
+                .get(self.canonical(key.as_ref()))?
+                .as_ref()
+                .map(String::as_ref),
+        )
+    }
+
+    /// Gets the first value with the given key as raw bytes, such as a
+    /// key taken from an `OsStr`. Keys that are not UTF-8 are never
+    /// present
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_by_bytes(&self, key: &[u8]) -> Option<Option<&str>> {
+        self.get(std::str::from_utf8(key).ok()?)
+    }
+
+    /// Gets all values with the given key. Values are ordered by the
+    /// precedence of their source, as established by `merge`, and then
+    /// by their position within that source
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_vec<K: AsRef<str>>(&self, key: K) -> Option<&Vec<Option<String>>> {
+        self.arg_map.get_vec(self.canonical(key.as_ref()))
+    }
+
+    /// Gets the first value with the given key, or the given default
+    /// when the argument is absent or given without a value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    ///
+    /// `default`: The value used in place of a missing one
+    pub fn get_or<'a, K: AsRef<str>>(&'a self, key: K, default: &'a str) -> &'a str {
+        self.get(key).flatten().unwrap_or(default)
+    }
+
+    /// Gets the first value with the given key converted to the type,
+    /// such as a port number. Returns `None` when the argument is absent
+    /// or given without a value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_as<T: FromStr>(&self, key: impl AsRef<str>) -> Option<Result<T, T::Err>> {
+        self.get(key)?.map(str::parse)
+    }
+
+    /// Gets every value with the given key converted to the type, in the
+    /// same order as `get_vec`, failing on the first that cannot be
+    /// converted. Instances without a value are skipped
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_vec_as<T: FromStr>(&self, key: impl AsRef<str>) -> Option<Result<Vec<T>, T::Err>> {
+        Some(
+            self.get_vec(key)?
+                .iter()
+                .filter_map(Option::as_deref)
+                .map(str::parse)
+                .collect(),
+        )
+    }
+
+    /// Gets the present key closest to the given key along with its
+    /// values, for tools forgiving typos at lookup time without a
+    /// specification. The key itself is preferred when present, and
+    /// keys equally close are broken alphabetically
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    ///
+    /// `max_distance`: The most edits a present key may be away
+    pub fn get_fuzzy<K: AsRef<str>>(
+        &self,
+        key: K,
+        max_distance: usize,
+    ) -> Option<(&str, &Vec<Option<String>>)> {
+        let key = key.as_ref();
+        self.arg_map
+            .iter_all()
+            .map(|(present, vals)| (fuzzy::levenshtein(key, present), present.as_str(), vals))
+            .filter(|&(distance, _, _)| distance <= max_distance)
+            .min_by_key(|&(distance, present, _)| (distance, present))
+            .map(|(_, present, vals)| (present, vals))
+    }
+
+    /// Gets all values with the given key along with the source each
+    /// came from, in the same order as `get_vec`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn values_with_sources<'a>(
+        &'a self,
+        key: &str,
+    ) -> impl Iterator<Item = (Option<&'a str>, &'a Source)> + 'a {
+        let key = self.canonical(key);
+        let vals = self.arg_map.get_vec(key).map(Vec::as_slice).unwrap_or(&[]);
+        let sources = self.sources.get_vec(key).map(Vec::as_slice).unwrap_or(&[]);
+        vals.iter()
+            .map(Option::as_deref)
+            .zip(sources.iter().map(|(source, _)| source))
+    }
+
+    /// Gets the values of the given keys interleaved in the order they
+    /// were given, for tools where the order of different flags matters
+    /// relative to each other, like `-H` and `-d` for `curl`. Merged
+    /// values follow those already present
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The keys to fetch
+    pub fn pairs_in_order<'a>(
+        &'a self,
+        keys: &[&str],
+    ) -> impl Iterator<Item = (&'a str, Option<&'a str>)> + 'a {
+        let mut pairs: Vec<(usize, &str, Option<&str>)> = Vec::new();
+        for (key, vals) in self.arg_map.iter_all() {
+            if !keys.contains(&key.as_str()) {
+                continue;
+            }
+            let ordinals = self.sources.get_vec(key).map(Vec::as_slice).unwrap_or(&[]);
+            for (val, (_, ordinal)) in vals.iter().zip(ordinals) {
+                pairs.push((*ordinal, key, val.as_deref()));
+            }
+        }
+        pairs.sort_unstable_by_key(|&(ordinal, _, _)| ordinal);
+        pairs.into_iter().map(|(_, key, val)| (key, val))
+    }
+
+    /// Appends the values of lower-precedence arguments, such as those
+    /// read from the environment or a file, after the values already
+    /// present for each key. Merging layers from the highest precedence
+    /// to the lowest makes `get` return the value that takes effect and
+    /// `get_vec` list every value from the most to the least important
+    ///
+    /// # Arguments
+    ///
+    /// `lower`: The lower-precedence arguments
+    pub fn merge(&mut self, lower: Arguments) {
+        self.merge_where(lower, |_, _| true)
+    }
+
+    /// Appends the values of lower-precedence arguments like `merge`,
+    /// skipping keys for which the predicate returns false. The
+    /// predicate receives the key and whether it is already present
+    pub(crate) fn merge_where<F: Fn(&str, bool) -> bool>(&mut self, lower: Arguments, keep: F) {
+        let offset = self.inserted;
+        for &alias in &lower.aliases {
+            if !self.aliases.contains(&alias) {
+                self.aliases.push(alias);
+            }
+        }
+        let mut sources = lower.sources;
+        for (key, vals) in lower.arg_map {
+            let canonical = self.canonical(&key).to_string();
+            if !keep(&canonical, self.contains(&canonical)) {
+                continue;
+            }
+            let key_sources = sources.remove(&key).unwrap_or_default();
+            for (val, (source, ordinal)) in vals.into_iter().zip(key_sources) {
+                self.insert_at(&canonical, val, source, offset + ordinal);
+            }
+        }
+        self.inserted = offset + lower.inserted;
+    }
+
+    /// Gets the operands that are neither keys nor values, in order, such
+    /// as `build`, `file1` and `file2` in `build file1 file2 -v`
+    pub fn positionals(&self) -> &[String] {
+        &self.positionals
+    }
+
+    /// Gets the arguments following `--`, unparsed and in order, such as
+    /// `cargo build --release` in `-v -- cargo build --release`, for
+    /// forwarding to a child process
+    pub fn trailing(&self) -> &[String] {
+        &self.trailing
+    }
+
+    /// Gets the tokens captured by the first instance of an argument
+    /// declared with `ArgSpec::capture_until`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_captured(&self, key: &str) -> Option<&[String]> {
+        self.captures.get(key).map(Vec::as_slice)
     }
 
-    /// Gets all values with the given key
+    /// Gets the tokens captured by every instance of an argument
+    /// declared with `ArgSpec::capture_until`
     ///
     /// # Arguments
     ///
     /// `key`: The key to fetch
-    pub fn get_vec(&self, key: &str) -> Option<&Vec<Option<String>>> {
-        self.arg_map.get_vec(key)
+    pub fn get_captured_vec(&self, key: &str) -> Option<&Vec<Vec<String>>> {
+        self.captures.get_vec(key)
+    }
+
+    /// Gets the tokens of every raw region declared with
+    /// `Spec::raw_region`, in order, without their markers
+    pub fn raw_regions(&self) -> &[Vec<String>] {
+        &self.raw_regions
+    }
+
+    /// Gets the keys corrected to declared arguments in lenient mode, in
+    /// order, so programs can warn about them
+    pub fn corrections(&self) -> &[Correction] {
+        &self.corrections
     }
 
     /// Returns the number of arguments that were parsed
     pub fn len(&self) -> usize {
         self.arg_map.len()
     }
+
+    /// Creates arguments without any keys or positionals
+    pub(crate) fn empty() -> Arguments {
+        Arguments {
+            arg_map: MultiMap::new(),
+            sources: MultiMap::new(),
+            positionals: Vec::new(),
+            captures: MultiMap::new(),
+            raw_values: MultiMap::new(),
+            raw_regions: Vec::new(),
+            corrections: Vec::new(),
+            operands: Vec::new(),
+            aliases: Vec::new(),
+            trailing: Vec::new(),
+            inserted: 0,
+            timings: None,
+        }
+    }
+
+    /// Returns the declared name an alias known from the specification
+    /// the arguments were parsed with stands for
+    fn alias_of(&self, key: &str) -> Option<&'static str> {
+        self.aliases
+            .iter()
+            .find(|&&(alias, _)| alias == key)
+            .map(|&(_, name)| name)
+    }
+
+    /// Returns the declared name an alias stands for, or the key itself
+    /// when it is not an alias
+    fn canonical<'a>(&self, key: &'a str) -> &'a str {
+        self.alias_of(key).unwrap_or(key)
+    }
+
+    /// Adds an instance of an argument after every other. Keys are
+    /// stored once, inline when they are short, while every value is
+    /// its own `String` as handed out by `get_vec`
+    pub(crate) fn insert(&mut self, key: &str, val: Option<String>, source: Source) {
+        let key = self.alias_of(key).unwrap_or(key);
+        self.insert_at(key, val, source, self.inserted);
+        self.inserted += 1;
+    }
+
+    /// Adds an instance of an argument at a position in the order they
+    /// were given, only storing the key the first time it is added
+    fn insert_at(&mut self, key: &str, val: Option<String>, source: Source, ordinal: usize) {
+        match (self.arg_map.get_vec_mut(key), self.sources.get_vec_mut(key)) {
+            (Some(vals), Some(sources)) => {
+                vals.push(val);
+                sources.push((source, ordinal));
+            }
+            _ => {
+                self.sources.insert(SmallStr::from(key), (source, ordinal));
+                self.arg_map.insert(SmallStr::from(key), val);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Arguments;
+    use super::{Arguments, Source};
 
     #[test]
     fn empty() {
@@ -103,6 +600,14 @@ mod test {
         let args = Arguments::parse(&["arg"]);
         assert!(args.is_empty());
         assert_eq!(args.len(), 0);
+        assert_eq!(args.positionals(), ["arg"]);
+    }
+
+    #[test]
+    fn positionals() {
+        let args = Arguments::parse(&["build", "file1", "file2", "-v", "-out", "dir", "x"]);
+        assert_eq!(args.positionals(), ["build", "file1", "file2", "x"]);
+        assert_eq!(args.get("out").unwrap().unwrap(), "dir");
     }
 
     #[test]
@@ -128,6 +633,39 @@ mod test {
         assert_eq!(args.get_vec("key").unwrap().len(), 1);
     }
 
+    #[test]
+    fn negative_numbers() {
+        let args = Arguments::parse(&["-t", "-5", "-u=-5"]);
+        assert_eq!(args.get("t"), Some(None));
+        assert!(args.contains("5"));
+        assert_eq!(args.get("u"), Some(Some("-5")));
+        let args = Arguments::parse_with_behavior(&["-t", "-5"], crate::Behavior::V3);
+        assert_eq!(args.get("t"), Some(Some("-5")));
+    }
+
+    #[test]
+    fn value_indices() {
+        let args = Arguments::parse(&["-key", "-key", "val", "-other"]);
+        assert!(!args.has_value_at("key", 0));
+        assert!(args.has_value_at("key", 1));
+        assert!(!args.has_value_at("key", 2));
+        assert_eq!(args.first_value_index("key"), Some(1));
+        assert_eq!(args.first_value_index("other"), None);
+        assert_eq!(args.first_value_index("missing"), None);
+    }
+
+    #[test]
+    fn count() {
+        let args = Arguments::parse(&["-v", "-v", "-v", "-key", "val"]);
+        assert_eq!(args.count("v"), 3);
+        assert_eq!(args.count("key"), 1);
+        assert_eq!(args.count("missing"), 0);
+        let spec = crate::Spec::new()
+            .arg(crate::ArgSpec::new("v").flag().aliases(&["verbose"]))
+            .clustered(true);
+        assert_eq!(spec.parse(&["-vvv", "-verbose"]).count("v"), 4);
+    }
+
     #[test]
     fn one_key_repeated() {
         let args = Arguments::parse(&["-key", "val", "-key", "val2"]);
@@ -194,6 +732,60 @@ mod test {
         assert_eq!(args.get_vec("key2").unwrap().len(), 1);
     }
 
+    #[test]
+    fn long_keys() {
+        let args = Arguments::parse(&["--key", "val", "-key", "val2", "--verbose"]);
+        assert_eq!(args.len(), 2);
+        assert!(!args.contains("-key"));
+        assert_eq!(args.get_vec("key").unwrap().len(), 2);
+        assert_eq!(args.get("key").unwrap().unwrap(), "val");
+        assert_eq!(args.get("verbose").unwrap(), None);
+    }
+
+    #[test]
+    fn attached_values() {
+        let args = Arguments::parse(&["-output=/tmp/foo", "--level=debug", "-expr=a=b", "x"]);
+        assert_eq!(args.len(), 3);
+        assert_eq!(args.get("output").unwrap().unwrap(), "/tmp/foo");
+        assert_eq!(args.get("level").unwrap().unwrap(), "debug");
+        assert_eq!(args.get("expr").unwrap().unwrap(), "a=b");
+    }
+
+    #[test]
+    fn trailing() {
+        let args = Arguments::parse(&["-v", "--", "cargo", "build", "--release"]);
+        assert_eq!(args.len(), 1);
+        assert_eq!(args.get("v").unwrap(), None);
+        assert_eq!(args.trailing(), ["cargo", "build", "--release"]);
+        assert!(args.positionals().is_empty());
+        assert!(Arguments::parse(&["-v", "x"]).trailing().is_empty());
+    }
+
+    #[test]
+    fn typed_values() {
+        let args = Arguments::parse(&["-port", "8080", "-n", "1", "-n", "-n", "2", "-v"]);
+        assert_eq!(args.get_as::<u16>("port"), Some(Ok(8080)));
+        assert!(args.get_as::<u8>("port").unwrap().is_err());
+        assert_eq!(args.get_as::<u16>("v"), None);
+        assert_eq!(args.get_as::<u16>("missing"), None);
+        assert_eq!(args.get_or("port", "80"), "8080");
+        assert_eq!(args.get_or("v", "1"), "1");
+        assert_eq!(args.get_or("threads", "4"), "4");
+        assert_eq!(args.get_vec_as::<u32>("n"), Some(Ok(vec![1, 2])));
+        assert!(args.get_vec_as::<bool>("n").unwrap().is_err());
+    }
+
+    #[test]
+    fn fuzzy_lookup() {
+        let args = Arguments::parse(&["-verbose", "-color", "auto", "-colour", "never"]);
+        let (key, vals) = args.get_fuzzy("verbos", 1).unwrap();
+        assert_eq!(key, "verbose");
+        assert_eq!(vals, &[None]);
+        assert_eq!(args.get_fuzzy("colour", 2).unwrap().0, "colour");
+        assert_eq!(args.get_fuzzy("colr", 2).unwrap().0, "color");
+        assert!(args.get_fuzzy("vrbs", 2).is_none());
+    }
+
     #[test]
     fn ergonomics() {
         let sys_args: Vec<String> = vec!["-key".into(), "val".into()];
@@ -206,4 +798,72 @@ mod test {
         assert_eq!(args.get("key").unwrap().unwrap(), "val");
         assert_eq!(args.get_vec("key").unwrap().len(), 1);
     }
+
+    #[test]
+    fn generic_keys() {
+        let args = Arguments::parse(&["-key", "val"]);
+        let key = String::from("key");
+        assert!(args.contains(&key));
+        assert!(args.contains_val(key.clone()));
+        assert_eq!(args.get(&key), Some(Some("val")));
+        assert_eq!(args.get_vec(key).unwrap().len(), 1);
+        assert_eq!(args.get_by_bytes(b"key"), Some(Some("val")));
+        assert_eq!(args.get_by_bytes(b"k\xffey"), None);
+    }
+
+    #[test]
+    fn ordered_pairs() {
+        let mut args = Arguments::parse(&["-H", "a", "-d", "1", "-v", "-H", "b", "-d", "2"]);
+        args.merge(Arguments::parse(&["-d", "3", "-H", "c"]));
+        let pairs: Vec<_> = args.pairs_in_order(&["H", "d"]).collect();
+        assert_eq!(
+            pairs,
+            [
+                ("H", Some("a")),
+                ("d", Some("1")),
+                ("H", Some("b")),
+                ("d", Some("2")),
+                ("d", Some("3")),
+                ("H", Some("c")),
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_sources() {
+        let mut args = Arguments::parse(&["-include", "cli", "-output", "out"]);
+        args.merge(Arguments::parse_with_source(
+            &["-include", "env"],
+            Source::Env("INCLUDE".to_string()),
+        ));
+        args.merge(Arguments::parse_with_source(
+            &["-include", "file1", "-include", "file2", "-extra"],
+            Source::File("app.conf".into()),
+        ));
+        assert_eq!(args.get("include").unwrap(), Some("cli"));
+        assert_eq!(
+            args.get_vec("include").unwrap(),
+            &vec![
+                Some("cli".to_string()),
+                Some("env".to_string()),
+                Some("file1".to_string()),
+                Some("file2".to_string())
+            ]
+        );
+        let sources: Vec<(Option<&str>, &Source)> = args.values_with_sources("include").collect();
+        assert_eq!(
+            sources,
+            vec![
+                (Some("cli"), &Source::CommandLine),
+                (Some("env"), &Source::Env("INCLUDE".to_string())),
+                (Some("file1"), &Source::File("app.conf".into())),
+                (Some("file2"), &Source::File("app.conf".into())),
+            ]
+        );
+        assert_eq!(
+            args.values_with_sources("extra").collect::<Vec<_>>(),
+            vec![(None, &Source::File("app.conf".into()))]
+        );
+        assert_eq!(args.values_with_sources("missing").count(), 0);
+    }
 }
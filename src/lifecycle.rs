@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{ArgSpec, Arguments, Spec};
+
+/// Where a declared argument is in its lifecycle at the version of the
+/// specification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// The argument is neither deprecated nor removed
+    Active,
+    /// The argument still works but warns through `Spec::deprecations`
+    Deprecated {
+        since: Option<&'static str>,
+        removed_in: Option<&'static str>,
+    },
+    /// The argument is rejected by `Spec::validate` and hidden from
+    /// help output and completions
+    Removed { removed_in: &'static str },
+}
+
+/// A deprecated argument given to the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deprecation {
+    /// The key of the argument
+    pub key: String,
+    /// The version the argument is deprecated since
+    pub since: Option<&'static str>,
+    /// The version the argument is removed in
+    pub removed_in: Option<&'static str>,
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "-{} is deprecated", self.key)?;
+        if let Some(since) = self.since {
+            write!(f, " since {}", since)?;
+        }
+        if let Some(removed_in) = self.removed_in {
+            write!(f, " and will be removed in {}", removed_in)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares dotted versions numerically, so `1.10` is after `1.9`.
+/// Missing components count as 0 and pre-release or build suffixes
+/// after `-` or `+` are ignored
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let components = |version: &str| -> Vec<u64> {
+        let version = version.trim_start_matches('v');
+        let end = version.find(['-', '+']).unwrap_or(version.len());
+        version[..end]
+            .split('.')
+            .map(|component| {
+                let digits = component
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(component.len());
+                component[..digits].parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (a, b) = (components(a), components(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let a = a.get(i).copied().unwrap_or(0);
+            a.cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|&ordering| ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+impl Spec {
+    /// Returns where a declared argument is in its lifecycle at the
+    /// version of the specification. An argument is removed once the
+    /// version reaches the one it is removed in, and deprecated before
+    /// that once the version reaches the one it is deprecated since, or
+    /// right away when only its removal is announced. Without a version,
+    /// arguments are never removed and always deprecated when marked so
+    ///
+    /// # Arguments
+    ///
+    /// `arg`: The declared argument
+    pub fn lifecycle(&self, arg: &ArgSpec) -> Lifecycle {
+        let reached = |version: &str| {
+            self.get_version()
+                .is_some_and(|current| compare_versions(current, version) != Ordering::Less)
+        };
+        let since = arg.get_deprecated_since();
+        let removed_in = arg.get_removed_in();
+        let deprecated = match since {
+            Some(since) => self.get_version().is_none() || reached(since),
+            None => removed_in.is_some(),
+        };
+        match removed_in {
+            Some(removed_in) if reached(removed_in) => Lifecycle::Removed { removed_in },
+            _ if deprecated => Lifecycle::Deprecated { since, removed_in },
+            _ => Lifecycle::Active,
+        }
+    }
+
+    /// Lists the deprecated arguments given, in declaration order, so
+    /// the program can warn about them
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn deprecations(&self, args: &Arguments) -> Vec<Deprecation> {
+        self.args()
+            .iter()
+            .filter(|arg| args.contains(arg.name()))
+            .filter_map(|arg| match self.lifecycle(arg) {
+                Lifecycle::Deprecated { since, removed_in } => Some(Deprecation {
+                    key: arg.name().to_string(),
+                    since,
+                    removed_in,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the declared arguments that are not removed, as shown in
+    /// help output and completions
+    pub(crate) fn visible_args(&self) -> impl Iterator<Item = &ArgSpec> {
+        self.args()
+            .iter()
+            .filter(move |arg| !matches!(self.lifecycle(arg), Lifecycle::Removed { .. }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::compare_versions;
+    use crate::{ArgSpec, Deprecation, Lifecycle, Spec, Violation};
+    use std::cmp::Ordering;
+
+    fn spec(version: &str) -> Spec {
+        Spec::new()
+            .version(version.to_string())
+            .arg(
+                ArgSpec::new("old")
+                    .deprecated_since("1.2")
+                    .removed_in("2.0"),
+            )
+            .arg(ArgSpec::new("legacy").deprecated_since("1.5"))
+            .arg(ArgSpec::new("port"))
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("2", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v1.2.0-beta", "1.2"), Ordering::Equal);
+        assert_eq!(compare_versions("0.9", "1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn timeline() {
+        let old = ArgSpec::new("old")
+            .deprecated_since("1.2")
+            .removed_in("2.0");
+        assert_eq!(spec("1.1").lifecycle(&old), Lifecycle::Active);
+        assert_eq!(
+            spec("1.2").lifecycle(&old),
+            Lifecycle::Deprecated {
+                since: Some("1.2"),
+                removed_in: Some("2.0"),
+            }
+        );
+        assert_eq!(
+            Spec::new().lifecycle(&ArgSpec::new("old").removed_in("2.0")),
+            Lifecycle::Deprecated {
+                since: None,
+                removed_in: Some("2.0"),
+            }
+        );
+        assert_eq!(
+            spec("2.1").lifecycle(&old),
+            Lifecycle::Removed { removed_in: "2.0" }
+        );
+        let spec_1_5 = spec("1.5");
+        let args = spec_1_5.parse(&["-old", "-legacy", "-port", "1"]);
+        assert_eq!(
+            spec_1_5.deprecations(&args),
+            [
+                Deprecation {
+                    key: "old".to_string(),
+                    since: Some("1.2"),
+                    removed_in: Some("2.0"),
+                },
+                Deprecation {
+                    key: "legacy".to_string(),
+                    since: Some("1.5"),
+                    removed_in: None,
+                },
+            ]
+        );
+        assert_eq!(
+            spec_1_5.deprecations(&args)[0].to_string(),
+            "-old is deprecated since 1.2 and will be removed in 2.0"
+        );
+        assert_eq!(spec("1.4").deprecations(&args).len(), 1);
+        let spec_2 = spec("2.0");
+        let err = spec_2.validate(&spec_2.parse(&["-old"])).unwrap_err();
+        assert_eq!(
+            err.violations,
+            [Violation::Removed {
+                key: "old".to_string(),
+                removed_in: "2.0".to_string(),
+            }]
+        );
+        assert_eq!(err.to_string(), "-old was removed in 2.0");
+        assert!(!spec_2.minimal_help().contains("-old"));
+        assert!(spec_1_5.minimal_help().contains("-old"));
+        assert_eq!(spec_2.complete(&["-o"]), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,250 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::{Arguments, Spec};
+
+/// Limits on the size of parse input, protecting programs that parse
+/// untrusted argument strings from resource exhaustion. Every builder
+/// method is a `const fn`, like `ArgSpec`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    max_args: usize,
+    max_key_len: usize,
+    max_value_len: usize,
+    max_file_depth: usize,
+    max_file_size: u64,
+}
+
+impl Limits {
+    /// Creates limits that allow any input, except that response files
+    /// may only nest 16 deep, so a file including itself fails instead
+    /// of expanding forever
+    pub const fn new() -> Limits {
+        Limits {
+            max_args: usize::MAX,
+            max_key_len: usize::MAX,
+            max_value_len: usize::MAX,
+            max_file_depth: 16,
+            max_file_size: u64::MAX,
+        }
+    }
+
+    /// Sets the largest number of raw arguments
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The largest number of arguments
+    pub const fn max_args(mut self, max: usize) -> Limits {
+        self.max_args = max;
+        self
+    }
+
+    /// Sets the longest key, in bytes
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The longest key
+    pub const fn max_key_len(mut self, max: usize) -> Limits {
+        self.max_key_len = max;
+        self
+    }
+
+    /// Sets the longest value, in bytes
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The longest value
+    pub const fn max_value_len(mut self, max: usize) -> Limits {
+        self.max_value_len = max;
+        self
+    }
+
+    /// Sets how deep response files may include other response files,
+    /// where 1 allows response files on the command line only
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The deepest nesting
+    pub const fn max_file_depth(mut self, max: usize) -> Limits {
+        self.max_file_depth = max;
+        self
+    }
+
+    /// Sets the largest total size of expanded response files, in bytes
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The largest total size
+    pub const fn max_file_size(mut self, max: u64) -> Limits {
+        self.max_file_size = max;
+        self
+    }
+
+    /// Returns the largest number of raw arguments
+    pub const fn get_max_args(&self) -> usize {
+        self.max_args
+    }
+
+    /// Returns the longest key, in bytes
+    pub const fn get_max_key_len(&self) -> usize {
+        self.max_key_len
+    }
+
+    /// Returns the longest value, in bytes
+    pub const fn get_max_value_len(&self) -> usize {
+        self.max_value_len
+    }
+
+    /// Returns how deep response files may nest
+    pub const fn get_max_file_depth(&self) -> usize {
+        self.max_file_depth
+    }
+
+    /// Returns the largest total size of expanded response files
+    pub const fn get_max_file_size(&self) -> u64 {
+        self.max_file_size
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits::new()
+    }
+}
+
+/// Error returned when parse input exceeds its limits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LimitsExceeded {
+    /// There are more raw arguments than allowed
+    Args { count: usize, max: usize },
+    /// A key is longer than allowed
+    KeyLength { key: String, max: usize },
+    /// A value is longer than allowed
+    ValueLength { key: String, max: usize },
+    /// A raw argument is longer than a key and a value of the longest
+    /// allowed lengths together, so it is rejected before parsing
+    TokenLength { index: usize, max: usize },
+    /// A response file is nested deeper than allowed
+    FileDepth { path: PathBuf, max: usize },
+    /// Expanding a response file exceeds the allowed total size
+    FileSize { path: PathBuf, max: u64 },
+}
+
+impl fmt::Display for LimitsExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitsExceeded::Args { count, max } => {
+                write!(f, "{} arguments exceed the limit of {}", count, max)
+            }
+            LimitsExceeded::KeyLength { key, max } => write!(
+                f,
+                "key -{} exceeds the limit of {} bytes",
+                key.chars().take(32).collect::<String>(),
+                max
+            ),
+            LimitsExceeded::ValueLength { key, max } => {
+                write!(f, "value for -{} exceeds the limit of {} bytes", key, max)
+            }
+            LimitsExceeded::TokenLength { index, max } => {
+                write!(f, "argument {} exceeds the limit of {} bytes", index, max)
+            }
+            LimitsExceeded::FileDepth { path, max } => write!(
+                f,
+                "response file {} is nested deeper than {}",
+                path.display(),
+                max
+            ),
+            LimitsExceeded::FileSize { path, max } => write!(
+                f,
+                "response file {} exceeds the limit of {} bytes",
+                path.display(),
+                max
+            ),
+        }
+    }
+}
+
+impl Error for LimitsExceeded {}
+
+impl Spec {
+    /// Parse arguments like `parse`, failing when the input exceeds the
+    /// limits of the specification. Raw arguments longer than a key and
+    /// a value of the longest allowed lengths together, with their dashes
+    /// and `=`, are rejected before anything is parsed, and keys and
+    /// values are then checked once parsed
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn parse_limited<S: AsRef<str>>(&self, args: &[S]) -> Result<Arguments, LimitsExceeded> {
+        let limits = self.get_limits();
+        if args.len() > limits.get_max_args() {
+            return Err(LimitsExceeded::Args {
+                count: args.len(),
+                max: limits.get_max_args(),
+            });
+        }
+        let max = limits
+            .get_max_key_len()
+            .saturating_add(limits.get_max_value_len())
+            .saturating_add(3);
+        if let Some(index) = args.iter().position(|arg| arg.as_ref().len() > max) {
+            return Err(LimitsExceeded::TokenLength { index, max });
+        }
+        let arguments = self.parse(args);
+        for (key, vals) in arguments.arg_map.iter_all() {
+            if key.len() > limits.get_max_key_len() {
+                return Err(LimitsExceeded::KeyLength {
+                    key: key.to_string(),
+                    max: limits.get_max_key_len(),
+                });
+            }
+            if vals
+                .iter()
+                .flatten()
+                .any(|val| val.len() > limits.get_max_value_len())
+            {
+                return Err(LimitsExceeded::ValueLength {
+                    key: key.to_string(),
+                    max: limits.get_max_value_len(),
+                });
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Limits, LimitsExceeded};
+    use crate::Spec;
+
+    #[test]
+    fn limits() {
+        let spec = Spec::new().limits(Limits::new().max_args(4).max_key_len(8).max_value_len(3));
+        assert!(spec.parse_limited(&["-port", "80"]).is_ok());
+        assert_eq!(
+            spec.parse_limited(&["a", "b", "c", "d", "e"]).err(),
+            Some(LimitsExceeded::Args { count: 5, max: 4 })
+        );
+        assert_eq!(
+            spec.parse_limited(&["-averylongkey"]).err(),
+            Some(LimitsExceeded::KeyLength {
+                key: "averylongkey".to_string(),
+                max: 8
+            })
+        );
+        let err = spec.parse_limited(&["-port", "8080"]).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "value for -port exceeds the limit of 3 bytes"
+        );
+        assert!(spec.parse_limited(&["--longkey8=abc"]).is_ok());
+        let err = spec.parse_limited(&["-port", "x".repeat(1 << 20).as_str()]);
+        assert_eq!(
+            err.err(),
+            Some(LimitsExceeded::TokenLength { index: 1, max: 14 })
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::lifecycle::compare_versions;
+use crate::{ArgSpec, Spec, Stage};
+
+/// An internal inconsistency of a specification found by `Spec::lint`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint {
+    /// The key is the name or an alias of more than one argument, so
+    /// only the first is ever parsed
+    DuplicateKey {
+        key: String,
+        first: String,
+        second: String,
+    },
+    /// The default value is outside of the choices of the argument or
+    /// rejected by its pipeline
+    InvalidDefault {
+        key: String,
+        default: String,
+        reason: String,
+    },
+    /// An operand conflicts with a key that is not declared
+    UnknownConflict { operand: String, key: String },
+    /// An argument requires or conflicts with a key that is not
+    /// declared
+    UnknownRelation { key: String, other: String },
+    /// The argument accepts glued values but is a flag or its name is
+    /// longer than one character, so values are never glued to it
+    UselessGlue(String),
+    /// The argument is removed in a version before the one it is
+    /// deprecated since
+    RemovedBeforeDeprecated {
+        key: String,
+        since: String,
+        removed_in: String,
+    },
+    /// The default subcommand is not declared
+    UnknownDefaultSubcommand(String),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::DuplicateKey { key, first, second } => {
+                write!(f, "-{} is declared by both -{} and -{}", key, first, second)
+            }
+            Lint::InvalidDefault {
+                key,
+                default,
+                reason,
+            } => write!(
+                f,
+                "the default '{}' of -{} is invalid: {}",
+                default, key, reason
+            ),
+            Lint::UnknownConflict { operand, key } => {
+                write!(f, "operand {} conflicts with undeclared -{}", operand, key)
+            }
+            Lint::UnknownRelation { key, other } => {
+                write!(f, "-{} refers to undeclared -{}", key, other)
+            }
+            Lint::UselessGlue(key) => write!(
+                f,
+                "-{} accepts glued values but is not a one-character argument taking a value",
+                key
+            ),
+            Lint::RemovedBeforeDeprecated {
+                key,
+                since,
+                removed_in,
+            } => write!(
+                f,
+                "-{} is removed in {} before being deprecated in {}",
+                key, removed_in, since
+            ),
+            Lint::UnknownDefaultSubcommand(name) => {
+                write!(f, "the default subcommand {} is not declared", name)
+            }
+        }
+    }
+}
+
+/// Checks a default value against the choices and pipeline of its
+/// argument, returning why it is invalid. Pipelines expanding the
+/// environment or canonicalizing paths are only checked up to that
+/// stage, since it depends on where the program runs
+fn default_error(arg: &ArgSpec, default: &str) -> Option<String> {
+    let choices = arg.get_choices();
+    if !choices.is_empty() && !choices.contains(&default) {
+        return Some(format!("expected one of {}", choices.join(", ")));
+    }
+    let mut value = default.to_string();
+    for stage in arg.get_pipeline() {
+        if let Stage::ExpandEnv | Stage::Canonicalize = stage {
+            break;
+        }
+        match stage.apply(&value, &|_| None) {
+            Ok(next) => value = next,
+            Err(message) => return Some(format!("{}: {}", stage.label(), message)),
+        }
+    }
+    None
+}
+
+impl Spec {
+    /// Checks the specification for internal inconsistencies: keys
+    /// declared by several arguments, default values that their own
+    /// choices or pipeline reject, operands conflicting with undeclared
+    /// keys, arguments requiring or conflicting with undeclared keys,
+    /// glued values that can never be given, removals announced
+    /// before deprecations and an undeclared default subcommand. Meant
+    /// for a startup assertion or a test, as in
+    /// `assert_eq!(spec.lint(), [])`
+    pub fn lint(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        let mut keys: Vec<(&str, &str)> = Vec::new();
+        for arg in self.args() {
+            for key in std::iter::once(arg.name()).chain(arg.get_aliases().iter().copied()) {
+                match keys.iter().find(|&&(declared, _)| declared == key) {
+                    Some(&(_, first)) => lints.push(Lint::DuplicateKey {
+                        key: key.to_string(),
+                        first: first.to_string(),
+                        second: arg.name().to_string(),
+                    }),
+                    None => keys.push((key, arg.name())),
+                }
+            }
+            if let Some(default) = arg.get_default_value() {
+                if let Some(reason) = default_error(arg, default) {
+                    lints.push(Lint::InvalidDefault {
+                        key: arg.name().to_string(),
+                        default: default.to_string(),
+                        reason,
+                    });
+                }
+            }
+            for &other in arg.get_requires().iter().chain(arg.get_conflicts_with()) {
+                if self.get(other).is_none() {
+                    lints.push(Lint::UnknownRelation {
+                        key: arg.name().to_string(),
+                        other: other.to_string(),
+                    });
+                }
+            }
+            if arg.is_glued_value() && (arg.is_flag() || arg.name().chars().nth(1).is_some()) {
+                lints.push(Lint::UselessGlue(arg.name().to_string()));
+            }
+            if let (Some(since), Some(removed_in)) =
+                (arg.get_deprecated_since(), arg.get_removed_in())
+            {
+                if compare_versions(removed_in, since) == Ordering::Less {
+                    lints.push(Lint::RemovedBeforeDeprecated {
+                        key: arg.name().to_string(),
+                        since: since.to_string(),
+                        removed_in: removed_in.to_string(),
+                    });
+                }
+            }
+        }
+        for positional in self.positionals() {
+            for &key in positional.get_conflicts() {
+                if self.get(key).is_none() {
+                    lints.push(Lint::UnknownConflict {
+                        operand: positional.name().to_string(),
+                        key: key.to_string(),
+                    });
+                }
+            }
+        }
+        if let Some(name) = self.get_default_subcommand() {
+            if self.find_subcommand(name).is_none() {
+                lints.push(Lint::UnknownDefaultSubcommand(name.to_string()));
+            }
+        }
+        lints
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Lint, PositionalSpec, Spec, Stage};
+
+    fn positive(value: &str) -> Result<(), String> {
+        match value.parse::<u32>() {
+            Ok(n) if n > 0 => Ok(()),
+            _ => Err(format!("'{}' is not a positive number", value)),
+        }
+    }
+
+    #[test]
+    fn findings() {
+        const PIPELINE: &[Stage] = &[Stage::Trim, Stage::Validate("positive", positive)];
+        let spec = Spec::new()
+            .arg(ArgSpec::new("verbose").flag().aliases(&["v"]))
+            .arg(ArgSpec::new("version").aliases(&["v"]))
+            .arg(
+                ArgSpec::new("color")
+                    .choices(&["auto", "never"])
+                    .default_value("always"),
+            )
+            .arg(ArgSpec::new("jobs").pipeline(PIPELINE).default_value(" 0"))
+            .arg(
+                ArgSpec::new("threads")
+                    .pipeline(PIPELINE)
+                    .default_value(" 4 "),
+            )
+            .arg(ArgSpec::new("output").glued_value().requires(&["format"]))
+            .arg(
+                ArgSpec::new("old")
+                    .deprecated_since("2.0")
+                    .removed_in("1.5"),
+            )
+            .positional(PositionalSpec::new("file").conflicts_with(&["stdin"]))
+            .default_subcommand("run");
+        assert_eq!(
+            spec.lint(),
+            [
+                Lint::DuplicateKey {
+                    key: "v".to_string(),
+                    first: "verbose".to_string(),
+                    second: "version".to_string(),
+                },
+                Lint::InvalidDefault {
+                    key: "color".to_string(),
+                    default: "always".to_string(),
+                    reason: "expected one of auto, never".to_string(),
+                },
+                Lint::InvalidDefault {
+                    key: "jobs".to_string(),
+                    default: " 0".to_string(),
+                    reason: "positive: '0' is not a positive number".to_string(),
+                },
+                Lint::UnknownRelation {
+                    key: "output".to_string(),
+                    other: "format".to_string(),
+                },
+                Lint::UselessGlue("output".to_string()),
+                Lint::RemovedBeforeDeprecated {
+                    key: "old".to_string(),
+                    since: "2.0".to_string(),
+                    removed_in: "1.5".to_string(),
+                },
+                Lint::UnknownConflict {
+                    operand: "file".to_string(),
+                    key: "stdin".to_string(),
+                },
+                Lint::UnknownDefaultSubcommand("run".to_string()),
+            ]
+        );
+        assert_eq!(
+            spec.lint()[0].to_string(),
+            "-v is declared by both -verbose and -version"
+        );
+        assert_eq!(Spec::new().arg(ArgSpec::new("port")).lint(), []);
+    }
+}
@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::from_args::convert_first;
+use crate::{Arguments, FromArgsError};
+
+/// How numbers are written in a locale, for parsing values such as
+/// `0,5` or `1.000.000`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    decimal: char,
+    thousands: Option<char>,
+}
+
+impl Locale {
+    /// The C locale, with a decimal point and no thousands separator
+    pub const C: Locale = Locale::new('.', None);
+
+    /// A decimal point with `,` separating thousands, as in English
+    pub const POINT: Locale = Locale::new('.', Some(','));
+
+    /// A decimal comma with `.` separating thousands, as in German
+    pub const COMMA: Locale = Locale::new(',', Some('.'));
+
+    /// Creates a locale
+    ///
+    /// # Arguments
+    ///
+    /// `decimal`: The decimal separator
+    ///
+    /// `thousands`: The thousands separator, if any
+    pub const fn new(decimal: char, thousands: Option<char>) -> Locale {
+        Locale { decimal, thousands }
+    }
+
+    /// Returns the decimal separator
+    pub const fn get_decimal(&self) -> char {
+        self.decimal
+    }
+
+    /// Returns the thousands separator
+    pub const fn get_thousands(&self) -> Option<char> {
+        self.thousands
+    }
+
+    /// Rewrites a number written in the locale in the form Rust parses,
+    /// dropping thousands separators and using `.` as the decimal point
+    ///
+    /// # Arguments
+    ///
+    /// `value`: The number as written in the locale
+    pub fn normalize(&self, value: &str) -> String {
+        value
+            .chars()
+            .filter(|&c| Some(c) != self.thousands)
+            .map(|c| if c == self.decimal { '.' } else { c })
+            .collect()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Locale {
+        Locale::C
+    }
+}
+
+impl Arguments {
+    /// Gets the first value with the given key as a number written in a
+    /// locale, such as `-threshold 0,5` with a decimal comma. Errors
+    /// name the value as it was written
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    ///
+    /// `locale`: How the number is written
+    pub fn get_localized<T: FromStr>(
+        &self,
+        key: &str,
+        locale: &Locale,
+    ) -> Result<Option<T>, FromArgsError>
+    where
+        T::Err: Display,
+    {
+        convert_first(self, key, |value| Cow::Owned(locale.normalize(value)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Locale;
+    use crate::Arguments;
+
+    #[test]
+    fn decimal_comma() {
+        let args = Arguments::parse(&["-threshold", "0,5", "-limit", "1.000.000", "-bad", "1,2,3"]);
+        assert_eq!(
+            args.get_localized("threshold", &Locale::COMMA),
+            Ok(Some(0.5))
+        );
+        assert_eq!(
+            args.get_localized("limit", &Locale::COMMA),
+            Ok(Some(1_000_000u32))
+        );
+        assert_eq!(
+            args.get_localized::<f64>("missing", &Locale::COMMA),
+            Ok(None)
+        );
+        let err = args
+            .get_localized::<f64>("bad", &Locale::COMMA)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid value '1,2,3' for -bad: "));
+        assert!(args.get_localized::<f64>("threshold", &Locale::C).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::{Arguments, Source};
+
+/// A single value recorded in a `Manifest`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The key of the argument
+    pub key: String,
+    /// The value, if any
+    pub value: Option<String>,
+    /// Where the value came from
+    pub source: Source,
+}
+
+/// A record of the exact invocation behind a run, for embedding in
+/// output artifacts so results can be traced back to it. Its `Display`
+/// form is stable, with one field per line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    /// The name of the tool
+    pub tool: String,
+    /// The version of the tool
+    pub version: String,
+    /// Every recorded value, sorted by key and then in order
+    pub entries: Vec<ManifestEntry>,
+    /// The positionals in order
+    pub positionals: Vec<String>,
+    /// The fingerprint of the recorded configuration
+    pub fingerprint: u64,
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "tool: {} {}", self.tool, self.version)?;
+        writeln!(f, "fingerprint: {:016x}", self.fingerprint)?;
+        for entry in &self.entries {
+            write!(f, "arg: -{}", entry.key)?;
+            if let Some(value) = &entry.value {
+                write!(f, "={}", value.escape_debug())?;
+            }
+            writeln!(f, " ({})", entry.source)?;
+        }
+        for positional in &self.positionals {
+            writeln!(f, "positional: {}", positional.escape_debug())?;
+        }
+        Ok(())
+    }
+}
+
+impl Arguments {
+    /// Builds a manifest of the invocation. Ignored keys, such as
+    /// secrets, are left out of both the entries and the fingerprint
+    ///
+    /// # Arguments
+    ///
+    /// `tool`: The name of the tool
+    ///
+    /// `version`: The version of the tool, such as
+    /// `env!("CARGO_PKG_VERSION")`
+    ///
+    /// `ignored`: Keys left out of the manifest
+    pub fn manifest(&self, tool: &str, version: &str, ignored: &[&str]) -> Manifest {
+        let mut keys: Vec<&str> = self
+            .arg_map
+            .keys()
+            .map(|key| key.as_str())
+            .filter(|key| !ignored.contains(key))
+            .collect();
+        keys.sort_unstable();
+        let entries = keys
+            .into_iter()
+            .flat_map(|key| {
+                self.values_with_sources(key)
+                    .map(move |(value, source)| ManifestEntry {
+                        key: key.to_string(),
+                        value: value.map(str::to_string),
+                        source: source.clone(),
+                    })
+            })
+            .collect();
+        Manifest {
+            tool: tool.to_string(),
+            version: version.to_string(),
+            entries,
+            positionals: self.positionals().to_vec(),
+            fingerprint: self.fingerprint_excluding(ignored),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Spec};
+
+    #[test]
+    fn manifest() {
+        let spec = Spec::new().arg(ArgSpec::new("quiet").flag());
+        let args = spec.parse(&["-level", "2", "-token", "secret", "-quiet", "in.txt"]);
+        let manifest = args.manifest("tool", "1.2.0", &["token"]);
+        assert_eq!(manifest.entries.len(), 2);
+        assert_eq!(manifest.fingerprint, args.fingerprint_excluding(&["token"]));
+        assert_eq!(
+            manifest.to_string(),
+            format!(
+                "tool: tool 1.2.0\n\
+                 fingerprint: {:016x}\n\
+                 arg: -level=2 (command line)\n\
+                 arg: -quiet (command line)\n\
+                 positional: in.txt\n",
+                manifest.fingerprint
+            )
+        );
+    }
+}
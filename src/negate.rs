@@ -0,0 +1,101 @@
+use crate::fallback::is_truthy;
+use crate::{ArgSpec, Arguments, Source, Spec};
+
+/// The prefix of keys explicitly turning a flag off, as in `-no-color`
+pub const NEGATION_PREFIX: &str = "no-";
+
+impl Arguments {
+    /// Gets a boolean flag, understanding presence, explicit values such
+    /// as `-color=false` and negation such as `-no-color`. On the command
+    /// line the last of `-color` and `-no-color` wins, so a later flag
+    /// overrides a default or an earlier one. Otherwise the first value
+    /// of the flag is used, falling back to its negation. A value is
+    /// false when it is `0`, `false`, `no` or `off`, and a flag without
+    /// a value is true. Returns `None` when neither is present
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the flag, without the negation prefix
+    pub fn get_bool<K: AsRef<str>>(&self, key: K) -> Option<bool> {
+        let key = self.canonical(key.as_ref());
+        let negated = format!("{}{}", NEGATION_PREFIX, key);
+        let instances = |key: &str| {
+            let vals = self.arg_map.get_vec(key).into_iter().flatten();
+            let sources = self.sources.get_vec(key).into_iter().flatten();
+            vals.zip(sources)
+                .map(|(val, (source, ordinal))| (val.as_deref(), source, *ordinal))
+                .collect::<Vec<_>>()
+        };
+        let truth = |val: Option<&str>| val.is_none_or(is_truthy);
+        let (set, unset) = (instances(key), instances(&negated));
+        let last = set
+            .iter()
+            .map(|&(val, source, ordinal)| (truth(val), source, ordinal))
+            .chain(
+                unset
+                    .iter()
+                    .map(|&(val, source, ordinal)| (!truth(val), source, ordinal)),
+            )
+            .filter(|(_, source, _)| **source == Source::CommandLine)
+            .max_by_key(|&(_, _, ordinal)| ordinal);
+        match (last, set.first(), unset.first()) {
+            (Some((value, _, _)), _, _) => Some(value),
+            (None, Some(&(val, _, _)), _) => Some(truth(val)),
+            (None, None, Some(&(val, _, _))) => Some(!truth(val)),
+            (None, None, None) => None,
+        }
+    }
+}
+
+impl Spec {
+    /// Returns the declared flag an undeclared key turns off, such as
+    /// `color` for `no-color`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key, without the leading `-`
+    pub(crate) fn negated_flag(&self, key: &str) -> Option<&ArgSpec> {
+        if self.get(key).is_some() {
+            return None;
+        }
+        self.get(key.strip_prefix(NEGATION_PREFIX)?)
+            .filter(|arg| arg.is_flag())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Source, Spec};
+
+    #[test]
+    fn negation() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("color").flag())
+            .arg(ArgSpec::new("cache").flag())
+            .arg(ArgSpec::new("no-proxy"))
+            .arg(ArgSpec::new("proxy").flag());
+        let args = spec.parse(&["-color", "-no-color", "input", "--no-cache", "-cache"]);
+        assert_eq!(args.get_bool("color"), Some(false));
+        assert_eq!(args.get_bool("cache"), Some(true));
+        assert_eq!(args.positionals(), ["input"]);
+        assert!(spec.validate(&args).is_ok());
+        let args = spec.parse(&["-cache", "-cache=no"]);
+        assert_eq!(args.get_bool("cache"), Some(false));
+        let args = spec.parse(&["-no-color", "-color=true", "-no-proxy", "host"]);
+        assert_eq!(args.get_bool("color"), Some(true));
+        assert_eq!(args.get("no-proxy"), Some(Some("host")));
+        assert_eq!(args.get_bool("missing"), None);
+        let mut args = Arguments::empty();
+        args.insert("no-color", None, Source::Default);
+        assert_eq!(args.get_bool("color"), Some(false));
+        args.insert(
+            "color",
+            Some("1".to_string()),
+            Source::Env("COLOR".to_string()),
+        );
+        assert_eq!(args.get_bool("color"), Some(true));
+        assert!(Spec::new()
+            .validate(&Spec::new().parse(&["-no-color"]))
+            .is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::{Arguments, Phase, PositionalSpec, Spec};
+
+/// Error validating the positional operands of parsed arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OperandError {
+    /// An operand was given along with an argument it conflicts with.
+    /// The operand is named by its placeholder, as in usage output
+    Conflict { placeholder: String, key: String },
+    /// An operand received fewer positionals than its minimum count
+    TooFew {
+        placeholder: String,
+        min: usize,
+        given: usize,
+    },
+    /// A greedy operand received more positionals than its maximum
+    /// count
+    TooMany {
+        placeholder: String,
+        max: usize,
+        given: usize,
+    },
+}
+
+impl fmt::Display for OperandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperandError::Conflict { placeholder, key } => {
+                write!(f, "{} cannot be used with -{}", placeholder, key)
+            }
+            OperandError::TooFew {
+                placeholder,
+                min,
+                given,
+            } => write!(
+                f,
+                "expected at least {} {}, but {} given",
+                min, placeholder, given
+            ),
+            OperandError::TooMany {
+                placeholder,
+                max,
+                given,
+            } => write!(
+                f,
+                "expected at most {} {}, but {} given",
+                max, placeholder, given
+            ),
+        }
+    }
+}
+
+impl Error for OperandError {}
+
+/// Returns the range of positionals matched to an operand among those
+/// declared, or `None` when it is not declared or received none
+fn operand_range(operands: &[PositionalSpec], count: usize, name: &str) -> Option<Range<usize>> {
+    let index = operands.iter().position(|operand| operand.name() == name)?;
+    let greedy = operands[..index].iter().any(|operand| operand.is_greedy());
+    let end = if operands[index].is_greedy() {
+        count
+    } else {
+        index + 1
+    };
+    Some(index..end).filter(|range| !greedy && range.end <= count && !range.is_empty())
+}
+
+impl Arguments {
+    /// Gets the positional matched to an operand declared with
+    /// `Spec::positional`, by its placeholder. A greedy operand yields
+    /// its first positional
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder of the operand, such as `INPUT`
+    pub fn get_positional(&self, name: &str) -> Option<&str> {
+        self.get_positional_vec(name)?.first().map(String::as_str)
+    }
+
+    /// Gets every positional matched to an operand declared with
+    /// `Spec::positional`, by its placeholder, which is more than one
+    /// only for a greedy operand
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder of the operand, such as `OUTPUTS`
+    pub fn get_positional_vec(&self, name: &str) -> Option<&[String]> {
+        operand_range(&self.operands, self.positionals.len(), name)
+            .map(|range| &self.positionals[range])
+    }
+
+    /// Converts the positional matched to an operand to the type
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder of the operand, such as `INPUT`
+    pub fn positional_as<T: FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        self.get_positional(name).map(str::parse)
+    }
+
+    /// Converts every positional matched to an operand to the type,
+    /// failing on the first that cannot be converted
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder of the operand, such as `OUTPUTS`
+    pub fn positional_vec_as<T: FromStr>(&self, name: &str) -> Option<Result<Vec<T>, T::Err>> {
+        self.get_positional_vec(name)
+            .map(|vals| vals.iter().map(|val| val.parse()).collect())
+    }
+}
+
+impl Spec {
+    /// Checks the positionals of parsed arguments against the declared
+    /// operands, which are matched to positionals in declaration order,
+    /// returning the first violation found: an operand given fewer or
+    /// more positionals than its counts allow, or one given along with
+    /// an argument it conflicts with
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn check_operands(&self, args: &Arguments) -> Result<(), OperandError> {
+        args.time(Phase::Validation, || self.check_operand_counts(args))
+    }
+
+    /// Checks the positionals of parsed arguments like `check_operands`
+    /// without timing the check
+    fn check_operand_counts(&self, args: &Arguments) -> Result<(), OperandError> {
+        let count = args.positionals().len();
+        for operand in self.positionals() {
+            let given = operand_range(self.positionals(), count, operand.name())
+                .map_or(0, |range| range.len());
+            if given < operand.get_min_count() {
+                return Err(OperandError::TooFew {
+                    placeholder: operand.name().to_string(),
+                    min: operand.get_min_count(),
+                    given,
+                });
+            }
+            if let Some(max) = operand.get_max_count().filter(|&max| given > max) {
+                return Err(OperandError::TooMany {
+                    placeholder: operand.name().to_string(),
+                    max,
+                    given,
+                });
+            }
+            if given == 0 {
+                continue;
+            }
+            if let Some(key) = operand
+                .get_conflicts()
+                .iter()
+                .find(|key| args.contains(key))
+            {
+                return Err(OperandError::Conflict {
+                    placeholder: operand.name().to_string(),
+                    key: key.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{ArgSpec, OperandError, PositionalSpec, Spec};
+
+    #[test]
+    fn conflicts() {
+        let spec = Spec::new()
+            .program("tool")
+            .arg(ArgSpec::new("stdin").flag())
+            .positional(PositionalSpec::new("FILE").conflicts_with(&["stdin"]));
+        assert!(spec.check_operands(&spec.parse(&["a.txt"])).is_ok());
+        assert!(spec.check_operands(&spec.parse(&["-stdin"])).is_ok());
+        let err = spec
+            .check_operands(&spec.parse(&["-stdin", "a.txt"]))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            OperandError::Conflict {
+                placeholder: "FILE".to_string(),
+                key: "stdin".to_string(),
+            }
+        );
+        assert_eq!(err.to_string(), "FILE cannot be used with -stdin");
+        assert_eq!(spec.minimal_help(), "usage: tool [-stdin] FILE\n");
+    }
+
+    #[test]
+    fn named() {
+        let spec = Spec::new()
+            .program("convert")
+            .arg(ArgSpec::new("verbose").flag())
+            .positional(PositionalSpec::new("INPUT"))
+            .positional(PositionalSpec::new("OUTPUTS").greedy());
+        let args = spec.parse(&["in.txt", "-verbose", "a.txt", "b.txt"]);
+        assert_eq!(
+            args.positional_as::<PathBuf>("INPUT").unwrap(),
+            Ok(PathBuf::from("in.txt"))
+        );
+        assert_eq!(
+            args.positional_vec_as::<PathBuf>("OUTPUTS").unwrap(),
+            Ok(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")])
+        );
+        assert_eq!(args.get_positional("OUTPUTS"), Some("a.txt"));
+        assert_eq!(args.get_positional("MISSING"), None);
+        let args = spec.parse(&["in.txt"]);
+        assert_eq!(args.get_positional("INPUT"), Some("in.txt"));
+        assert_eq!(args.get_positional_vec("OUTPUTS"), None);
+        assert!(spec
+            .parse(&["many"])
+            .positional_as::<u8>("INPUT")
+            .unwrap()
+            .is_err());
+        assert_eq!(
+            spec.minimal_help(),
+            "usage: convert [-verbose] INPUT OUTPUTS...\n"
+        );
+    }
+
+    #[test]
+    fn counts() {
+        let spec = Spec::new()
+            .positional(PositionalSpec::new("DEST").min_count(1))
+            .positional(
+                PositionalSpec::new("FILE")
+                    .greedy()
+                    .min_count(1)
+                    .max_count(2),
+            );
+        assert!(spec.check_operands(&spec.parse(&["out", "a"])).is_ok());
+        assert!(spec.check_operands(&spec.parse(&["out", "a", "b"])).is_ok());
+        let err = spec.check_operands(&spec.parse(&["out"])).unwrap_err();
+        assert_eq!(
+            err,
+            OperandError::TooFew {
+                placeholder: "FILE".to_string(),
+                min: 1,
+                given: 0,
+            }
+        );
+        assert_eq!(err.to_string(), "expected at least 1 FILE, but 0 given");
+        assert_eq!(
+            spec.check_operands(&spec.parse::<&str>(&[]))
+                .unwrap_err()
+                .to_string(),
+            "expected at least 1 DEST, but 0 given"
+        );
+        assert_eq!(
+            spec.check_operands(&spec.parse(&["out", "a", "b", "c"]))
+                .unwrap_err()
+                .to_string(),
+            "expected at most 2 FILE, but 3 given"
+        );
+    }
+}
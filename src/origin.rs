@@ -0,0 +1,109 @@
+use crate::{Arguments, Source};
+
+impl Arguments {
+    /// Adds a value injected by an embedded front-end, tagged with its
+    /// origin so lookups can be scoped to it. Values already present
+    /// are kept, so `get` still returns the first value from any origin
+    ///
+    /// # Arguments
+    ///
+    /// `origin`: The name of the front-end, such as a script
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `val`: The value, if any
+    pub fn inject(&mut self, origin: &str, key: &str, val: Option<&str>) {
+        self.insert(
+            key,
+            val.map(str::to_string),
+            Source::Origin(origin.to_string()),
+        );
+    }
+
+    /// Gets the first value with the given key injected by an origin,
+    /// like `get` scoped to the origin
+    ///
+    /// # Arguments
+    ///
+    /// `origin`: The name of the front-end
+    ///
+    /// `key`: The key to fetch
+    pub fn get_from(&self, origin: &str, key: &str) -> Option<Option<&str>> {
+        self.get_vec_from(origin, key).into_iter().next()
+    }
+
+    /// Gets every value with the given key injected by an origin, in the
+    /// same order as `get_vec`
+    ///
+    /// # Arguments
+    ///
+    /// `origin`: The name of the front-end
+    ///
+    /// `key`: The key to fetch
+    pub fn get_vec_from(&self, origin: &str, key: &str) -> Vec<Option<&str>> {
+        self.values_with_sources(key)
+            .filter(|(_, source)| matches!(source, Source::Origin(name) if name == origin))
+            .map(|(val, _)| val)
+            .collect()
+    }
+
+    /// Lists the keys with values injected by an origin, sorted
+    ///
+    /// # Arguments
+    ///
+    /// `origin`: The name of the front-end
+    pub fn keys_from(&self, origin: &str) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .sources
+            .iter_all()
+            .filter(|(_, sources)| {
+                sources
+                    .iter()
+                    .any(|(source, _)| matches!(source, Source::Origin(name) if name == origin))
+            })
+            .map(|(key, _)| key.as_str())
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Lists every origin that injected values, sorted
+    pub fn origins(&self) -> Vec<&str> {
+        let mut origins: Vec<&str> = self
+            .sources
+            .iter_all()
+            .flat_map(|(_, sources)| sources)
+            .filter_map(|(source, _)| match source {
+                Source::Origin(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        origins.sort_unstable();
+        origins.dedup();
+        origins
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Arguments, Source};
+
+    #[test]
+    fn origins() {
+        let mut args = Arguments::parse(&["-theme", "dark"]);
+        args.inject("init.lua", "theme", Some("light"));
+        args.inject("init.lua", "autosave", None);
+        args.inject("plugin.py", "theme", Some("solarized"));
+        assert_eq!(args.get("theme"), Some(Some("dark")));
+        assert_eq!(args.get_from("init.lua", "theme"), Some(Some("light")));
+        assert_eq!(args.get_from("init.lua", "autosave"), Some(None));
+        assert_eq!(args.get_vec_from("plugin.py", "theme"), [Some("solarized")]);
+        assert_eq!(args.get_from("plugin.py", "autosave"), None);
+        assert_eq!(args.keys_from("init.lua"), ["autosave", "theme"]);
+        assert_eq!(args.origins(), ["init.lua", "plugin.py"]);
+        assert_eq!(
+            Source::Origin("init.lua".to_string()).to_string(),
+            "origin init.lua"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::Arguments;
+
+/// Splits a `name=value` value at the first `=` that is not escaped.
+/// In the name, `\=` stands for `=` and `\\` for `\`, while the value
+/// is kept verbatim, so `a\=b=c=d` is the name `a=b` with the value
+/// `c=d`. Returns `None` when there is no unescaped `=`
+pub(crate) fn split_pair(raw: &str) -> Option<(String, &str)> {
+    let mut name = String::new();
+    let mut chars = raw.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '=' => return Some((name, &raw[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('=' | '\\'))) => name.push(escaped),
+                Some((_, other)) => {
+                    name.push('\\');
+                    name.push(other);
+                }
+                None => name.push('\\'),
+            },
+            _ => name.push(c),
+        }
+    }
+    None
+}
+
+impl Arguments {
+    /// Gets the first value with the given key exactly as given on the
+    /// command line, before `Spec::parse` trims it, strips its quotes or
+    /// splits it at the delimiter of the argument, and before it is
+    /// split into a name and a value by `get_pair`, so
+    /// `-filter name=foo=bar` gives `name=foo=bar`. Values that did not
+    /// go through `Spec::parse` are returned like `get`, and `None` is
+    /// returned when the first occurrence has no value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_raw<K: AsRef<str>>(&self, key: K) -> Option<&str> {
+        let key = self.canonical(key.as_ref());
+        match self.raw_values.get(key) {
+            Some(raw) => raw.as_deref(),
+            None => self.get(key).flatten(),
+        }
+    }
+
+    /// Gets the first value with the given key as a `name=value` pair,
+    /// as in `-D NAME=1` or `-filter=name=foo=bar`. The token is split
+    /// into the key and the value at its first `=`, and the value into
+    /// the name and the value of the pair at its first `=` not escaped
+    /// with a backslash, so the value of the pair may contain `=` and
+    /// `-filter a\=b=c` gives the name `a=b`. Returns `None` when the
+    /// argument is absent, has no value or the value has no `=`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_pair<K: AsRef<str>>(&self, key: K) -> Option<(String, &str)> {
+        split_pair(self.get(key).flatten()?)
+    }
+
+    /// Gets every value with the given key as a `name=value` pair like
+    /// `get_pair`, in the same order as `get_vec`, skipping values
+    /// without a value or without an unescaped `=`
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_pairs<K: AsRef<str>>(&self, key: K) -> Vec<(String, &str)> {
+        self.get_vec(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|val| split_pair(val.as_deref()?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::split_pair;
+    use crate::Arguments;
+
+    #[test]
+    fn pairs() {
+        assert_eq!(
+            split_pair("name=foo=bar"),
+            Some(("name".to_string(), "foo=bar"))
+        );
+        assert_eq!(split_pair("a\\=b=c"), Some(("a=b".to_string(), "c")));
+        assert_eq!(split_pair("a\\\\=b"), Some(("a\\".to_string(), "b")));
+        assert_eq!(split_pair("a\\x=\\="), Some(("a\\x".to_string(), "\\=")));
+        assert_eq!(split_pair("a\\=b"), None);
+        let args = Arguments::parse(&[
+            "-filter",
+            "name=foo=bar",
+            "-filter=size\\=max=10",
+            "-filter",
+            "-filter=plain",
+        ]);
+        assert_eq!(args.get_raw("filter"), Some("name=foo=bar"));
+        assert_eq!(
+            args.get_pair("filter"),
+            Some(("name".to_string(), "foo=bar"))
+        );
+        assert_eq!(
+            args.get_pairs("filter"),
+            [
+                ("name".to_string(), "foo=bar"),
+                ("size=max".to_string(), "10")
+            ]
+        );
+        assert_eq!(args.get_pair("missing"), None);
+        let spec = crate::Spec::new()
+            .arg(crate::ArgSpec::new("tags").value_delimiter(','))
+            .strip_quotes(true);
+        let args = spec.parse(&["-name", "'a=b'", "-tags", "x,y", "-tags", "z"]);
+        assert_eq!(args.get("name"), Some(Some("a=b")));
+        assert_eq!(args.get_raw("name"), Some("'a=b'"));
+        assert_eq!(args.get_pair("name"), Some(("a".to_string(), "b")));
+        assert_eq!(args.get("tags"), Some(Some("x")));
+        assert_eq!(args.get_raw("tags"), Some("x,y"));
+        let spec = crate::Spec::new()
+            .arg(crate::ArgSpec::new("path").unique().optional_value())
+            .trim_values(true);
+        let args = spec.parse(&["-path", "-path=a", "-path= a"]);
+        assert_eq!(
+            args.get_vec("path").unwrap(),
+            &[None, Some("a".to_string())]
+        );
+        assert_eq!(args.raw_values.get_vec("path").unwrap().len(), 2);
+        assert_eq!(args.get_raw("path"), None);
+        let args = spec.parse(&["-path= a", "-path=a"]);
+        assert_eq!(args.get_vec("path").unwrap().len(), 1);
+        assert_eq!(args.get_raw("path"), Some(" a"));
+    }
+}
@@ -0,0 +1,863 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+use crate::fuzzy::rank;
+use crate::{
+    is_negative_number, strip_key, ArgSpec, Arguments, Behavior, Phase, Source, Spec,
+    ValidationError,
+};
+
+/// A key or subcommand corrected to a declared one in lenient mode
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Correction {
+    /// The key or subcommand as typed
+    pub typed: String,
+    /// The declared key or subcommand it was corrected to
+    pub corrected: String,
+    /// Whether a subcommand was corrected rather than a key
+    pub subcommand: bool,
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.subcommand {
+            write!(
+                f,
+                "unknown subcommand '{}', assuming '{}'",
+                self.typed, self.corrected
+            )
+        } else {
+            write!(
+                f,
+                "unknown argument -{}, assuming -{}",
+                self.typed, self.corrected
+            )
+        }
+    }
+}
+
+/// Error returned by `try_parse` for arguments that cannot be parsed
+/// unambiguously
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// A lone `-` at the index, which names no key
+    BareDash { index: usize },
+    /// A key that is empty once its dashes are stripped, such as `-=x`
+    EmptyKey { index: usize, token: String },
+    /// The arguments do not match the specification, such as a
+    /// single-use argument given twice
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::BareDash { index } => {
+                write!(f, "argument {} is a lone '-' without a key", index)
+            }
+            ParseError::EmptyKey { index, token } => {
+                write!(f, "argument {} '{}' has an empty key", index, token)
+            }
+            ParseError::Invalid(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Invalid(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that parsed arguments have no empty key, blaming the first
+/// raw argument that is a key without a name
+pub(crate) fn check_keys<S: AsRef<str>>(args: &[S], parsed: &Arguments) -> Result<(), ParseError> {
+    if !parsed.contains("") {
+        return Ok(());
+    }
+    let (index, token) = args
+        .iter()
+        .map(AsRef::as_ref)
+        .enumerate()
+        .find(|&(_, token)| strip_key(token).is_some_and(|key| key.split('=').next() == Some("")))
+        .unwrap_or((0, ""));
+    Err(if token == "-" {
+        ParseError::BareDash { index }
+    } else {
+        ParseError::EmptyKey {
+            index,
+            token: token.to_string(),
+        }
+    })
+}
+
+impl Spec {
+    /// Parse arguments like `parse`, failing on malformed tokens and on
+    /// arguments that do not match the specification, as reported by
+    /// `validate`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn try_parse<S: AsRef<str>>(&self, args: &[S]) -> Result<Arguments, ParseError> {
+        let arguments = self.parse(args);
+        check_keys(args, &arguments)?;
+        self.validate(&arguments).map_err(ParseError::Invalid)?;
+        Ok(arguments)
+    }
+
+    /// Creates a specification from a POSIX `getopt` option string, in
+    /// POSIX mode. Every character declares a single-character option,
+    /// which takes a value when followed by `:`, or an optional attached
+    /// value when followed by `::` as in GNU `getopt`. `W;` makes
+    /// `-W name` read as the long option `name`, like `getopt_long`
+    ///
+    /// # Arguments
+    ///
+    /// `optstring`: The option string, such as `"ab:W;"`
+    pub fn from_optstring(optstring: &'static str) -> Spec {
+        let mut spec = Spec::new().posix(true);
+        let mut chars = optstring.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == ':' {
+                continue;
+            }
+            if c == 'W' && chars.peek().map(|&(_, next)| next) == Some(';') {
+                chars.next();
+                spec = spec.w_long_options(true);
+                continue;
+            }
+            let arg = ArgSpec::new(&optstring[i..i + c.len_utf8()]);
+            let colons = optstring[i + c.len_utf8()..]
+                .chars()
+                .take_while(|&next| next == ':')
+                .count();
+            spec = spec.arg(match colons {
+                0 => arg.flag(),
+                1 => arg,
+                _ => arg.optional_value(),
+            });
+        }
+        spec
+    }
+
+    /// Parse arguments according to the specification. Declared flags
+    /// and their negations such as `-no-color` never consume the
+    /// argument following them, declared arguments
+    /// taking a value take a following negative number such as `-5` as
+    /// their value unless it is itself declared, and tokens that are
+    /// neither keys nor values are kept as positionals. Other arguments
+    /// behave like `Arguments::parse`, unless POSIX mode is enabled
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn parse<S: AsRef<str>>(&self, args: &[S]) -> Arguments {
+        let start = Instant::now();
+        let mut arguments = Arguments::empty();
+        arguments.aliases = self
+            .args()
+            .iter()
+            .flat_map(|arg| {
+                arg.get_aliases()
+                    .iter()
+                    .map(move |&alias| (alias, arg.name()))
+            })
+            .collect();
+        let mut tokens = args.iter().map(|s| s.as_ref()).peekable();
+        if self.is_posix() {
+            self.parse_posix(&mut arguments, &mut tokens);
+        }
+        while let Some(token) = tokens.next() {
+            if let Some(end) = self.raw_region_end(token) {
+                let region = tokens
+                    .by_ref()
+                    .take_while(|&token| token != end)
+                    .map(str::to_string)
+                    .collect();
+                arguments.raw_regions.push(region);
+                continue;
+            }
+            if token == "--" && !self.is_posix() {
+                arguments.trailing = tokens.by_ref().map(str::to_string).collect();
+                if self.get_behavior() >= Behavior::V2 {
+                    arguments.positionals.extend_from_slice(&arguments.trailing);
+                }
+                break;
+            }
+            if let Some(escaped) = self.unescape(token) {
+                arguments.positionals.push(escaped.to_string());
+                continue;
+            }
+            let key = match strip_key(token) {
+                Some(key)
+                    if !self.is_posix()
+                        && (self.is_intermixed() || arguments.positionals.is_empty()) =>
+                {
+                    key
+                }
+                _ => {
+                    arguments.positionals.push(token.to_string());
+                    continue;
+                }
+            };
+            let (key, attached) = match (self.glued(token, key), key.split_once('=')) {
+                (Some((flags, key, glued)), _) => {
+                    for flag in flags {
+                        arguments.insert(flag, None, Source::CommandLine);
+                    }
+                    (key, Some(glued))
+                }
+                (None, Some((key, attached))) => (key, Some(attached)),
+                (None, None) => (key, None),
+            };
+            let key = match self.cluster(token, key) {
+                Some((flags, last)) => {
+                    for flag in flags {
+                        arguments.insert(flag, None, Source::CommandLine);
+                    }
+                    last
+                }
+                None => key,
+            };
+            let key = match self.correct_key(key) {
+                Some(corrected) => {
+                    arguments.corrections.push(Correction {
+                        typed: key.to_string(),
+                        corrected: corrected.to_string(),
+                        subcommand: false,
+                    });
+                    corrected
+                }
+                None => key,
+            };
+            if let Some(val) = attached {
+                self.insert_value(&mut arguments, key, Some(val.to_string()));
+                continue;
+            }
+            if let Some(terminator) = self.get(key).and_then(|arg| arg.get_terminator()) {
+                let captured = tokens
+                    .by_ref()
+                    .take_while(|&token| token != terminator)
+                    .map(str::to_string)
+                    .collect();
+                arguments.captures.insert(key.to_string(), captured);
+                arguments.insert(key, None, Source::CommandLine);
+                continue;
+            }
+            if self.get(key).is_some_and(ArgSpec::is_multi_value) {
+                let mut given = false;
+                while let Some(val) = self.take_value(key, &mut tokens) {
+                    self.insert_value(&mut arguments, key, Some(val));
+                    given = true;
+                }
+                if !given {
+                    self.insert_value(&mut arguments, key, None);
+                }
+                continue;
+            }
+            let val = if self
+                .get(key)
+                .is_some_and(|arg| arg.is_flag() || arg.is_optional_value())
+                || self.negated_flag(key).is_some()
+            {
+                None
+            } else {
+                self.take_value(key, &mut tokens)
+            };
+            self.insert_value(&mut arguments, key, val);
+        }
+        arguments.operands = self.positionals().to_vec();
+        if self.is_timed() {
+            arguments.enable_timings();
+            arguments.record(Phase::Parse, start.elapsed());
+        }
+        arguments
+    }
+
+    /// Takes the token following a key as its value, unless it is a key
+    /// itself, empty or a raw region marker. Escaped tokens are always
+    /// values
+    fn take_value<'a, I: Iterator<Item = &'a str>>(
+        &self,
+        key: &str,
+        tokens: &mut std::iter::Peekable<I>,
+    ) -> Option<String> {
+        let &val = tokens.peek()?;
+        if !self.is_value(key, val) {
+            return None;
+        }
+        tokens.next();
+        Some(self.unescape(val).unwrap_or(val).to_string())
+    }
+
+    /// Checks whether a token following a key taking a value is its
+    /// value, as read by `take_value`
+    pub(crate) fn is_value(&self, key: &str, val: &str) -> bool {
+        self.unescape(val).is_some()
+            || (!val.is_empty()
+                && (!val.starts_with('-') || self.takes_negative(key, val))
+                && self.raw_region_end(val).is_none())
+    }
+
+    /// Adds a value given on the command line, normalized, split at the
+    /// delimiter of the argument when it has one, and dropped when the
+    /// argument is unique and already has the value
+    fn insert_value(&self, arguments: &mut Arguments, key: &str, val: Option<String>) {
+        let arg = self.get(key);
+        let raw = val.clone();
+        let val = val.map(|val| self.normalize(&val).to_string());
+        let vals = match (val, arg.and_then(ArgSpec::get_value_delimiter)) {
+            (Some(val), Some(delimiter)) => val
+                .split(delimiter)
+                .map(|part| Some(self.normalize(part).to_string()))
+                .collect(),
+            (val, _) => vec![val],
+        };
+        for val in vals {
+            let repeated = arg.is_some_and(ArgSpec::is_unique)
+                && arguments
+                    .get_vec(key)
+                    .is_some_and(|vals| vals.contains(&val));
+            if !repeated {
+                // one raw value per kept value, so both share the index
+                let canonical = arguments.canonical(key).to_string();
+                arguments.raw_values.insert(canonical, raw.clone());
+                arguments.insert(key, val, Source::CommandLine);
+            }
+        }
+    }
+
+    /// Trims a value and strips the quotes enclosing it, as enabled
+    fn normalize<'a>(&self, val: &'a str) -> &'a str {
+        let val = if self.is_trimming_values() {
+            val.trim()
+        } else {
+            val
+        };
+        if !self.is_stripping_quotes() {
+            return val;
+        }
+        ['"', '\'']
+            .iter()
+            .find_map(|&quote| val.strip_prefix(quote)?.strip_suffix(quote))
+            .unwrap_or(val)
+    }
+
+    /// Checks whether a key takes a following negative number as its
+    /// value: always when the key is declared and the number is not, and
+    /// for undeclared keys from `Behavior::V3` on
+    fn takes_negative(&self, key: &str, val: &str) -> bool {
+        is_negative_number(val)
+            && self.get(&val[1..]).is_none()
+            && (self.get(key).is_some() || self.get_behavior() >= Behavior::V3)
+    }
+
+    /// Splits an undeclared single-dash key into a one-character
+    /// argument accepting glued values and its value. When clustering
+    /// is enabled, every one-character argument taking a value accepts
+    /// glued values, as with getopt, and may follow grouped flags, so
+    /// `-vofile.txt` is `-v -o file.txt`
+    pub(crate) fn glued<'a>(
+        &self,
+        token: &str,
+        key: &'a str,
+    ) -> Option<(Vec<&'static str>, &'static str, &'a str)> {
+        if token.starts_with("--") || self.get(key).is_some() {
+            return None;
+        }
+        let mut flags = Vec::new();
+        for (i, c) in key.char_indices() {
+            let arg = self.get(&key[i..i + c.len_utf8()])?;
+            if arg.is_flag() && self.is_clustered() {
+                flags.push(arg.name());
+                continue;
+            }
+            let value = &key[i + c.len_utf8()..];
+            let glued = !arg.is_flag() && (arg.is_glued_value() || self.is_clustered());
+            return (glued && !value.is_empty() && !value.starts_with('=')).then_some((
+                flags,
+                arg.name(),
+                value,
+            ));
+        }
+        None
+    }
+
+    /// Splits an undeclared single-dash key into the grouped flags and
+    /// the last grouped argument when clustering is enabled and every
+    /// character is a declared argument
+    pub(crate) fn cluster(
+        &self,
+        token: &str,
+        key: &str,
+    ) -> Option<(Vec<&'static str>, &'static str)> {
+        if !self.is_clustered()
+            || token.starts_with("--")
+            || key.chars().nth(1).is_none()
+            || self.get(key).is_some()
+        {
+            return None;
+        }
+        let mut names = key
+            .char_indices()
+            .map(|(i, c)| self.get(&key[i..i + c.len_utf8()]).map(ArgSpec::name))
+            .collect::<Option<Vec<&'static str>>>()?;
+        let last = names.pop()?;
+        names
+            .iter()
+            .all(|name| self.get(name).is_some_and(ArgSpec::is_flag))
+            .then_some((names, last))
+    }
+
+    /// Returns the declared key an undeclared key is a typo of in
+    /// lenient mode, when it is one edit away from exactly one
+    pub(crate) fn correct_key(&self, key: &str) -> Option<&'static str> {
+        if !self.is_lenient() || self.get(key).is_some() {
+            return None;
+        }
+        let names = self.args().iter().map(|arg| (arg.name(), arg.name()));
+        let ranked = rank(key, names);
+        let mut close = ranked.iter().filter(|&&(distance, _)| distance == 1);
+        match (close.next(), close.next()) {
+            (Some(&(_, name)), None) => self.get(name).map(|arg| arg.name()),
+            _ => None,
+        }
+    }
+
+    /// Strips the escape prefix from a token that starts with it
+    pub(crate) fn unescape<'a>(&self, token: &'a str) -> Option<&'a str> {
+        token.strip_prefix(self.get_escape_prefix()?)
+    }
+
+    /// Returns the closing marker of the raw region a token opens
+    fn raw_region_end(&self, token: &str) -> Option<&str> {
+        self.raw_regions()
+            .find(|&(begin, _)| begin == token)
+            .map(|(_, end)| end)
+    }
+
+    /// Parse options following POSIX conventions, stopping after `--`
+    /// or before the first operand
+    fn parse_posix<'a, I: Iterator<Item = &'a str>>(
+        &self,
+        arguments: &mut Arguments,
+        tokens: &mut std::iter::Peekable<I>,
+    ) {
+        while let Some(&token) = tokens.peek() {
+            if token == "--" {
+                tokens.next();
+                return;
+            }
+            if self.raw_region_end(token).is_some() {
+                return;
+            }
+            let options = match token.strip_prefix('-') {
+                Some(options) if !options.is_empty() => options,
+                _ => return,
+            };
+            tokens.next();
+            for (i, c) in options.char_indices() {
+                let key = &options[i..i + c.len_utf8()];
+                let rest = &options[i + c.len_utf8()..];
+                if c == 'W' && self.is_w_long_options() {
+                    let long = if rest.is_empty() {
+                        tokens.next()
+                    } else {
+                        Some(rest)
+                    };
+                    let (name, val) = match long {
+                        Some(long) => match long.split_once('=') {
+                            Some((name, val)) => (name, Some(val.to_string())),
+                            None => (long, None),
+                        },
+                        None => (key, None),
+                    };
+                    arguments.insert(name, val, Source::CommandLine);
+                    break;
+                }
+                if self.get(key).is_some_and(|arg| arg.is_optional_value()) {
+                    let val = Some(rest).filter(|rest| !rest.is_empty());
+                    arguments.insert(key, val.map(str::to_string), Source::CommandLine);
+                    break;
+                }
+                if self.get(key).is_some_and(|arg| !arg.is_flag()) {
+                    let val = if rest.is_empty() {
+                        tokens.next()
+                    } else {
+                        Some(rest)
+                    };
+                    arguments.insert(key, val.map(str::to_string), Source::CommandLine);
+                    break;
+                }
+                arguments.insert(key, None, Source::CommandLine);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, ParseError, Spec, Violation};
+
+    #[test]
+    fn spec_parse() {
+        let spec = Spec::new().arg(ArgSpec::new("verbose").flag());
+        let args = spec.parse(&["-verbose", "input.txt", "-level", "2", "out"]);
+        assert_eq!(args.get("verbose"), Some(None));
+        assert_eq!(args.get("level"), Some(Some("2")));
+        assert_eq!(args.positionals(), ["input.txt", "out"]);
+    }
+
+    #[test]
+    fn capture() {
+        let spec = Spec::new().arg(ArgSpec::new("exec").capture_until(";"));
+        let args = spec.parse(&[
+            "src", "-exec", "grep", "-n", "{}", ";", "-name", "x", "-exec", "rm", "{}",
+        ]);
+        assert_eq!(args.get_captured("exec").unwrap(), ["grep", "-n", "{}"]);
+        assert_eq!(args.get_captured_vec("exec").unwrap()[1], ["rm", "{}"]);
+        assert_eq!(args.get_vec("exec").unwrap().len(), 2);
+        assert_eq!(args.get("name"), Some(Some("x")));
+        assert_eq!(args.positionals(), ["src"]);
+    }
+
+    #[test]
+    fn raw_regions() {
+        let spec = Spec::new().raw_region("--begin-raw", "--end-raw");
+        let args = spec.parse(&[
+            "-level",
+            "1",
+            "--begin-raw",
+            "-x",
+            "y",
+            "--end-raw",
+            "-quiet",
+            "--begin-raw",
+            "-z",
+        ]);
+        assert_eq!(args.raw_regions(), [vec!["-x", "y"], vec!["-z"]]);
+        assert_eq!(args.len(), 2);
+        assert!(args.contains("quiet"));
+        assert!(!args.contains("x"));
+    }
+
+    #[test]
+    fn escaped_values() {
+        let spec = Spec::new().escape_prefix("\\");
+        let args = spec.parse(&["-pattern", "\\-v", "\\-file", "-x", "\\\\y"]);
+        assert_eq!(args.get("pattern"), Some(Some("-v")));
+        assert_eq!(args.get("x"), Some(Some("\\y")));
+        assert_eq!(args.positionals(), ["-file"]);
+        let args = Spec::new()
+            .escape_prefix("--=")
+            .parse(&["-offset", "--=-5"]);
+        assert_eq!(args.get("offset"), Some(Some("-5")));
+    }
+
+    #[test]
+    fn optional_values() {
+        let spec = Spec::new().arg(ArgSpec::new("color").optional_value());
+        let args = spec.parse(&["-color", "always", "-color=never"]);
+        assert_eq!(
+            args.get_vec("color").unwrap(),
+            &vec![None, Some("never".to_string())]
+        );
+        assert_eq!(args.positionals(), ["always"]);
+        let spec = Spec::from_optstring("c::");
+        let args = spec.parse(&["-c", "-cauto", "file"]);
+        assert_eq!(
+            args.get_vec("c").unwrap(),
+            &vec![None, Some("auto".to_string())]
+        );
+        assert_eq!(args.positionals(), ["file"]);
+    }
+
+    #[test]
+    fn try_parse() {
+        assert!(Arguments::try_parse(&["-a", "1", "--", "-"]).is_ok());
+        assert_eq!(
+            Arguments::try_parse(&["-a", "-"]).err(),
+            Some(ParseError::BareDash { index: 1 })
+        );
+        let err = Arguments::try_parse(&["--=x"]).err().unwrap();
+        assert_eq!(
+            err,
+            ParseError::EmptyKey {
+                index: 0,
+                token: "--=x".to_string(),
+            }
+        );
+        assert_eq!(err.to_string(), "argument 0 '--=x' has an empty key");
+        let spec = Spec::new().arg(ArgSpec::new("out").max_occurrences(1));
+        assert!(spec.try_parse(&["-out", "a"]).is_ok());
+        match spec.try_parse(&["-out", "a", "-out", "b"]).err().unwrap() {
+            ParseError::Invalid(err) => assert_eq!(
+                err.violations,
+                [Violation::TooMany {
+                    key: "out".to_string(),
+                    max: 1,
+                    given: 2,
+                }]
+            ),
+            err => panic!("unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn lenient_keys() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("verbose").flag())
+            .arg(ArgSpec::new("stage"))
+            .arg(ArgSpec::new("stake"));
+        let args = spec.parse(&["-verbse", "file"]);
+        assert!(args.contains("verbse"));
+        assert!(args.corrections().is_empty());
+        let spec = spec.lenient(true);
+        let args = spec.parse(&["-verbse", "file", "-stale", "x"]);
+        assert_eq!(args.get("verbose"), Some(None));
+        assert_eq!(args.positionals(), ["file"]);
+        assert_eq!(
+            args.corrections()[0].to_string(),
+            "unknown argument -verbse, assuming -verbose"
+        );
+        // one edit away from both "stage" and "stake"
+        assert_eq!(args.get("stale"), Some(Some("x")));
+        assert_eq!(args.corrections().len(), 1);
+        let args = spec.parse(&["-stagee=1", "file"]);
+        assert_eq!(args.get("stage"), Some(Some("1")));
+        assert_eq!(args.positionals(), ["file"]);
+    }
+
+    #[test]
+    fn strict_ordering() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("verbose").flag())
+            .intermixed(false);
+        let args = spec.parse(&["-verbose", "ls", "-l", "-verbose"]);
+        assert_eq!(args.get_vec("verbose").unwrap().len(), 1);
+        assert!(!args.contains("l"));
+        assert_eq!(args.positionals(), ["ls", "-l", "-verbose"]);
+    }
+
+    #[test]
+    fn clustering() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("r").flag())
+            .arg(ArgSpec::new("f").flag())
+            .arg(ArgSpec::new("x").flag())
+            .arg(ArgSpec::new("o"))
+            .arg(ArgSpec::new("rf"));
+        let args = spec.parse(&["-xf", "dir"]);
+        assert!(args.contains("xf"));
+        let spec = spec.clustered(true);
+        let args = spec.parse(&["-xf", "dir", "-rf", "-xo", "out", "-fo=a", "--xf", "-xq"]);
+        assert_eq!(args.get_vec("x").unwrap().len(), 2);
+        assert_eq!(args.get_vec("f").unwrap().len(), 2);
+        assert_eq!(args.get("rf"), Some(None));
+        assert!(!args.contains("r"));
+        assert_eq!(
+            args.get_vec("o").unwrap(),
+            &[Some("out".to_string()), Some("a".to_string())]
+        );
+        assert!(args.contains("xf"));
+        assert!(args.contains("xq"));
+        assert_eq!(args.positionals(), ["dir"]);
+        let spec = Spec::new()
+            .arg(ArgSpec::new("o"))
+            .arg(ArgSpec::new("x").flag())
+            .arg(ArgSpec::new("v").flag())
+            .clustered(true);
+        let args = spec.parse(&["-ox", "a", "-vxofile.txt", "-xo=b", "-vofile=c"]);
+        assert_eq!(
+            args.get_vec("o").unwrap(),
+            &[
+                Some("x".to_string()),
+                Some("file.txt".to_string()),
+                Some("b".to_string()),
+                Some("file=c".to_string())
+            ]
+        );
+        assert_eq!(args.get_vec("v").unwrap().len(), 2);
+        assert_eq!(args.get_vec("x").unwrap().len(), 2);
+        assert_eq!(args.positionals(), ["a"]);
+        assert!(Spec::new()
+            .arg(ArgSpec::new("o"))
+            .parse(&["-ofile"])
+            .contains("ofile"));
+    }
+
+    #[test]
+    fn multi_values() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("files").multi_value())
+            .arg(ArgSpec::new("offsets").multi_value())
+            .escape_prefix("\\");
+        let args = spec.parse(&[
+            "-files", "a", "b", "\\-c", "-offsets", "-1", "2", "-files", "d", "--", "e",
+        ]);
+        assert_eq!(
+            args.get_vec("files").unwrap(),
+            &[
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("-c".to_string()),
+                Some("d".to_string())
+            ]
+        );
+        assert_eq!(args.get_vec_as::<i32>("offsets"), Some(Ok(vec![-1, 2])));
+        assert_eq!(args.trailing(), ["e"]);
+        let args = spec.parse(&["-files=a", "b", "-files"]);
+        assert_eq!(
+            args.get_vec("files").unwrap(),
+            &[Some("a".to_string()), None]
+        );
+        assert_eq!(args.positionals(), ["b"]);
+    }
+
+    #[test]
+    fn normalized_values() {
+        let args = Spec::new().parse(&["-name", " \"a b\" "]);
+        assert_eq!(args.get("name"), Some(Some(" \"a b\" ")));
+        let spec = Spec::new()
+            .arg(ArgSpec::new("features").value_delimiter(','))
+            .trim_values(true)
+            .strip_quotes(true);
+        let args = spec.parse(&[
+            "-name",
+            " \"a b\" ",
+            "-path='C:\\dir'",
+            "-quote",
+            "\"",
+            "-mixed",
+            "\"x'",
+            "-features",
+            "'a, b'",
+        ]);
+        assert_eq!(args.get("name"), Some(Some("a b")));
+        assert_eq!(args.get("path"), Some(Some("C:\\dir")));
+        assert_eq!(args.get("quote"), Some(Some("\"")));
+        assert_eq!(args.get("mixed"), Some(Some("\"x'")));
+        assert_eq!(
+            args.get_vec("features").unwrap(),
+            &[Some("a".to_string()), Some("b".to_string())]
+        );
+        let args = Spec::new().strip_quotes(true).parse(&["-name", " 'a' "]);
+        assert_eq!(args.get("name"), Some(Some(" 'a' ")));
+    }
+
+    #[test]
+    fn delimited_values() {
+        let spec = Spec::new()
+            .arg(
+                ArgSpec::new("features")
+                    .value_delimiter(',')
+                    .aliases(&["F"]),
+            )
+            .arg(ArgSpec::new("name"));
+        let args = spec.parse(&["-features", "a,b", "-F=c", "-name", "x,y", "-features"]);
+        assert_eq!(
+            args.get_vec("features").unwrap(),
+            &[
+                Some("a".to_string()),
+                Some("b".to_string()),
+                Some("c".to_string()),
+                None
+            ]
+        );
+        assert_eq!(args.get("name"), Some(Some("x,y")));
+    }
+
+    #[test]
+    fn glued_values() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("o").glued_value())
+            .arg(ArgSpec::new("D").glued_value())
+            .arg(ArgSpec::new("f"))
+            .arg(ArgSpec::new("output"));
+        let args = spec.parse(&[
+            "-ooutput.bin",
+            "-o=a",
+            "-o",
+            "b",
+            "-DNAME=1",
+            "-output",
+            "c",
+            "-farchive.tar",
+            "--ox",
+        ]);
+        assert_eq!(
+            args.get_vec("o").unwrap(),
+            &[
+                Some("output.bin".to_string()),
+                Some("a".to_string()),
+                Some("b".to_string())
+            ]
+        );
+        assert_eq!(args.get("D"), Some(Some("NAME=1")));
+        assert_eq!(args.get("output"), Some(Some("c")));
+        assert!(args.contains("farchive.tar"));
+        assert!(args.contains("ox"));
+    }
+
+    #[test]
+    fn negative_values() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("t"))
+            .arg(ArgSpec::new("offset"))
+            .arg(ArgSpec::new("1").flag());
+        let args = spec.parse(&["-t", "-5", "-offset", "-1.5e3", "-x", "-2"]);
+        assert_eq!(args.get("t"), Some(Some("-5")));
+        assert_eq!(args.get("offset"), Some(Some("-1.5e3")));
+        assert_eq!(args.get("x"), Some(None));
+        assert!(args.contains("2"));
+        let args = spec.parse(&["-t", "-1", "-offset", "-inf"]);
+        assert_eq!(args.get("t"), Some(None));
+        assert_eq!(args.get("1"), Some(None));
+        assert_eq!(args.get("offset"), Some(None));
+    }
+
+    #[test]
+    fn aliases() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("verbose").flag().aliases(&["v", "loud"]))
+            .arg(ArgSpec::new("output").aliases(&["o"]));
+        let args = spec.parse(&["-v", "--verbose", "-loud", "-o", "a.txt", "in"]);
+        assert_eq!(args.get_vec("verbose").unwrap().len(), 3);
+        assert_eq!(args.get_vec("v").unwrap().len(), 3);
+        assert!(args.contains("loud"));
+        assert_eq!(args.get("o"), Some(Some("a.txt")));
+        assert_eq!(args.get("output"), Some(Some("a.txt")));
+        assert_eq!(args.positionals(), ["in"]);
+        assert_eq!(args.len(), 2);
+        assert!(spec.validate(&args).is_ok());
+        let mut args = spec.parse(&["-o", "b.txt"]);
+        args.merge(Arguments::parse(&["-o", "c.txt"]));
+        assert_eq!(args.get_vec("output").unwrap().len(), 2);
+        let args = Spec::from_optstring("vo:").parse(&["-vo", "x"]);
+        assert_eq!(args.get("o"), Some(Some("x")));
+    }
+
+    #[test]
+    fn posix() {
+        let spec = Spec::from_optstring("ab:W;");
+        assert!(spec.is_posix());
+        assert!(spec.get("a").unwrap().is_flag());
+        assert!(!spec.get("b").unwrap().is_flag());
+        let args = spec.parse(&["-ab", "-x", "-b-y", "-W", "color=auto", "file", "-a"]);
+        assert_eq!(
+            args.get_vec("b").unwrap(),
+            &vec![Some("-x".to_string()), Some("-y".to_string())]
+        );
+        assert_eq!(args.get_vec("a").unwrap().len(), 1);
+        assert_eq!(args.get("color"), Some(Some("auto")));
+        assert_eq!(args.positionals(), ["file", "-a"]);
+        let args = spec.parse(&["-a", "--", "-b"]);
+        assert_eq!(args.positionals(), ["-b"]);
+        assert!(!args.contains("b"));
+    }
+}
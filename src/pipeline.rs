@@ -0,0 +1,310 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::str::FromStr;
+
+use crate::{Arguments, Phase, Spec};
+
+/// A stage of the processing pipeline declared with
+/// `ArgSpec::pipeline`. Stages run in order, each receiving the output
+/// of the previous one
+#[derive(Clone, Copy, Debug)]
+pub enum Stage {
+    /// Strips leading and trailing whitespace
+    Trim,
+    /// Replaces `$NAME` and `${NAME}` with the value of the environment
+    /// variable, failing when it is not set
+    ExpandEnv,
+    /// Makes the path absolute and resolves `.`, `..` and symbolic
+    /// links, failing when it does not exist
+    Canonicalize,
+    /// Rejects values for which the check returns an error message,
+    /// reported under the label
+    Validate(&'static str, fn(&str) -> Result<(), String>),
+    /// Transforms the value or fails with an error message, reported
+    /// under the label
+    Map(&'static str, fn(&str) -> Result<String, String>),
+}
+
+impl Stage {
+    /// Returns the label errors of the stage are reported under
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stage::Trim => "trim",
+            Stage::ExpandEnv => "expand env",
+            Stage::Canonicalize => "canonicalize",
+            Stage::Validate(label, _) | Stage::Map(label, _) => label,
+        }
+    }
+
+    /// Runs the stage on a value
+    ///
+    /// # Arguments
+    ///
+    /// `value`: The output of the previous stage
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub(crate) fn apply(
+        &self,
+        value: &str,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<String, String> {
+        match self {
+            Stage::Trim => Ok(value.trim().to_string()),
+            Stage::ExpandEnv => expand_env(value, lookup),
+            Stage::Canonicalize => fs::canonicalize(value)
+                .map(|path| path.to_string_lossy().into_owned())
+                .map_err(|err| err.to_string()),
+            Stage::Validate(_, check) => check(value).map(|()| value.to_string()),
+            Stage::Map(_, map) => map(value),
+        }
+    }
+}
+
+/// Replaces `$NAME` and `${NAME}` with environment variables read
+/// through the lookup. A `$` not followed by a name is kept as is
+fn expand_env(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, tail) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => return Err(format!("unterminated ${{ in '{}'", value)),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        if name.is_empty() {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        match lookup(name) {
+            Some(var) => out.push_str(&var),
+            None => return Err(format!("environment variable {} is not set", name)),
+        }
+        rest = tail;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Error from a stage of a value pipeline
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineError {
+    /// The key of the argument
+    pub key: String,
+    /// The label of the failing stage, or `parse` when converting the
+    /// processed value failed
+    pub stage: &'static str,
+    /// The value the failing stage received
+    pub value: String,
+    /// Why the stage failed
+    pub message: String,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid value '{}' for -{} at stage {}: {}",
+            self.value, self.key, self.stage, self.message
+        )
+    }
+}
+
+impl Error for PipelineError {}
+
+impl Spec {
+    /// Runs a value through the pipeline of a declared argument. An
+    /// undeclared argument or one without a pipeline leaves the value
+    /// unchanged
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `value`: The raw value
+    pub fn process(&self, key: &str, value: &str) -> Result<String, PipelineError> {
+        self.process_with(key, value, |var| env::var(var).ok())
+    }
+
+    /// Runs a value through the pipeline of a declared argument like
+    /// `process`, reading environment variables through a lookup
+    /// function instead of the process environment
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `value`: The raw value
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub fn process_with<F: Fn(&str) -> Option<String>>(
+        &self,
+        key: &str,
+        value: &str,
+        lookup: F,
+    ) -> Result<String, PipelineError> {
+        let stages = self.get(key).map(|arg| arg.get_pipeline()).unwrap_or(&[]);
+        let mut value = value.to_string();
+        for stage in stages {
+            value = stage
+                .apply(&value, &lookup)
+                .map_err(|message| PipelineError {
+                    key: key.to_string(),
+                    stage: stage.label(),
+                    value,
+                    message,
+                })?;
+        }
+        Ok(value)
+    }
+
+    /// Gets the first value of an argument run through its pipeline and
+    /// then converted to the type, where a failed conversion is
+    /// reported as the `parse` stage. Returns `None` when the argument
+    /// is absent or has no value
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    ///
+    /// `key`: The key of the argument
+    pub fn get_processed<T: FromStr>(
+        &self,
+        args: &Arguments,
+        key: &str,
+    ) -> Result<Option<T>, PipelineError>
+    where
+        T::Err: Display,
+    {
+        let value = match args.get(key).flatten() {
+            Some(value) => args.time(Phase::Validation, || self.process(key, value))?,
+            None => return Ok(None),
+        };
+        value
+            .parse()
+            .map(Some)
+            .map_err(|err: T::Err| PipelineError {
+                key: key.to_string(),
+                stage: "parse",
+                message: err.to_string(),
+                value,
+            })
+    }
+
+    /// Gets every value of an argument run through its pipeline, in the
+    /// same order as `Arguments::get_vec`, keeping only the first
+    /// occurrence of each processed value when the argument is unique.
+    /// Instances without a value are skipped, and an absent argument
+    /// gives no values
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    ///
+    /// `key`: The key of the argument
+    pub fn get_processed_vec(
+        &self,
+        args: &Arguments,
+        key: &str,
+    ) -> Result<Vec<String>, PipelineError> {
+        let unique = self.get(key).is_some_and(|arg| arg.is_unique());
+        let mut processed: Vec<String> = Vec::new();
+        for value in args.get_vec(key).into_iter().flatten().flatten() {
+            let value = args.time(Phase::Validation, || self.process(key, value))?;
+            if !unique || !processed.contains(&value) {
+                processed.push(value);
+            }
+        }
+        Ok(processed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_dir::TestDir;
+    use crate::{ArgSpec, Arguments, PipelineError, Spec, Stage};
+
+    fn not_empty(value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            Err("must not be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stages() {
+        let spec = Spec::new().arg(ArgSpec::new("port").pipeline(&[
+            Stage::Trim,
+            Stage::ExpandEnv,
+            Stage::Validate("not empty", not_empty),
+        ]));
+        let env = |var: &str| (var == "PORT").then(|| "8080".to_string());
+        assert_eq!(
+            spec.process_with("port", " ${PORT} ", env),
+            Ok("8080".to_string())
+        );
+        assert_eq!(
+            spec.process_with("port", "$PORT/x", env),
+            Ok("8080/x".to_string())
+        );
+        let args = Arguments::parse(&["-port", " 8080 "]);
+        assert_eq!(spec.get_processed::<u16>(&args, "port"), Ok(Some(8080)));
+        assert_eq!(spec.process("port", "$ 5"), Ok("$ 5".to_string()));
+        assert_eq!(
+            spec.process("port", "  "),
+            Err(PipelineError {
+                key: "port".to_string(),
+                stage: "not empty",
+                value: String::new(),
+                message: "must not be empty".to_string(),
+            })
+        );
+        let err = spec.process_with("port", "$UNSET", env).unwrap_err();
+        assert_eq!(err.stage, "expand env");
+        assert_eq!(err.message, "environment variable UNSET is not set");
+        let err = spec
+            .get_processed::<u16>(&Arguments::parse(&["-port", "eighty"]), "port")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'eighty' for -port at stage parse: invalid digit found in string"
+        );
+        assert_eq!(spec.get_processed::<u16>(&args, "missing"), Ok(None));
+    }
+
+    #[test]
+    fn search_paths() {
+        let dir = TestDir::new("search-paths");
+        let spec = Spec::new().arg(
+            ArgSpec::new("I")
+                .glued_value()
+                .unique()
+                .pipeline(&[Stage::Canonicalize]),
+        );
+        let path = dir.path().to_str().unwrap();
+        let dotted = format!("{}/.", path);
+        let args = spec.parse(&["-I", path, &format!("-I{}", dotted), "-I", path]);
+        assert_eq!(args.count("I"), 2);
+        let canonical = std::fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(
+            spec.get_processed_vec(&args, "I"),
+            Ok(vec![canonical.to_string_lossy().into_owned()])
+        );
+        let missing = dir.join("missing");
+        let args = spec.parse(&["-I", missing.to_str().unwrap()]);
+        let err = spec.get_processed_vec(&args, "I").unwrap_err();
+        assert_eq!(err.stage, "canonicalize");
+        assert_eq!(spec.get_processed_vec(&args, "L"), Ok(vec![]));
+    }
+}
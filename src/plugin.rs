@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// An external executable implementing a subcommand, found on the
+/// search path under the name `<tool>-<subcommand>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plugin {
+    name: String,
+    path: PathBuf,
+}
+
+impl Plugin {
+    /// Returns the subcommand name the plugin implements
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path to the plugin executable
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Builds a command running the plugin with the given arguments
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments to forward to the plugin
+    pub fn to_command<S: AsRef<OsStr>>(&self, args: &[S]) -> Command {
+        let mut command = Command::new(&self.path);
+        command.args(args);
+        command
+    }
+}
+
+/// Discovers plugin subcommands for a tool, in the style of `git` and
+/// `cargo`, where `tool foo` runs an executable named `tool-foo`
+#[derive(Clone, Debug)]
+pub struct Plugins {
+    tool: String,
+    dirs: Vec<PathBuf>,
+}
+
+impl Plugins {
+    /// Creates a plugin finder searching the directories in `PATH`
+    ///
+    /// # Arguments
+    ///
+    /// `tool`: The name of the tool, used as the executable prefix
+    pub fn new(tool: &str) -> Plugins {
+        let dirs = env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default();
+        Plugins::with_dirs(tool, dirs)
+    }
+
+    /// Creates a plugin finder searching the given directories
+    ///
+    /// # Arguments
+    ///
+    /// `tool`: The name of the tool, used as the executable prefix
+    ///
+    /// `dirs`: The directories to search, in order of priority
+    pub fn with_dirs(tool: &str, dirs: Vec<PathBuf>) -> Plugins {
+        Plugins {
+            tool: tool.to_string(),
+            dirs,
+        }
+    }
+
+    /// Lists every discovered plugin sorted by name. When a plugin is
+    /// present in multiple directories, the first directory wins
+    pub fn list(&self) -> Vec<Plugin> {
+        let prefix = format!("{}-", self.tool);
+        let mut seen = HashSet::new();
+        let mut plugins = Vec::new();
+        for dir in &self.dirs {
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                let name = match file_name
+                    .to_str()
+                    .and_then(|name| name.strip_prefix(&prefix))
+                    .and_then(|name| name.strip_suffix(env::consts::EXE_SUFFIX))
+                {
+                    Some(name) if !name.is_empty() => name,
+                    _ => continue,
+                };
+                let path = entry.path();
+                if is_executable(&path) && seen.insert(name.to_string()) {
+                    plugins.push(Plugin {
+                        name: name.to_string(),
+                        path,
+                    });
+                }
+            }
+        }
+        plugins.sort_by(|a, b| a.name.cmp(&b.name));
+        plugins
+    }
+
+    /// Finds the plugin implementing a subcommand. Names containing a
+    /// path separator, `..` or a NUL are never found, so a subcommand
+    /// typed by the user cannot reach outside of the directories
+    ///
+    /// # Arguments
+    ///
+    /// `subcommand`: The name of the subcommand
+    pub fn find(&self, subcommand: &str) -> Option<Plugin> {
+        if subcommand.is_empty()
+            || subcommand.contains(['/', '\\', '\0'])
+            || subcommand.contains("..")
+        {
+            return None;
+        }
+        let file_name = format!("{}-{}{}", self.tool, subcommand, env::consts::EXE_SUFFIX);
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(&file_name))
+            .find(|path| is_executable(path))
+            .map(|path| Plugin {
+                name: subcommand.to_string(),
+                path,
+            })
+    }
+
+    /// Resolves the first argument to a plugin subcommand, returning
+    /// the plugin and the remaining arguments to forward to it. Built-in
+    /// subcommands should be matched before falling back to this
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, starting with the subcommand
+    pub fn resolve<'a, S: AsRef<str>>(&self, args: &'a [S]) -> Option<(Plugin, &'a [S])> {
+        let (first, rest) = args.split_first()?;
+        let first = first.as_ref();
+        if first.starts_with('-') {
+            return None;
+        }
+        Some((self.find(first)?, rest))
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|meta| meta.is_file())
+        .unwrap_or(false)
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::Plugins;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    fn plugin_dir(name: &str, files: &[(&str, u32)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "simple-args-plugins-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for &(file, mode) in files {
+            let path = dir.join(file);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn discover() {
+        let first = plugin_dir("first", &[("tool-sync", 0o755), ("tool-data", 0o644)]);
+        let second = plugin_dir("second", &[("tool-sync", 0o755), ("tool-lint", 0o755)]);
+        let plugins = Plugins::with_dirs("tool", vec![first.clone(), second.clone()]);
+        let list = plugins.list();
+        let names: Vec<&str> = list.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["lint", "sync"]);
+        assert_eq!(list[1].path(), first.join("tool-sync"));
+        assert!(plugins.find("data").is_none());
+        fs::remove_dir_all(first).unwrap();
+        fs::remove_dir_all(second).unwrap();
+    }
+
+    #[test]
+    fn resolve() {
+        let dir = plugin_dir("resolve", &[("tool-sync", 0o755)]);
+        let plugins = Plugins::with_dirs("tool", vec![dir.clone()]);
+        let args = ["sync", "-remote", "origin"];
+        let (plugin, rest) = plugins.resolve(&args).unwrap();
+        assert_eq!(plugin.name(), "sync");
+        assert_eq!(rest, &["-remote", "origin"]);
+        let command = plugin.to_command(rest);
+        assert_eq!(command.get_program(), dir.join("tool-sync"));
+        assert_eq!(command.get_args().count(), 2);
+        assert!(plugins.resolve(&["-sync"]).is_none());
+        assert!(plugins.resolve(&["status"]).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn traversal() {
+        let dir = plugin_dir("traversal", &[]);
+        for nested in ["tool-a", "tool-.."] {
+            fs::create_dir(dir.join(nested)).unwrap();
+            let path = dir.join(nested).join("b");
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let plugins = Plugins::with_dirs("tool", vec![dir.clone()]);
+        for subcommand in ["a/b", "../b", "a\\b", "a\0b"] {
+            assert!(plugins.find(subcommand).is_none(), "{:?}", subcommand);
+        }
+        assert!(plugins.resolve(&["a/b"]).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::small::SmallStr;
+use crate::{Arguments, Source, SourceKind};
+
+/// Numeric precedence of the sources of arguments, where values from a
+/// heavier source take effect over lighter ones. Weights can be set for
+/// every key or for a single key, and a key can be locked to a source
+/// so no other source can override it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Precedence {
+    weights: Vec<(SourceKind, i32)>,
+    keys: Vec<(Cow<'static, str>, SourceKind, i32)>,
+}
+
+impl Precedence {
+    /// Creates the usual precedence: the command line over the
+    /// environment over embedded front-ends over configuration files
+    /// over defaults, weighing 40, 30, 20, 10 and 0
+    pub fn new() -> Precedence {
+        Precedence {
+            weights: vec![
+                (SourceKind::CommandLine, 40),
+                (SourceKind::Env, 30),
+                (SourceKind::Origin, 20),
+                (SourceKind::File, 10),
+                (SourceKind::Default, 0),
+            ],
+            keys: Vec::new(),
+        }
+    }
+
+    /// Sets the weight of a source for every key
+    ///
+    /// # Arguments
+    ///
+    /// `kind`: The kind of source
+    ///
+    /// `weight`: The weight, where heavier sources take effect
+    pub fn source(mut self, kind: SourceKind, weight: i32) -> Precedence {
+        match self
+            .weights
+            .iter_mut()
+            .find(|(existing, _)| *existing == kind)
+        {
+            Some((_, existing)) => *existing = weight,
+            None => self.weights.push((kind, weight)),
+        }
+        self
+    }
+
+    /// Sets the weight of a source for a single key, over the weight
+    /// set for every key
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `kind`: The kind of source
+    ///
+    /// `weight`: The weight, where heavier sources take effect
+    pub fn key<K: Into<Cow<'static, str>>>(
+        mut self,
+        key: K,
+        kind: SourceKind,
+        weight: i32,
+    ) -> Precedence {
+        self.keys.push((key.into(), kind, weight));
+        self
+    }
+
+    /// Locks a key to a source, so values from any other source never
+    /// take effect over it, as for a setting fixed by configuration
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `kind`: The kind of source the key is locked to
+    pub fn lock<K: Into<Cow<'static, str>>>(self, key: K, kind: SourceKind) -> Precedence {
+        self.key(key, kind, i32::MAX)
+    }
+
+    /// Returns the weight of a source for a key
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the argument
+    ///
+    /// `kind`: The kind of source
+    pub fn weight(&self, key: &str, kind: SourceKind) -> i32 {
+        self.keys
+            .iter()
+            .rev()
+            .find(|(existing, existing_kind, _)| existing == key && *existing_kind == kind)
+            .map(|&(_, _, weight)| weight)
+            .or_else(|| {
+                self.weights
+                    .iter()
+                    .find(|(existing, _)| *existing == kind)
+                    .map(|&(_, weight)| weight)
+            })
+            .unwrap_or(0)
+    }
+
+    /// Reorders the values of every key from the heaviest source to the
+    /// lightest, so `get` returns the value that takes effect. Values
+    /// from equally heavy sources keep the order they were added in, so
+    /// the result never depends on hashing. Merge every layer with
+    /// `Arguments::merge` beforehand so each candidate is kept. Returns
+    /// how each key was decided, sorted by key
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments from every source
+    pub fn apply(&self, args: &mut Arguments) -> Vec<Decision> {
+        let mut keys: Vec<SmallStr> = args.arg_map.keys().cloned().collect();
+        keys.sort_unstable();
+        let mut decisions = Vec::with_capacity(keys.len());
+        for key in keys {
+            let vals = args.arg_map.remove(&key).unwrap_or_default();
+            let sources = args.sources.remove(&key).unwrap_or_default();
+            let mut candidates: Vec<(i32, Option<String>, (Source, usize))> = vals
+                .into_iter()
+                .zip(sources)
+                .map(|(val, source)| (self.weight(&key, source.0.kind()), val, source))
+                .collect();
+            candidates.sort_by_key(|(weight, _, (_, ordinal))| (-(*weight as i64), *ordinal));
+            decisions.push(Decision {
+                key: key.to_string(),
+                value: candidates[0].1.clone(),
+                candidates: candidates
+                    .iter()
+                    .map(|(weight, _, (source, _))| (source.clone(), *weight))
+                    .collect(),
+            });
+            for (_, val, source) in candidates {
+                args.arg_map.insert(key.clone(), val);
+                args.sources.insert(key.clone(), source);
+            }
+        }
+        decisions
+    }
+}
+
+impl Default for Precedence {
+    fn default() -> Precedence {
+        Precedence::new()
+    }
+}
+
+/// How the value of a key was decided by `Precedence::apply`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decision {
+    /// The key of the argument
+    pub key: String,
+    /// The value that takes effect
+    pub value: Option<String>,
+    /// The source of every value with its weight, from the one taking
+    /// effect to the lightest
+    pub candidates: Vec<(Source, i32)>,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "-{}", self.key)?;
+        if let Some(value) = &self.value {
+            write!(f, " = '{}'", value)?;
+        }
+        let mut candidates = self.candidates.iter();
+        if let Some((source, weight)) = candidates.next() {
+            write!(f, " from {} (weight {})", source, weight)?;
+        }
+        for (i, (source, weight)) in candidates.enumerate() {
+            let separator = if i == 0 { ", over" } else { "," };
+            write!(f, "{} {} (weight {})", separator, source, weight)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Arguments, Precedence, Source, SourceKind};
+
+    fn layered() -> Arguments {
+        let mut args = Arguments::parse_with_source(
+            &["-port", "80", "-host", "file"],
+            Source::File("app.conf".into()),
+        );
+        args.merge(Arguments::parse(&["-port", "8080", "-host", "cli"]));
+        args.merge(Arguments::parse_with_source(
+            &["-port", "9000"],
+            Source::Env("PORT".to_string()),
+        ));
+        args
+    }
+
+    #[test]
+    fn weights() {
+        let mut args = layered();
+        let decisions = Precedence::new().apply(&mut args);
+        assert_eq!(args.get("port"), Some(Some("8080")));
+        assert_eq!(args.get("host"), Some(Some("cli")));
+        assert_eq!(
+            args.get_vec("port").unwrap(),
+            &[
+                Some("8080".to_string()),
+                Some("9000".to_string()),
+                Some("80".to_string())
+            ]
+        );
+        assert_eq!(
+            decisions[1].to_string(),
+            "-port = '8080' from command line (weight 40), \
+             over environment variable PORT (weight 30), file app.conf (weight 10)"
+        );
+        let mut args = layered();
+        Precedence::new()
+            .source(SourceKind::File, 45)
+            .apply(&mut args);
+        assert_eq!(args.get("port"), Some(Some("80")));
+        assert_eq!(args.get("host"), Some(Some("file")));
+    }
+
+    #[test]
+    fn locked_keys() {
+        let mut args = layered();
+        let precedence = Precedence::new().lock("port", SourceKind::File);
+        let decisions = precedence.apply(&mut args);
+        assert_eq!(args.get("port"), Some(Some("80")));
+        assert_eq!(args.get("host"), Some(Some("cli")));
+        assert_eq!(decisions[1].key, "port");
+        assert_eq!(
+            decisions[1].candidates[0],
+            (Source::File("app.conf".into()), i32::MAX)
+        );
+        assert_eq!(precedence.weight("host", SourceKind::File), 10);
+    }
+}
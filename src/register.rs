@@ -0,0 +1,101 @@
+use crate::{Arguments, Spec};
+
+/// Components that contribute their own arguments to an extensible
+/// application, such as dynamically loaded plugins. Their arguments are
+/// namespaced on the command line as `-<namespace>.<key>`, and each
+/// component receives only its own arguments with the namespace removed
+pub trait RegisterArgs {
+    /// Returns the namespace of the arguments of the component
+    fn namespace(&self) -> &str;
+
+    /// Returns the specification of the arguments of the component,
+    /// with keys relative to its namespace
+    fn spec(&self) -> Spec;
+
+    /// Receives the arguments of the component at startup, with the
+    /// namespace removed from their keys and fallbacks from its
+    /// specification applied
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments of the component
+    fn receive_args(&mut self, args: &Arguments);
+}
+
+impl Arguments {
+    /// Returns the arguments under a namespace, written as
+    /// `-<namespace>.<key>`, with the namespace removed from their keys
+    /// and their sources kept
+    ///
+    /// # Arguments
+    ///
+    /// `namespace`: The namespace to extract
+    pub fn namespaced(&self, namespace: &str) -> Arguments {
+        let prefix = format!("{}.", namespace);
+        let mut scoped = Arguments::empty();
+        for (key, _) in self.arg_map.iter() {
+            let name = match key.strip_prefix(&prefix) {
+                Some(name) => name,
+                None => continue,
+            };
+            for (val, source) in self.values_with_sources(key) {
+                scoped.insert(name, val.map(str::to_string), source.clone());
+            }
+        }
+        scoped
+    }
+
+    /// Hands every component its namespaced arguments, applying the
+    /// fallbacks declared in its specification first
+    ///
+    /// # Arguments
+    ///
+    /// `components`: The components to configure
+    pub fn register_all(&self, components: &mut [&mut dyn RegisterArgs]) {
+        for component in components {
+            let mut scoped = self.namespaced(component.namespace());
+            component.spec().apply_fallbacks(&mut scoped);
+            component.receive_args(&scoped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RegisterArgs;
+    use crate::{ArgSpec, Arguments, Spec};
+
+    #[derive(Default)]
+    struct Cache {
+        size: Option<String>,
+        seen: usize,
+    }
+
+    impl RegisterArgs for Cache {
+        fn namespace(&self) -> &str {
+            "cache"
+        }
+
+        fn spec(&self) -> Spec {
+            Spec::new().arg(ArgSpec::new("size").default_value("64"))
+        }
+
+        fn receive_args(&mut self, args: &Arguments) {
+            self.size = args.get("size").flatten().map(str::to_string);
+            self.seen = args.len();
+        }
+    }
+
+    #[test]
+    fn namespaced_components() {
+        let args = Arguments::parse(&["-cache.verbose", "-log.level", "debug"]);
+        let mut cache = Cache::default();
+        args.register_all(&mut [&mut cache]);
+        assert_eq!(cache.size.as_deref(), Some("64"));
+        assert_eq!(cache.seen, 2);
+        let args = Arguments::parse(&["-cache.size", "128"]);
+        args.register_all(&mut [&mut cache]);
+        assert_eq!(cache.size.as_deref(), Some("128"));
+        assert!(!args.namespaced("log").contains("size"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::{Arguments, Phase, Spec};
+
+/// Why remote configuration did not contribute to the resolved
+/// arguments
+#[derive(Debug)]
+pub enum Degradation {
+    /// The remote source did not answer within the timeout
+    TimedOut(Duration),
+    /// The remote source failed
+    Unreachable(io::Error),
+    /// Resolution was cancelled before the remote source answered
+    Cancelled,
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Degradation::TimedOut(timeout) => write!(
+                f,
+                "remote configuration timed out after {}ms, using command line and environment only",
+                timeout.as_millis()
+            ),
+            Degradation::Unreachable(err) => write!(
+                f,
+                "remote configuration is unreachable ({}), using command line and environment only",
+                err
+            ),
+            Degradation::Cancelled => write!(
+                f,
+                "remote configuration was cancelled, using command line and environment only"
+            ),
+        }
+    }
+}
+
+impl Error for Degradation {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Degradation::Unreachable(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Arguments resolved from every layer that could be reached
+pub struct Layered {
+    /// The resolved arguments
+    pub args: Arguments,
+    /// Why remote configuration is missing from the arguments, if it is
+    pub degraded: Option<Degradation>,
+}
+
+impl Spec {
+    /// Merges remote configuration below the local arguments, which are
+    /// usually the command line with environment fallbacks applied.
+    /// When the remote source fails or does not answer within the
+    /// timeout, the local arguments are used alone and the degradation
+    /// is reported. Nothing is merged until the remote source answers,
+    /// so dropping the future is always safe
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The local arguments
+    ///
+    /// `remote`: Fetches the remote configuration
+    ///
+    /// `timeout`: How long to wait for the remote source
+    pub async fn resolve_layers<F: Future<Output = io::Result<Arguments>>>(
+        &self,
+        args: Arguments,
+        remote: F,
+        timeout: Duration,
+    ) -> Layered {
+        self.resolve_layers_until(args, remote, timeout, std::future::pending())
+            .await
+    }
+
+    /// Merges remote configuration below the local arguments like
+    /// `resolve_layers`, giving up on the remote source as soon as the
+    /// cancellation future completes, such as on a shutdown signal
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The local arguments
+    ///
+    /// `remote`: Fetches the remote configuration
+    ///
+    /// `timeout`: How long to wait for the remote source
+    ///
+    /// `cancel`: Completes when resolution should stop waiting
+    pub async fn resolve_layers_until<F, C>(
+        &self,
+        mut args: Arguments,
+        remote: F,
+        timeout: Duration,
+        cancel: C,
+    ) -> Layered
+    where
+        F: Future<Output = io::Result<Arguments>>,
+        C: Future<Output = ()>,
+    {
+        let start = Instant::now();
+        let degraded = tokio::select! {
+            fetched = tokio::time::timeout(timeout, remote) => match fetched {
+                Ok(Ok(lower)) => {
+                    self.merge(&mut args, lower);
+                    None
+                }
+                Ok(Err(err)) => Some(Degradation::Unreachable(err)),
+                Err(_) => Some(Degradation::TimedOut(timeout)),
+            },
+            () = cancel => Some(Degradation::Cancelled),
+        };
+        args.record(Phase::Remote, start.elapsed());
+        Layered { args, degraded }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::{pending, ready};
+    use std::io;
+    use std::time::Duration;
+
+    use crate::{Arguments, Degradation, Source, Spec};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn layers() {
+        let spec = Spec::new();
+        let timeout = Duration::from_millis(10);
+        let remote = Arguments::parse_with_source(
+            &["-port", "80", "-host", "remote"],
+            Source::File("remote".into()),
+        );
+        let layered = block_on(spec.resolve_layers(
+            Arguments::parse(&["-port", "8080"]),
+            ready(Ok(remote)),
+            timeout,
+        ));
+        assert!(layered.degraded.is_none());
+        assert_eq!(layered.args.get("port"), Some(Some("8080")));
+        assert_eq!(layered.args.get("host"), Some(Some("remote")));
+        let layered =
+            block_on(spec.resolve_layers(Arguments::parse(&["-port", "8080"]), pending(), timeout));
+        assert!(matches!(layered.degraded, Some(Degradation::TimedOut(_))));
+        assert_eq!(layered.args.get("port"), Some(Some("8080")));
+        let layered = block_on(spec.resolve_layers(
+            Arguments::parse(&["-port", "8080"]),
+            ready(Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "refused",
+            ))),
+            timeout,
+        ));
+        assert_eq!(
+            layered.degraded.unwrap().to_string(),
+            "remote configuration is unreachable (refused), using command line and environment only"
+        );
+        let layered = block_on(spec.resolve_layers_until(
+            Arguments::parse(&["-port", "8080"]),
+            pending(),
+            Duration::from_secs(60),
+            ready(()),
+        ));
+        assert!(matches!(layered.degraded, Some(Degradation::Cancelled)));
+        assert!(!layered.args.contains("host"));
+    }
+}
@@ -0,0 +1,439 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::drift::{escape, unescape};
+use crate::{strip_key, ArgSpec, Arguments, AuditPolicy, FileValue, Source, Spec};
+
+/// The values of a recorded layer, in order
+type LayerValues = Vec<(String, Option<String>)>;
+
+/// The raw inputs of a parse, written to a replay file so a user's parse
+/// can be reproduced when triaging issues. Values of redacted arguments
+/// are replaced by `***` as they are recorded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recording {
+    policy: AuditPolicy,
+    argv: Vec<String>,
+    env: Vec<(String, String)>,
+    layers: Vec<(PathBuf, LayerValues)>,
+}
+
+impl Recording {
+    /// Adds a configuration layer below the command line, such as a
+    /// parsed configuration file. Layers are replayed in the order they
+    /// are added, from the highest precedence to the lowest
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path the layer was read from
+    ///
+    /// `args`: The arguments of the layer
+    pub fn layer<P: Into<PathBuf>>(mut self, path: P, args: &Arguments) -> Recording {
+        let mut keys: Vec<&str> = args.arg_map.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        let pairs = keys
+            .into_iter()
+            .flat_map(|key| {
+                let redacted = self.policy.is_redacted_as(key, |key| args.canonical(key));
+                args.arg_map
+                    .get_vec(key)
+                    .into_iter()
+                    .flatten()
+                    .map(move |val| {
+                        let val = val.as_ref().map(|val| {
+                            if redacted {
+                                String::from("***")
+                            } else {
+                                val.clone()
+                            }
+                        });
+                        (key.to_string(), val)
+                    })
+            })
+            .collect();
+        self.layers.push((path.into(), pairs));
+        self
+    }
+
+    /// Returns the recorded raw arguments
+    pub fn argv(&self) -> &[String] {
+        &self.argv
+    }
+
+    /// Returns the recorded environment variables read by the
+    /// specification, where a `_FILE` variable is recorded as the
+    /// contents of its file under the variable it stands in for
+    pub fn env(&self) -> &[(String, String)] {
+        &self.env
+    }
+
+    /// Writes the recording to a replay file
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the replay file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Reads a recording from a replay file
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the replay file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Recording> {
+        Recording::from_text(&fs::read_to_string(path)?)
+            .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+
+    /// Renders the recording as one tab-separated entry per line
+    fn to_text(&self) -> String {
+        let mut out = String::new();
+        let mut line = |fields: &[&str]| {
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push('\t');
+                }
+                escape(&mut out, field);
+            }
+            out.push('\n');
+        };
+        for token in &self.argv {
+            line(&["arg", token]);
+        }
+        for (var, value) in &self.env {
+            line(&["env", var, value]);
+        }
+        for (path, pairs) in &self.layers {
+            line(&["layer", &path.to_string_lossy()]);
+            for (key, val) in pairs {
+                match val {
+                    Some(val) => line(&["value", key, val]),
+                    None => line(&["key", key]),
+                }
+            }
+        }
+        out
+    }
+
+    /// Reads a recording rendered by `to_text`
+    fn from_text(text: &str) -> Result<Recording, String> {
+        let mut recording = Recording {
+            policy: AuditPolicy::new(),
+            argv: Vec::new(),
+            env: Vec::new(),
+            layers: Vec::new(),
+        };
+        for (i, line) in text.lines().enumerate() {
+            let fields = line
+                .split('\t')
+                .map(unescape)
+                .collect::<Result<Vec<String>, String>>()
+                .map_err(|message| format!("line {}: {}", i + 1, message))?;
+            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+            match fields[..] {
+                [""] => {}
+                ["arg", token] => recording.argv.push(token.to_string()),
+                ["env", var, value] => recording.env.push((var.to_string(), value.to_string())),
+                ["layer", path] => recording.layers.push((PathBuf::from(path), Vec::new())),
+                ["value", key, val] => recording.push_pair(i, key, Some(val.to_string()))?,
+                ["key", key] => recording.push_pair(i, key, None)?,
+                _ => return Err(format!("line {}: unknown entry '{}'", i + 1, line)),
+            }
+        }
+        Ok(recording)
+    }
+
+    /// Adds a value read from line `i` of a replay file to the last
+    /// layer
+    fn push_pair(&mut self, i: usize, key: &str, val: Option<String>) -> Result<(), String> {
+        let (_, pairs) = self
+            .layers
+            .last_mut()
+            .ok_or_else(|| format!("line {}: value outside of a layer", i + 1))?;
+        pairs.push((key.to_string(), val));
+        Ok(())
+    }
+
+    /// Merges the recorded layers below the arguments
+    fn merge_layers(&self, args: &mut Arguments, spec: Option<&Spec>) {
+        for (path, pairs) in &self.layers {
+            let mut layer = Arguments::empty();
+            for (key, val) in pairs {
+                layer.insert(key, val.clone(), Source::File(path.clone()));
+            }
+            match spec {
+                Some(spec) => spec.merge(args, layer),
+                None => args.merge(layer),
+            }
+        }
+    }
+}
+
+impl Spec {
+    /// Records the raw arguments and the environment variables read by
+    /// the specification for a replay file, redacting values per the
+    /// policy. Configuration layers are added with `Recording::layer`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The raw arguments, excluding the program name
+    ///
+    /// `policy`: Which arguments to redact
+    pub fn record<S: AsRef<str>>(&self, args: &[S], policy: &AuditPolicy) -> Recording {
+        self.record_with(args, policy, |var| std::env::var(var).ok())
+    }
+
+    /// Records the raw arguments like `record`, reading environment
+    /// variables through a lookup function instead of the process
+    /// environment
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The raw arguments, excluding the program name
+    ///
+    /// `policy`: Which arguments to redact
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub fn record_with<S: AsRef<str>, F: Fn(&str) -> Option<String>>(
+        &self,
+        args: &[S],
+        policy: &AuditPolicy,
+        lookup: F,
+    ) -> Recording {
+        let mut argv = Vec::with_capacity(args.len());
+        let mut value_of: Option<&str> = None;
+        let mut tokens = args.iter().map(AsRef::as_ref);
+        while let Some(token) = tokens.next() {
+            if let Some(name) = value_of.take() {
+                if self.is_value(name, token) {
+                    argv.push(String::from("***"));
+                    if self.get(name).is_some_and(ArgSpec::is_multi_value) {
+                        value_of = Some(name);
+                    }
+                    continue;
+                }
+            }
+            if token == "--" && !self.is_posix() {
+                argv.push(token.to_string());
+                argv.extend(tokens.by_ref().map(str::to_string));
+                break;
+            }
+            let key = match strip_key(token) {
+                Some(key) if self.unescape(token).is_none() => key,
+                _ => {
+                    argv.push(token.to_string());
+                    continue;
+                }
+            };
+            // split the key from its value as `Spec::parse` does
+            let (name, attached) = match (self.glued(token, key), key.split_once('=')) {
+                (Some((_, name, glued)), _) => (name, Some(glued)),
+                (None, Some((name, attached))) => (name, Some(attached)),
+                (None, None) => (key, None),
+            };
+            let name = self.cluster(token, name).map_or(name, |(_, last)| last);
+            let name = self.canonical(self.correct_key(name).unwrap_or(name));
+            if !policy.is_redacted_as(name, |key| self.canonical(key)) {
+                argv.push(token.to_string());
+                continue;
+            }
+            match attached {
+                Some(value) => {
+                    argv.push(format!("{}***", &token[..token.len() - value.len()]));
+                }
+                None => {
+                    let takes_value = !self.get(name).is_some_and(|arg| {
+                        arg.is_flag() || arg.is_optional_value() || arg.get_terminator().is_some()
+                    }) && self.negated_flag(name).is_none();
+                    value_of = Some(name).filter(|_| takes_value);
+                    argv.push(token.to_string());
+                }
+            }
+        }
+        let mut env = Vec::new();
+        for arg in self.args() {
+            let var = match self.env_var(arg) {
+                Some(var) => var,
+                None => continue,
+            };
+            let value = lookup(var.as_ref())
+                .filter(|value| !value.is_empty())
+                .or_else(|| {
+                    let path = lookup(&format!("{}_FILE", var))?;
+                    FileValue::read(&path)
+                        .ok()
+                        .map(|file| file.trimmed().to_string())
+                });
+            if let Some(value) = value {
+                let value = if policy.is_redacted_as(arg.name(), |key| self.canonical(key)) {
+                    String::from("***")
+                } else {
+                    value
+                };
+                env.push((var.to_string(), value));
+            }
+        }
+        Recording {
+            policy: policy.clone(),
+            argv,
+            env,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Reproduces a recorded parse: the recorded arguments are parsed
+    /// according to the specification, the recorded layers are merged
+    /// below them and fallbacks are applied from the recorded
+    /// environment instead of the process environment
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the replay file
+    pub fn replay<P: AsRef<Path>>(&self, path: P) -> io::Result<Arguments> {
+        let recording = Recording::load(path)?;
+        let mut args = self.parse(recording.argv());
+        recording.merge_layers(&mut args, Some(self));
+        self.apply_fallbacks_with(&mut args, |var| {
+            recording
+                .env()
+                .iter()
+                .find(|(recorded, _)| recorded == var)
+                .map(|(_, value)| value.clone())
+        });
+        Ok(args)
+    }
+}
+
+impl Arguments {
+    /// Reproduces a recorded parse without a specification: the recorded
+    /// arguments are parsed like `parse` and the recorded layers are
+    /// merged below them. The recorded environment is only applied by
+    /// `Spec::replay`, which knows the variables of each argument
+    ///
+    /// # Arguments
+    ///
+    /// `path`: The path of the replay file
+    pub fn replay<P: AsRef<Path>>(path: P) -> io::Result<Arguments> {
+        let recording = Recording::load(path)?;
+        let mut args = Arguments::parse(recording.argv());
+        recording.merge_layers(&mut args, None);
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_dir::TestDir;
+    use crate::{ArgSpec, Arguments, AuditPolicy, Source, Spec};
+
+    #[test]
+    fn record_and_replay() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("user"))
+            .arg(ArgSpec::new("password"))
+            .arg(ArgSpec::new("token").env("SIMPLE_ARGS_REPLAY_TOKEN"))
+            .arg(ArgSpec::new("region").env("SIMPLE_ARGS_REPLAY_REGION"));
+        let env = |var: &str| match var {
+            "SIMPLE_ARGS_REPLAY_TOKEN" => Some("secret".to_string()),
+            "SIMPLE_ARGS_REPLAY_REGION" => Some("eu\twest".to_string()),
+            _ => None,
+        };
+        let policy = AuditPolicy::new().redact("password").redact("token");
+        let recording = spec
+            .record_with(
+                &[
+                    "-user",
+                    "alice",
+                    "-password",
+                    "hunter2",
+                    "--password=x",
+                    "in.txt",
+                ],
+                &policy,
+                env,
+            )
+            .layer(
+                "app.conf",
+                &Arguments::parse(&["-user", "bob", "-verbose", "-token", "t"]),
+            );
+        assert_eq!(
+            recording.argv(),
+            [
+                "-user",
+                "alice",
+                "-password",
+                "***",
+                "--password=***",
+                "in.txt"
+            ]
+        );
+        assert_eq!(
+            recording.env(),
+            [
+                ("SIMPLE_ARGS_REPLAY_TOKEN".to_string(), "***".to_string()),
+                (
+                    "SIMPLE_ARGS_REPLAY_REGION".to_string(),
+                    "eu\twest".to_string()
+                ),
+            ]
+        );
+        let dir = TestDir::new("replay");
+        let path = dir.join("args.replay");
+        recording.save(&path).unwrap();
+        let args = spec.replay(&path).unwrap();
+        assert_eq!(args.get_vec("user").unwrap().len(), 1);
+        assert_eq!(args.get("user"), Some(Some("alice")));
+        assert_eq!(args.get("password"), Some(Some("***")));
+        assert_eq!(args.get("verbose"), Some(None));
+        assert_eq!(args.get("token"), Some(Some("***")));
+        assert_eq!(args.get("region"), Some(Some("eu\twest")));
+        assert_eq!(args.positionals(), ["in.txt"]);
+        let args = Arguments::replay(&path).unwrap();
+        assert_eq!(
+            args.values_with_sources("user").nth(1).unwrap().1,
+            &Source::File("app.conf".into())
+        );
+        assert!(!args.contains("region"));
+    }
+
+    #[test]
+    fn redaction() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("password").aliases(&["p"]))
+            .arg(ArgSpec::new("D").glued_value())
+            .arg(ArgSpec::new("v").flag());
+        let policy = AuditPolicy::new().redact("password").redact("D");
+        let recording = spec.record(
+            &[
+                "-p",
+                "secret",
+                "-Dkey=value",
+                "-password",
+                "-123",
+                "-v",
+                "-p=x",
+                "--",
+                "-p",
+                "kept",
+            ],
+            &policy,
+        );
+        assert_eq!(
+            recording.argv(),
+            [
+                "-p",
+                "***",
+                "-D***",
+                "-password",
+                "***",
+                "-v",
+                "-p=***",
+                "--",
+                "-p",
+                "kept"
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{Arguments, FromArgsError, Spec};
+
+/// Resolves the value of an argument against a context supplied at
+/// access time, for values like `-color auto` that only take meaning
+/// from the environment the program runs in, such as whether the output
+/// is a terminal. Implemented for closures taking the value and the
+/// context
+pub trait Resolve<C> {
+    /// The type the value resolves to
+    type Output;
+
+    /// Resolves a value, returning an error message when it is invalid
+    ///
+    /// # Arguments
+    ///
+    /// `value`: The value of the argument
+    ///
+    /// `context`: The context to resolve against
+    fn resolve(&self, value: &str, context: &C) -> Result<Self::Output, String>;
+}
+
+impl<C, T, F: Fn(&str, &C) -> Result<T, String>> Resolve<C> for F {
+    type Output = T;
+
+    fn resolve(&self, value: &str, context: &C) -> Result<T, String> {
+        self(value, context)
+    }
+}
+
+/// A handle to a declared argument whose value is resolved against a
+/// context when accessed rather than when parsed
+#[derive(Clone, Copy, Debug)]
+pub struct Resolved<R> {
+    key: &'static str,
+    resolver: R,
+}
+
+impl<R> Resolved<R> {
+    /// Returns the key of the argument
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Resolves the first value of the argument if present, failing
+    /// when it is given without a value or the resolver rejects it
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    ///
+    /// `context`: The context to resolve against
+    pub fn resolve<C>(
+        &self,
+        args: &Arguments,
+        context: &C,
+    ) -> Result<Option<R::Output>, FromArgsError>
+    where
+        R: Resolve<C>,
+    {
+        match args.values_with_sources(self.key).next() {
+            None => Ok(None),
+            Some((None, _)) => Err(FromArgsError::MissingValue(self.key.to_string())),
+            Some((Some(value), source)) => {
+                self.resolver
+                    .resolve(value, context)
+                    .map(Some)
+                    .map_err(|message| FromArgsError::Invalid {
+                        key: self.key.to_string(),
+                        value: value.to_string(),
+                        source: source.clone(),
+                        message,
+                    })
+            }
+        }
+    }
+}
+
+impl Spec {
+    /// Creates a handle to a declared argument whose value is resolved
+    /// against a context at access time. Declaring a default value such
+    /// as `auto` makes it resolve even when absent after fallbacks
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the declared argument
+    ///
+    /// `resolver`: Resolves the value against the context
+    ///
+    /// # Panics
+    ///
+    /// Panics when the argument is not declared
+    pub fn resolved<R>(&self, key: &'static str, resolver: R) -> Resolved<R> {
+        assert!(self.get(key).is_some(), "argument -{} is not declared", key);
+        Resolved { key, resolver }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, FromArgsError, Spec};
+
+    struct Terminal {
+        is_tty: bool,
+    }
+
+    #[test]
+    fn context_resolution() {
+        let spec = Spec::new().arg(ArgSpec::new("color"));
+        let color = spec.resolved("color", |value: &str, terminal: &Terminal| match value {
+            "always" => Ok(true),
+            "never" => Ok(false),
+            "auto" => Ok(terminal.is_tty),
+            _ => Err("expected always, never or auto".to_string()),
+        });
+        let args = Arguments::parse(&["-color", "auto"]);
+        assert_eq!(
+            color.resolve(&args, &Terminal { is_tty: true }),
+            Ok(Some(true))
+        );
+        assert_eq!(
+            color.resolve(&args, &Terminal { is_tty: false }),
+            Ok(Some(false))
+        );
+        assert_eq!(
+            color.resolve(&Arguments::parse::<&str>(&[]), &Terminal { is_tty: true }),
+            Ok(None)
+        );
+        assert!(matches!(
+            color.resolve(
+                &Arguments::parse(&["-color", "red"]),
+                &Terminal { is_tty: true }
+            ),
+            Err(FromArgsError::Invalid { .. })
+        ));
+    }
+}
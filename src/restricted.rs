@@ -0,0 +1,123 @@
+use crate::{Arguments, Source};
+
+/// A read-only view of arguments that only answers for an allowed set
+/// of keys, with every other key behaving as absent. Handing this to
+/// untrusted code, such as a plugin, keeps secrets out of its reach
+#[derive(Clone, Copy)]
+pub struct Restricted<'a> {
+    args: &'a Arguments,
+    allowed: &'a [&'a str],
+}
+
+impl<'a> Restricted<'a> {
+    /// Checks whether a key is visible through the view
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn is_allowed(&self, key: &str) -> bool {
+        self.allowed.contains(&key)
+    }
+
+    /// Checks whether or not an allowed argument is present
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn contains(&self, key: &str) -> bool {
+        self.is_allowed(key) && self.args.contains(key)
+    }
+
+    /// Checks whether or not an allowed argument is present with a
+    /// non-empty value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to check
+    pub fn contains_val(&self, key: &str) -> bool {
+        self.is_allowed(key) && self.args.contains_val(key)
+    }
+
+    /// Checks whether or not no allowed argument is present
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the first value of an allowed argument
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get(&self, key: &str) -> Option<Option<&'a str>> {
+        if !self.is_allowed(key) {
+            return None;
+        }
+        self.args.get(key)
+    }
+
+    /// Gets all values of an allowed argument
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn get_vec(&self, key: &str) -> Option<&'a Vec<Option<String>>> {
+        if !self.is_allowed(key) {
+            return None;
+        }
+        self.args.get_vec(key)
+    }
+
+    /// Gets all values of an allowed argument along with their sources
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    pub fn values_with_sources(
+        &self,
+        key: &str,
+    ) -> impl Iterator<Item = (Option<&'a str>, &'a Source)> + 'a {
+        let allowed = self.is_allowed(key);
+        self.args.values_with_sources(key).filter(move |_| allowed)
+    }
+
+    /// Returns the number of allowed arguments that are present
+    pub fn len(&self) -> usize {
+        self.allowed
+            .iter()
+            .filter(|key| self.args.contains(key))
+            .count()
+    }
+}
+
+impl Arguments {
+    /// Creates a view that only answers for the given keys, with every
+    /// other key behaving as absent
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The keys visible through the view
+    pub fn restricted<'a>(&'a self, keys: &'a [&'a str]) -> Restricted<'a> {
+        Restricted {
+            args: self,
+            allowed: keys,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Arguments;
+
+    #[test]
+    fn restricted_view() {
+        let args = Arguments::parse(&["-user", "alice", "-token", "secret", "-verbose"]);
+        let view = args.restricted(&["user", "verbose", "missing"]);
+        assert_eq!(view.get("user"), Some(Some("alice")));
+        assert!(view.contains("verbose"));
+        assert!(!view.contains("token"));
+        assert_eq!(view.get("token"), None);
+        assert_eq!(view.values_with_sources("token").count(), 0);
+        assert_eq!(view.values_with_sources("user").count(), 1);
+        assert_eq!(view.len(), 2);
+    }
+}
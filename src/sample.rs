@@ -0,0 +1,337 @@
+use std::fmt;
+
+use crate::{ArgSpec, Spec};
+
+/// The size of the values of `FuzzKind::Huge` cases
+const HUGE: usize = 64 * 1024;
+
+/// Values of the wrong type for numeric arguments
+const WRONG_TYPES: [&str; 4] = ["abc", "-1", "340282366920938463463374607431768211456", ""];
+
+/// What a command line of a fuzzing corpus exercises
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FuzzKind {
+    /// A valid command line from `Spec::sample_invocations`
+    Valid,
+    /// The required argument is absent
+    MissingRequired(String),
+    /// The argument is given without its value
+    MissingValue(String),
+    /// The flag is given a value
+    UnexpectedValue(String),
+    /// The numeric argument is given a value that does not parse as its
+    /// type or overflows it
+    WrongType(String),
+    /// The argument is given a huge value
+    Huge(String),
+    /// The argument is given twice
+    Duplicate(String),
+    /// The argument is given a value outside of its choices
+    InvalidChoice(String),
+    /// An undeclared key is given
+    Unknown(String),
+    /// A token that is not a well-formed key is given
+    Malformed(String),
+}
+
+impl fmt::Display for FuzzKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuzzKind::Valid => write!(f, "valid"),
+            FuzzKind::MissingRequired(key) => write!(f, "missing required -{}", key),
+            FuzzKind::MissingValue(key) => write!(f, "missing value of -{}", key),
+            FuzzKind::UnexpectedValue(key) => write!(f, "value given to flag -{}", key),
+            FuzzKind::WrongType(key) => write!(f, "wrong type for -{}", key),
+            FuzzKind::Huge(key) => write!(f, "huge value of -{}", key),
+            FuzzKind::Duplicate(key) => write!(f, "duplicate -{}", key),
+            FuzzKind::InvalidChoice(key) => write!(f, "invalid choice for -{}", key),
+            FuzzKind::Unknown(key) => write!(f, "unknown -{}", key),
+            FuzzKind::Malformed(token) => write!(f, "malformed token '{}'", token),
+        }
+    }
+}
+
+/// A command line of a fuzzing corpus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzCase {
+    /// The arguments, excluding the program name
+    pub args: Vec<String>,
+    /// What the arguments exercise
+    pub kind: FuzzKind,
+}
+
+impl Spec {
+    /// Generates up to `n` distinct command lines that pass `validate`,
+    /// for documentation, smoke tests and fuzzing seeds. The first
+    /// only gives the required arguments, the second gives every
+    /// argument and the rest add one optional argument at a time,
+    /// cycling through its choices. Values are taken from the choices,
+    /// then the default value, then a sample of the value type, then
+    /// the value placeholder. Required operands are named after their
+    /// specification
+    ///
+    /// # Arguments
+    ///
+    /// `n`: The most command lines to generate
+    pub fn sample_invocations(&self, n: usize) -> Vec<Vec<String>> {
+        let optional: Vec<&str> = self
+            .args()
+            .iter()
+            .filter(|arg| !arg.is_required())
+            .map(ArgSpec::name)
+            .collect();
+        let choices = self
+            .args()
+            .iter()
+            .map(|arg| arg.get_choices().len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let candidates = 2 + optional.len() * choices;
+        let mut samples: Vec<Vec<String>> = Vec::new();
+        for i in 0..candidates {
+            if samples.len() == n {
+                break;
+            }
+            let included = |arg: &ArgSpec| {
+                arg.is_required()
+                    || i == 1
+                    || (i > 1 && optional[(i - 2) % optional.len()] == arg.name())
+            };
+            let round = i.saturating_sub(2).checked_div(optional.len()).unwrap_or(0);
+            let sample = self.command_line(
+                self.args()
+                    .iter()
+                    .filter(|arg| included(arg))
+                    .map(|arg| (arg, sample_value(arg, round))),
+            );
+            if !samples.contains(&sample) {
+                samples.push(sample);
+            }
+        }
+        samples
+    }
+
+    /// Generates a corpus of valid and boundary-pushing invalid command
+    /// lines to seed fuzzers and property tests of a program's main
+    /// function. The valid ones are those of `sample_invocations`, and
+    /// the others start from the one only giving required arguments and
+    /// leave out a required argument, give an argument without its value
+    /// or twice, give a flag a value, give numeric arguments values of
+    /// the wrong type, give huge values or, for arguments with choices,
+    /// values outside of them, or add undeclared keys and malformed tokens. Wrong
+    /// types and huge values pass `validate`, and are left for the
+    /// program to reject
+    pub fn fuzz_corpus(&self) -> Vec<FuzzCase> {
+        let mut corpus: Vec<FuzzCase> = self
+            .sample_invocations(usize::MAX)
+            .into_iter()
+            .map(|args| FuzzCase {
+                args,
+                kind: FuzzKind::Valid,
+            })
+            .collect();
+        let required = || {
+            self.args()
+                .iter()
+                .filter(|arg| arg.is_required())
+                .map(|arg| (arg, sample_value(arg, 0)))
+        };
+        let mut case = |kind: FuzzKind, args: Vec<String>| corpus.push(FuzzCase { args, kind });
+        for arg in self.args() {
+            let key = arg.name().to_string();
+            if arg.is_required() {
+                let args = self.command_line(required().filter(|(other, _)| other.name() != key));
+                case(FuzzKind::MissingRequired(key.clone()), args);
+            }
+            let mut args = self.command_line(required().chain([(arg, sample_value(arg, 0))]));
+            args.push(format!("-{}", key));
+            if !arg.is_flag() && !arg.is_optional_value() {
+                case(FuzzKind::MissingValue(key.clone()), args.clone());
+            }
+            case(FuzzKind::Duplicate(key.clone()), args);
+            if arg.is_flag() {
+                let mut args = self.command_line(required());
+                args.push(format!("-{}=yes", key));
+                case(FuzzKind::UnexpectedValue(key), args);
+                continue;
+            }
+            let with_value = |value: String| {
+                self.command_line(
+                    required()
+                        .filter(|(other, _)| other.name() != arg.name())
+                        .chain([(arg, value)]),
+                )
+            };
+            if arg.get_value_type().and_then(sample_of_type).is_some() {
+                for value in WRONG_TYPES {
+                    case(
+                        FuzzKind::WrongType(key.clone()),
+                        with_value(value.to_string()),
+                    );
+                }
+            }
+            if arg.get_choices().is_empty() {
+                case(FuzzKind::Huge(key), with_value("x".repeat(HUGE)));
+            } else {
+                let value = format!("not-{}", arg.get_choices().join("-"));
+                case(FuzzKind::InvalidChoice(key), with_value(value));
+            }
+        }
+        let base = self.command_line(required());
+        let mut unknown = String::from("unknown");
+        while self.get(&unknown).is_some() {
+            unknown.push('_');
+        }
+        let mut args = base.clone();
+        args.push(format!("-{}", unknown));
+        case(FuzzKind::Unknown(unknown), args);
+        for token in ["-", "--=", "-=x"] {
+            let mut args = base.clone();
+            args.push(token.to_string());
+            case(FuzzKind::Malformed(token.to_string()), args);
+        }
+        corpus
+    }
+
+    /// Builds a command line giving arguments with their values,
+    /// followed by the required operands
+    fn command_line<'a, I: IntoIterator<Item = (&'a ArgSpec, String)>>(
+        &self,
+        args: I,
+    ) -> Vec<String> {
+        let mut line = Vec::new();
+        for (arg, value) in args {
+            let key = format!("-{}", arg.name());
+            if arg.is_flag() {
+                line.push(key);
+            } else if arg.is_optional_value() || value.is_empty() || value.starts_with('-') {
+                line.push(format!("{}={}", key, value));
+            } else {
+                line.push(key);
+                line.push(value);
+            }
+            if let Some(terminator) = arg.get_terminator() {
+                line.push(terminator.to_string());
+            }
+        }
+        for positional in self.positionals() {
+            for _ in 0..positional.get_min_count() {
+                line.push(positional.name().to_string());
+            }
+        }
+        line
+    }
+}
+
+/// Returns the sample value of an argument, with the choice picked by
+/// the round of optional arguments
+fn sample_value(arg: &ArgSpec, round: usize) -> String {
+    match arg.get_choices() {
+        [] => arg
+            .get_default_value()
+            .or_else(|| arg.get_value_type().and_then(sample_of_type))
+            .unwrap_or_else(|| arg.get_value_name())
+            .to_string(),
+        choices => choices[round % choices.len()].to_string(),
+    }
+}
+
+/// Returns a sample value of a numeric or boolean type name
+fn sample_of_type(value_type: &str) -> Option<&'static str> {
+    match value_type {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" => Some("1"),
+        "f32" | "f64" => Some("1.5"),
+        "bool" => Some("true"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, FuzzKind, PositionalSpec, Spec};
+
+    #[test]
+    fn samples() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("host").required().value_name("HOST"))
+            .arg(ArgSpec::new("port").value_type("u16"))
+            .arg(ArgSpec::new("level").default_value("info"))
+            .arg(ArgSpec::new("color").choices(&["auto", "never"]))
+            .arg(ArgSpec::new("verbose").flag())
+            .positional(PositionalSpec::new("input").min_count(1));
+        let samples = spec.sample_invocations(100);
+        assert_eq!(samples[0], ["-host", "HOST", "input"]);
+        assert_eq!(
+            samples[1],
+            [
+                "-host", "HOST", "-port", "1", "-level", "info", "-color", "auto", "-verbose",
+                "input"
+            ]
+        );
+        assert!(samples.contains(&vec![
+            "-host".to_string(),
+            "HOST".to_string(),
+            "-color".to_string(),
+            "never".to_string(),
+            "input".to_string()
+        ]));
+        assert_eq!(samples.len(), 7);
+        for sample in &samples {
+            assert!(spec.parse_validated(sample).is_ok(), "{:?}", sample);
+        }
+        assert_eq!(spec.sample_invocations(2).len(), 2);
+        assert_eq!(Spec::new().sample_invocations(5), [Vec::<String>::new()]);
+    }
+
+    #[test]
+    fn fuzz_corpus() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("host").required())
+            .arg(ArgSpec::new("port").value_type("u16").max_occurrences(1))
+            .arg(ArgSpec::new("color").choices(&["auto", "never"]))
+            .arg(ArgSpec::new("verbose").flag());
+        let corpus = spec.fuzz_corpus();
+        let find = |kind: FuzzKind| corpus.iter().find(|case| case.kind == kind).unwrap();
+        assert_eq!(
+            find(FuzzKind::MissingRequired("host".to_string())).args,
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            find(FuzzKind::WrongType("port".to_string())).args,
+            ["-host", "value", "-port", "abc"]
+        );
+        assert_eq!(
+            corpus
+                .iter()
+                .filter(|case| case.kind == FuzzKind::WrongType("port".to_string()))
+                .nth(1)
+                .unwrap()
+                .args,
+            ["-host", "value", "-port=-1"]
+        );
+        assert_eq!(
+            find(FuzzKind::UnexpectedValue("verbose".to_string())).args,
+            ["-host", "value", "-verbose=yes"]
+        );
+        assert_eq!(
+            find(FuzzKind::Huge("host".to_string())).args[1].len(),
+            64 * 1024
+        );
+        for case in &corpus {
+            let valid = spec.parse_validated(&case.args).is_ok();
+            match &case.kind {
+                FuzzKind::Valid | FuzzKind::WrongType(_) | FuzzKind::Huge(_) => {
+                    assert!(valid, "{}", case.kind)
+                }
+                FuzzKind::Duplicate(key) if key != "port" => {}
+                _ => assert!(!valid, "{}: {:?}", case.kind, case.args),
+            }
+        }
+        assert_eq!(
+            FuzzKind::MissingValue("port".to_string()).to_string(),
+            "missing value of -port"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::Arguments;
+
+/// Characters with a special meaning to POSIX shells
+const SHELL_METACHARACTERS: &str = "|&;<>()$`\\\"'*?[]{}~!#";
+
+/// Checks applied to untrusted values by `Arguments::get_sanitized`.
+/// Every builder method is a `const fn`, like `ArgSpec`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sanitizer {
+    control: bool,
+    traversal: bool,
+    shell: bool,
+}
+
+impl Sanitizer {
+    /// Creates a sanitizer that accepts every value
+    pub const fn new() -> Sanitizer {
+        Sanitizer {
+            control: false,
+            traversal: false,
+            shell: false,
+        }
+    }
+
+    /// Creates a sanitizer applying every built-in check
+    pub const fn strict() -> Sanitizer {
+        Sanitizer::new()
+            .no_control()
+            .no_traversal()
+            .no_shell_metacharacters()
+    }
+
+    /// Rejects values containing control characters, such as line
+    /// breaks or terminal escape sequences
+    pub const fn no_control(mut self) -> Sanitizer {
+        self.control = true;
+        self
+    }
+
+    /// Rejects values containing a `..` path component, separated by
+    /// `/` or `\`, which could escape the intended directory
+    pub const fn no_traversal(mut self) -> Sanitizer {
+        self.traversal = true;
+        self
+    }
+
+    /// Rejects values containing characters with a special meaning to
+    /// shells, such as `;`, `|`, `$` or quotes
+    pub const fn no_shell_metacharacters(mut self) -> Sanitizer {
+        self.shell = true;
+        self
+    }
+
+    /// Checks a value, returning the first problem found
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key the value belongs to, for the error
+    ///
+    /// `value`: The value to check
+    pub fn check(&self, key: &str, value: &str) -> Result<(), SanitizeError> {
+        if self.control && value.chars().any(char::is_control) {
+            return Err(SanitizeError::ControlCharacter(key.to_string()));
+        }
+        if self.traversal && value.split(['/', '\\']).any(|part| part == "..") {
+            return Err(SanitizeError::PathTraversal(key.to_string()));
+        }
+        if self.shell {
+            if let Some(c) = value.chars().find(|&c| SHELL_METACHARACTERS.contains(c)) {
+                return Err(SanitizeError::ShellMetacharacter {
+                    key: key.to_string(),
+                    character: c,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Sanitizer {
+    fn default() -> Sanitizer {
+        Sanitizer::new()
+    }
+}
+
+/// Error returned when a value fails a sanitizer check. The value
+/// itself is left out, so the error can be logged safely
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SanitizeError {
+    /// The value contains a control character
+    ControlCharacter(String),
+    /// The value contains a `..` path component
+    PathTraversal(String),
+    /// The value contains a shell metacharacter
+    ShellMetacharacter { key: String, character: char },
+}
+
+impl fmt::Display for SanitizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanitizeError::ControlCharacter(key) => {
+                write!(f, "value for -{} contains a control character", key)
+            }
+            SanitizeError::PathTraversal(key) => {
+                write!(f, "value for -{} contains a '..' path component", key)
+            }
+            SanitizeError::ShellMetacharacter { key, character } => write!(
+                f,
+                "value for -{} contains the shell metacharacter '{}'",
+                key, character
+            ),
+        }
+    }
+}
+
+impl Error for SanitizeError {}
+
+impl Arguments {
+    /// Gets the first value with the given key after checking it with
+    /// a sanitizer, for values taken from untrusted users. Returns
+    /// `None` when the key is absent or has no value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key to fetch
+    ///
+    /// `sanitizer`: The checks the value must pass
+    pub fn get_sanitized(
+        &self,
+        key: &str,
+        sanitizer: Sanitizer,
+    ) -> Result<Option<&str>, SanitizeError> {
+        match self.get(key).flatten() {
+            Some(value) => sanitizer.check(key, value).map(|_| Some(value)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SanitizeError, Sanitizer};
+    use crate::Arguments;
+
+    #[test]
+    fn sanitizers() {
+        let args = Arguments::parse(&[
+            "-name",
+            "report.txt",
+            "-path",
+            "logs/../../etc/passwd",
+            "-cmd",
+            "ls; rm",
+            "-title",
+            "a\u{1b}[2J",
+        ]);
+        let strict = Sanitizer::strict();
+        assert_eq!(args.get_sanitized("name", strict), Ok(Some("report.txt")));
+        assert_eq!(args.get_sanitized("missing", strict), Ok(None));
+        assert_eq!(
+            args.get_sanitized("path", strict),
+            Err(SanitizeError::PathTraversal("path".to_string()))
+        );
+        assert_eq!(
+            args.get_sanitized("cmd", strict).unwrap_err().to_string(),
+            "value for -cmd contains the shell metacharacter ';'"
+        );
+        assert_eq!(
+            args.get_sanitized("title", Sanitizer::new().no_control()),
+            Err(SanitizeError::ControlCharacter("title".to_string()))
+        );
+        assert_eq!(
+            args.get_sanitized("cmd", Sanitizer::new()),
+            Ok(Some("ls; rm"))
+        );
+    }
+}
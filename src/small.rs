@@ -0,0 +1,144 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// The longest string stored without a heap allocation, keeping
+/// `SmallStr` the size of a `String`
+const INLINE: usize = 22;
+
+/// An immutable string stored inline when it is at most `INLINE`
+/// bytes long, so the short keys of typical flags are stored without
+/// a heap allocation. Longer strings are boxed
+#[derive(Clone)]
+pub(crate) enum SmallStr {
+    Inline { len: u8, buf: [u8; INLINE] },
+    Heap(Box<str>),
+}
+
+impl SmallStr {
+    /// Returns the string
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            SmallStr::Inline { len, buf } => {
+                // SAFETY: `From<&str>` copies the first `len` bytes of a
+                // `str` and the buffer is never modified afterwards, so
+                // they are valid UTF-8
+                unsafe { std::str::from_utf8_unchecked(&buf[..*len as usize]) }
+            }
+            SmallStr::Heap(text) => text,
+        }
+    }
+
+    /// Checks whether the string is stored without a heap allocation
+    #[cfg(test)]
+    fn is_inline(&self) -> bool {
+        matches!(self, SmallStr::Inline { .. })
+    }
+}
+
+impl From<&str> for SmallStr {
+    fn from(text: &str) -> SmallStr {
+        if text.len() > INLINE {
+            return SmallStr::Heap(text.into());
+        }
+        let mut buf = [0; INLINE];
+        buf[..text.len()].copy_from_slice(text.as_bytes());
+        SmallStr::Inline {
+            len: text.len() as u8,
+            buf,
+        }
+    }
+}
+
+impl From<String> for SmallStr {
+    fn from(text: String) -> SmallStr {
+        if text.len() > INLINE {
+            SmallStr::Heap(text.into_boxed_str())
+        } else {
+            SmallStr::from(text.as_str())
+        }
+    }
+}
+
+impl Deref for SmallStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmallStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmallStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+// hashing and comparisons follow `str` so lookups can borrow a `&str`
+impl Hash for SmallStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq for SmallStr {
+    fn eq(&self, other: &SmallStr) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallStr {}
+
+impl PartialOrd for SmallStr {
+    fn partial_cmp(&self, other: &SmallStr) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmallStr {
+    fn cmp(&self, other: &SmallStr) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl fmt::Debug for SmallStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SmallStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SmallStr, INLINE};
+
+    #[test]
+    fn inline() {
+        assert_eq!(
+            std::mem::size_of::<SmallStr>(),
+            std::mem::size_of::<String>()
+        );
+        let short = SmallStr::from("verbose");
+        assert!(short.is_inline());
+        assert_eq!(short.as_str(), "verbose");
+        let edge = "é".repeat(INLINE / 2);
+        assert!(SmallStr::from(edge.as_str()).is_inline());
+        let long = SmallStr::from("x".repeat(INLINE + 1));
+        assert!(!long.is_inline());
+        assert_eq!(long.len(), INLINE + 1);
+        assert!(SmallStr::from("a") < SmallStr::from("b"));
+    }
+}
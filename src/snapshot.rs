@@ -0,0 +1,137 @@
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::Arguments;
+
+/// A shared, immutable view of parsed arguments. Cloning it only bumps
+/// a reference count, so it can be handed to many threads or tasks, and
+/// it dereferences to `Arguments` for the whole read API
+#[derive(Clone)]
+pub struct ArcArguments {
+    inner: Arc<Arguments>,
+}
+
+impl Deref for ArcArguments {
+    type Target = Arguments;
+
+    fn deref(&self) -> &Arguments {
+        &self.inner
+    }
+}
+
+impl From<Arguments> for ArcArguments {
+    fn from(args: Arguments) -> ArcArguments {
+        ArcArguments {
+            inner: Arc::new(args),
+        }
+    }
+}
+
+impl Arguments {
+    /// Freezes the arguments into a snapshot that can be shared across
+    /// threads without copying the parsed values
+    pub fn snapshot(self) -> ArcArguments {
+        ArcArguments::from(self)
+    }
+}
+
+/// A swappable cell holding the current arguments of a long-running
+/// program, for reloading them without restarting. Every swap bumps a
+/// generation counter, so components can cheaply check whether the
+/// arguments changed since they last loaded them
+pub struct ArgsCell {
+    current: RwLock<(ArcArguments, u64)>,
+    generation: AtomicU64,
+}
+
+impl ArgsCell {
+    /// Creates a cell holding the initial arguments at generation 0
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The initial arguments
+    pub fn new<A: Into<ArcArguments>>(args: A) -> ArgsCell {
+        ArgsCell {
+            current: RwLock::new((args.into(), 0)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns a snapshot of the current arguments
+    pub fn load(&self) -> ArcArguments {
+        self.load_with_generation().0
+    }
+
+    /// Returns a snapshot of the current arguments along with the
+    /// generation they were stored at, read together so they always
+    /// belong to the same swap
+    pub fn load_with_generation(&self) -> (ArcArguments, u64) {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        (current.0.clone(), current.1)
+    }
+
+    /// Replaces the current arguments, returning the new generation.
+    /// Snapshots loaded earlier keep the arguments they were loaded with
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The new arguments
+    pub fn store<A: Into<ArcArguments>>(&self, args: A) -> u64 {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let generation = current.1 + 1;
+        *current = (args.into(), generation);
+        self.generation.store(generation, Ordering::Release);
+        generation
+    }
+
+    /// Returns the generation of the current arguments without locking
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Checks whether the arguments were swapped after the given
+    /// generation without locking
+    ///
+    /// # Arguments
+    ///
+    /// `generation`: The generation last seen by the caller
+    pub fn changed_since(&self, generation: u64) -> bool {
+        self.generation() != generation
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgsCell, Arguments};
+    use std::thread;
+
+    #[test]
+    fn shared_across_threads() {
+        let args = Arguments::parse(&["-port", "80", "-verbose"]).snapshot();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let args = args.clone();
+                thread::spawn(move || args.get("port").unwrap().unwrap().to_string())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "80");
+        }
+        assert!(args.contains("verbose"));
+        assert_eq!(args.len(), 2);
+    }
+
+    #[test]
+    fn cell_generations() {
+        let cell = ArgsCell::new(Arguments::parse(&["-port", "80"]));
+        let (args, seen) = cell.load_with_generation();
+        assert_eq!(seen, 0);
+        assert!(!cell.changed_since(seen));
+        assert_eq!(cell.store(Arguments::parse(&["-port", "81"])), 1);
+        assert!(cell.changed_since(seen));
+        assert_eq!(args.get("port").unwrap(), Some("80"));
+        assert_eq!(cell.load().get("port").unwrap(), Some("81"));
+        assert_eq!(cell.generation(), 1);
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where the value of an argument came from
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The command line
+    CommandLine,
+    /// An environment variable, by name
+    Env(String),
+    /// A configuration file, by path
+    File(PathBuf),
+    /// A default value declared in the specification
+    Default,
+    /// A front-end embedded in the application, such as a script or
+    /// module, by name
+    Origin(String),
+}
+
+/// The kind of a source, without the variable, path or name it carries
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    /// The command line
+    CommandLine,
+    /// Environment variables
+    Env,
+    /// Configuration files
+    File,
+    /// Default values declared in the specification
+    Default,
+    /// Front-ends embedded in the application
+    Origin,
+}
+
+impl Source {
+    /// Returns the kind of the source
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            Source::CommandLine => SourceKind::CommandLine,
+            Source::Env(_) => SourceKind::Env,
+            Source::File(_) => SourceKind::File,
+            Source::Default => SourceKind::Default,
+            Source::Origin(_) => SourceKind::Origin,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::CommandLine => write!(f, "command line"),
+            Source::Env(var) => write!(f, "environment variable {}", var),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Default => write!(f, "default"),
+            Source::Origin(origin) => write!(f, "origin {}", origin),
+        }
+    }
+}
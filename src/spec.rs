@@ -0,0 +1,1318 @@
+use std::borrow::Cow;
+
+use crate::export::env_segment;
+use crate::fuzzy::{levenshtein, max_distance};
+use crate::{Arguments, Behavior, Environment, Limits, Stage, Subcommand};
+
+/// How the values of an argument from a lower-precedence layer combine
+/// with values already present from higher-precedence layers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Lower-precedence values are dropped when the argument is already
+    /// present, so the most important layer wins outright
+    Replace,
+    /// Lower-precedence values are appended after those already present,
+    /// so every layer contributes
+    Append,
+}
+
+/// Specification of a single argument. Every builder method is a
+/// `const fn`, so specifications can be declared in `static` arrays
+#[derive(Clone, Copy, Debug)]
+pub struct ArgSpec {
+    name: &'static str,
+    description: &'static str,
+    global: bool,
+    flag: bool,
+    default_value: Option<&'static str>,
+    default_with: Option<fn(&Environment) -> Option<&'static str>>,
+    ci_default: Option<&'static str>,
+    env: Option<&'static str>,
+    merge_policy: MergePolicy,
+    sticky: bool,
+    terminator: Option<&'static str>,
+    optional_value: bool,
+    completer: Option<fn(&str) -> Vec<String>>,
+    pipeline: &'static [Stage],
+    value_type: Option<&'static str>,
+    value_name: &'static str,
+    required: bool,
+    max_occurrences: Option<usize>,
+    choices: &'static [&'static str],
+    glued_value: bool,
+    deprecated_since: Option<&'static str>,
+    removed_in: Option<&'static str>,
+    experimental: bool,
+    aliases: &'static [&'static str],
+    value_delimiter: Option<char>,
+    unique: bool,
+    multi_value: bool,
+    requires: &'static [&'static str],
+    conflicts_with: &'static [&'static str],
+}
+
+impl ArgSpec {
+    /// Creates a new argument specification
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The key of the argument, without the leading `-`
+    pub const fn new(name: &'static str) -> ArgSpec {
+        ArgSpec {
+            name,
+            description: "",
+            global: false,
+            flag: false,
+            default_value: None,
+            default_with: None,
+            ci_default: None,
+            env: None,
+            merge_policy: MergePolicy::Replace,
+            sticky: false,
+            terminator: None,
+            optional_value: false,
+            completer: None,
+            pipeline: &[],
+            value_type: None,
+            value_name: "value",
+            required: false,
+            max_occurrences: None,
+            choices: &[],
+            glued_value: false,
+            deprecated_since: None,
+            removed_in: None,
+            experimental: false,
+            aliases: &[],
+            value_delimiter: None,
+            unique: false,
+            multi_value: false,
+            requires: &[],
+            conflicts_with: &[],
+        }
+    }
+
+    /// Sets the description shown in help output
+    ///
+    /// # Arguments
+    ///
+    /// `description`: The description of the argument
+    pub const fn description(mut self, description: &'static str) -> ArgSpec {
+        self.description = description;
+        self
+    }
+
+    /// Marks the argument as global, so it is inherited by every
+    /// subcommand of the specification it is declared in
+    pub const fn global(mut self) -> ArgSpec {
+        self.global = true;
+        self
+    }
+
+    /// Marks the argument as a flag, which never takes a value, so the
+    /// argument following it is not consumed as its value
+    pub const fn flag(mut self) -> ArgSpec {
+        self.flag = true;
+        self
+    }
+
+    /// Sets the value used when the argument is absent from the command
+    /// line and its environment variable
+    ///
+    /// # Arguments
+    ///
+    /// `value`: The default value
+    pub const fn default_value(mut self, value: &'static str) -> ArgSpec {
+        self.default_value = Some(value);
+        self
+    }
+
+    /// Sets a callback picking the default value from the environment
+    /// the program runs under, such as `never` for `-color` in
+    /// continuous integration. It is used by `Spec::default_in` and
+    /// `Spec::apply_fallbacks_in`, falling back to the default value
+    /// when it returns `None`
+    ///
+    /// # Arguments
+    ///
+    /// `default`: Picks the default value from the environment
+    pub const fn default_with(
+        mut self,
+        default: fn(&Environment) -> Option<&'static str>,
+    ) -> ArgSpec {
+        self.default_with = Some(default);
+        self
+    }
+
+    /// Sets the default value used by the continuous integration
+    /// profile, such as `never` for `-color`, `0` for a `-prompt` flag
+    /// or `json` for `-format`. The profile is active when the
+    /// environment is detected as continuous integration or `-ci` is
+    /// given, and takes precedence over the other defaults in
+    /// `Spec::default_in` and `Spec::apply_fallbacks_in`
+    ///
+    /// # Arguments
+    ///
+    /// `value`: The default value under continuous integration
+    pub const fn ci_default(mut self, value: &'static str) -> ArgSpec {
+        self.ci_default = Some(value);
+        self
+    }
+
+    /// Sets the environment variable read when the argument is absent
+    /// from the command line
+    ///
+    /// # Arguments
+    ///
+    /// `var`: The name of the environment variable
+    pub const fn env(mut self, var: &'static str) -> ArgSpec {
+        self.env = Some(var);
+        self
+    }
+
+    /// Sets how values from lower-precedence layers combine with those
+    /// already present when merged through `Spec::merge`. Arguments
+    /// replace lower-precedence values by default
+    ///
+    /// # Arguments
+    ///
+    /// `policy`: The merge policy
+    pub const fn merge_policy(mut self, policy: MergePolicy) -> ArgSpec {
+        self.merge_policy = policy;
+        self
+    }
+
+    /// Marks the argument as sticky, so `Arguments::check_compatible`
+    /// rejects a run that changes it from a previously persisted run,
+    /// as for a database path that must not change once data is written
+    pub const fn sticky(mut self) -> ArgSpec {
+        self.sticky = true;
+        self
+    }
+
+    /// Makes the argument capture every following token verbatim until
+    /// the terminator or the end of the arguments when parsed through
+    /// `Spec::parse`, like `find -exec ... ;`. The terminator itself is
+    /// consumed, and the tokens are available from
+    /// `Arguments::get_captured`
+    ///
+    /// # Arguments
+    ///
+    /// `terminator`: The token that ends the capture, such as `;`
+    pub const fn capture_until(mut self, terminator: &'static str) -> ArgSpec {
+        self.terminator = Some(terminator);
+        self
+    }
+
+    /// Makes the value of the argument optional and only accepted when
+    /// attached, as in `-color=always`, when parsed through
+    /// `Spec::parse`. A bare `-color` never consumes the following
+    /// token, so it cannot be mistaken for a positional, matching GNU
+    /// conventions for options with optional arguments
+    pub const fn optional_value(mut self) -> ArgSpec {
+        self.optional_value = true;
+        self
+    }
+
+    /// Lets a one-character argument taking a value have the value
+    /// glued to it, as in `-ooutput.bin` or `-O2`, when parsed through
+    /// `Spec::parse`. The value may still be attached with `=` or given
+    /// as the next token. Keys that are declared themselves are never
+    /// split, so `-output` stays `output` when it is declared. Clustered
+    /// specifications glue values to every one-character argument
+    /// taking one
+    pub const fn glued_value(mut self) -> ArgSpec {
+        self.glued_value = true;
+        self
+    }
+
+    /// Sets a callback listing the values of the argument that complete
+    /// what has been typed so far, invoked at completion time by
+    /// `Spec::complete`, so candidates such as profiles on disk or git
+    /// branches are always current
+    ///
+    /// # Arguments
+    ///
+    /// `completer`: Returns the candidates for a typed prefix
+    pub const fn complete_with(mut self, completer: fn(&str) -> Vec<String>) -> ArgSpec {
+        self.completer = Some(completer);
+        self
+    }
+
+    /// Sets the stages every value of the argument is processed by, in
+    /// order, when read through `Spec::get_processed`, such as trimming,
+    /// expanding environment variables and validating before the value
+    /// is converted to its type
+    ///
+    /// # Arguments
+    ///
+    /// `stages`: The processing stages
+    pub const fn pipeline(mut self, stages: &'static [Stage]) -> ArgSpec {
+        self.pipeline = stages;
+        self
+    }
+
+    /// Names the type of the value of the argument, such as `u16` or
+    /// `PathBuf`, so exported specifications can be compared for drift
+    /// between related programs
+    ///
+    /// # Arguments
+    ///
+    /// `value_type`: The name of the type
+    pub const fn value_type(mut self, value_type: &'static str) -> ArgSpec {
+        self.value_type = Some(value_type);
+        self
+    }
+
+    /// Sets the placeholder shown for the value of the argument in help
+    /// output, as in `-port <PORT>`, which is `value` by default
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder, without angle brackets
+    pub const fn value_name(mut self, name: &'static str) -> ArgSpec {
+        self.value_name = name;
+        self
+    }
+
+    /// Marks the argument as required, so `Spec::validate` rejects
+    /// arguments missing it
+    pub const fn required(mut self) -> ArgSpec {
+        self.required = true;
+        self
+    }
+
+    /// Sets how many times the argument may appear, so `Spec::validate`
+    /// rejects arguments repeating it more often. Arguments may appear
+    /// any number of times by default
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The most times the argument may appear
+    pub const fn max_occurrences(mut self, max: usize) -> ArgSpec {
+        self.max_occurrences = Some(max);
+        self
+    }
+
+    /// Marks the argument as deprecated from a version of the program
+    /// on, so `Spec::deprecations` warns about it once the version of
+    /// the specification reaches it
+    ///
+    /// # Arguments
+    ///
+    /// `version`: The version the argument is deprecated since
+    pub const fn deprecated_since(mut self, version: &'static str) -> ArgSpec {
+        self.deprecated_since = Some(version);
+        self
+    }
+
+    /// Announces the version of the program removing the argument, from
+    /// which `Spec::validate` rejects it and help output and completions
+    /// hide it
+    ///
+    /// # Arguments
+    ///
+    /// `version`: The version the argument is removed in
+    pub const fn removed_in(mut self, version: &'static str) -> ArgSpec {
+        self.removed_in = Some(version);
+        self
+    }
+
+    /// Sets alternative keys of the argument, such as `v` for `verbose`.
+    /// Parsing through `Spec::parse` stores values given under an alias
+    /// under the name of the argument, and `Arguments::contains`, `get`
+    /// and `get_vec` accept either
+    ///
+    /// # Arguments
+    ///
+    /// `aliases`: The alternative keys, without the leading `-`
+    pub const fn aliases(mut self, aliases: &'static [&'static str]) -> ArgSpec {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Lets the argument take every following token that is not a key
+    /// as a value when parsed through `Spec::parse`, so `-files a b c`
+    /// gives three values retrievable with `Arguments::get_vec`. The
+    /// values end at the next key or at `--`, so operands following
+    /// them have to be given after `--`. A value attached with `=` is
+    /// the only one
+    pub const fn multi_value(mut self) -> ArgSpec {
+        self.multi_value = true;
+        self
+    }
+
+    /// Sets the arguments that must be given whenever this argument is,
+    /// such as `cert-file` for `key-file`, as checked by
+    /// `Spec::validate`
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The keys of the required arguments, without the leading `-`
+    pub const fn requires(mut self, keys: &'static [&'static str]) -> ArgSpec {
+        self.requires = keys;
+        self
+    }
+
+    /// Sets the arguments that cannot be given together with this
+    /// argument, such as `verbose` for `quiet`, as checked by
+    /// `Spec::validate`. A flag turned off by its negation, as in
+    /// `-quiet -no-quiet`, is not given
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The keys of the conflicting arguments, without the leading `-`
+    pub const fn conflicts_with(mut self, keys: &'static [&'static str]) -> ArgSpec {
+        self.conflicts_with = keys;
+        self
+    }
+
+    /// Splits each value of the argument at a delimiter when parsed
+    /// through `Spec::parse`, so `-features a,b,c` gives three values
+    /// retrievable with `Arguments::get_vec`
+    ///
+    /// # Arguments
+    ///
+    /// `delimiter`: The character separating the values, such as `,`
+    pub const fn value_delimiter(mut self, delimiter: char) -> ArgSpec {
+        self.value_delimiter = Some(delimiter);
+        self
+    }
+
+    /// Keeps only the first occurrence of each value of the argument,
+    /// as wanted for search paths given with `-I` or `-L`. Values
+    /// repeated on the command line are dropped by `Spec::parse`, and
+    /// values that only become equal through the pipeline, such as
+    /// paths under `Stage::Canonicalize`, by `Spec::get_processed_vec`
+    pub const fn unique(mut self) -> ArgSpec {
+        self.unique = true;
+        self
+    }
+
+    /// Marks the argument as experimental, so `Spec::validate` rejects it
+    /// unless unlocked by the `-unstable` switch or the variable of
+    /// `Spec::unstable_var`, for staged rollouts
+    pub const fn experimental(mut self) -> ArgSpec {
+        self.experimental = true;
+        self
+    }
+
+    /// Restricts the values of the argument to a set of choices, so
+    /// `Spec::validate` rejects any other value
+    ///
+    /// # Arguments
+    ///
+    /// `choices`: The accepted values
+    pub const fn choices(mut self, choices: &'static [&'static str]) -> ArgSpec {
+        self.choices = choices;
+        self
+    }
+
+    /// Returns the key of the argument
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the description of the argument
+    pub const fn get_description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Checks whether the argument is inherited by subcommands
+    pub const fn is_global(&self) -> bool {
+        self.global
+    }
+
+    /// Checks whether the argument never takes a value
+    pub const fn is_flag(&self) -> bool {
+        self.flag
+    }
+
+    /// Returns the default value of the argument
+    pub const fn get_default_value(&self) -> Option<&'static str> {
+        self.default_value
+    }
+
+    /// Returns the callback picking the default value from the
+    /// environment
+    pub const fn get_default_with(&self) -> Option<fn(&Environment) -> Option<&'static str>> {
+        self.default_with
+    }
+
+    /// Returns the default value used by the continuous integration
+    /// profile
+    pub const fn get_ci_default(&self) -> Option<&'static str> {
+        self.ci_default
+    }
+
+    /// Returns the environment variable the argument falls back to
+    pub const fn get_env(&self) -> Option<&'static str> {
+        self.env
+    }
+
+    /// Returns how values from lower-precedence layers are merged
+    pub const fn get_merge_policy(&self) -> MergePolicy {
+        self.merge_policy
+    }
+
+    /// Checks whether the argument must not change between runs
+    pub const fn is_sticky(&self) -> bool {
+        self.sticky
+    }
+
+    /// Returns the token that ends the capture of the argument
+    pub const fn get_terminator(&self) -> Option<&'static str> {
+        self.terminator
+    }
+
+    /// Checks whether the value of the argument is optional and only
+    /// accepted when attached
+    pub const fn is_optional_value(&self) -> bool {
+        self.optional_value
+    }
+
+    /// Returns whether the value of the argument may be glued to it
+    pub const fn is_glued_value(&self) -> bool {
+        self.glued_value
+    }
+
+    /// Returns the callback completing values of the argument
+    pub const fn get_completer(&self) -> Option<fn(&str) -> Vec<String>> {
+        self.completer
+    }
+
+    /// Returns the stages values of the argument are processed by
+    pub const fn get_pipeline(&self) -> &'static [Stage] {
+        self.pipeline
+    }
+
+    /// Returns the name of the type of the value of the argument
+    pub const fn get_value_type(&self) -> Option<&'static str> {
+        self.value_type
+    }
+
+    /// Returns the placeholder shown for the value of the argument
+    pub const fn get_value_name(&self) -> &'static str {
+        self.value_name
+    }
+
+    /// Returns whether the argument is required
+    pub const fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns how many times the argument may appear
+    pub const fn get_max_occurrences(&self) -> Option<usize> {
+        self.max_occurrences
+    }
+
+    /// Returns the version the argument is deprecated since
+    pub const fn get_deprecated_since(&self) -> Option<&'static str> {
+        self.deprecated_since
+    }
+
+    /// Returns the version the argument is removed in
+    pub const fn get_removed_in(&self) -> Option<&'static str> {
+        self.removed_in
+    }
+
+    /// Returns the alternative keys of the argument
+    pub const fn get_aliases(&self) -> &'static [&'static str] {
+        self.aliases
+    }
+
+    /// Returns the character values of the argument are split at
+    pub const fn get_value_delimiter(&self) -> Option<char> {
+        self.value_delimiter
+    }
+
+    /// Checks whether the argument takes every following value
+    pub const fn is_multi_value(&self) -> bool {
+        self.multi_value
+    }
+
+    /// Returns the arguments that must be given with this argument
+    pub const fn get_requires(&self) -> &'static [&'static str] {
+        self.requires
+    }
+
+    /// Returns the arguments that cannot be given with this argument
+    pub const fn get_conflicts_with(&self) -> &'static [&'static str] {
+        self.conflicts_with
+    }
+
+    /// Checks whether only the first occurrence of each value is kept
+    pub const fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns whether the argument is experimental
+    pub const fn is_experimental(&self) -> bool {
+        self.experimental
+    }
+
+    /// Returns the accepted values of the argument, which are empty when
+    /// any value is accepted
+    pub const fn get_choices(&self) -> &'static [&'static str] {
+        self.choices
+    }
+
+    /// Scores how well the argument matches a lowercase search query,
+    /// where lower is better and `None` is no match
+    fn search_score(&self, query: &str) -> Option<usize> {
+        let name = self.name.to_lowercase();
+        if name == query {
+            return Some(0);
+        }
+        if name.contains(query) {
+            return Some(1);
+        }
+        if self.description.to_lowercase().contains(query) {
+            return Some(2);
+        }
+        let distance = levenshtein(&name, query);
+        if distance <= max_distance(query) {
+            return Some(2 + distance);
+        }
+        None
+    }
+}
+
+/// Specification of a positional operand, identified by the
+/// placeholder it is shown as in usage output, such as `FILE`. Operands
+/// are matched to positionals in declaration order and retrieved by
+/// placeholder with `Arguments::get_positional`, and every builder
+/// method is a `const fn`, like `ArgSpec`
+#[derive(Clone, Copy, Debug)]
+pub struct PositionalSpec {
+    name: &'static str,
+    description: &'static str,
+    conflicts: &'static [&'static str],
+    greedy: bool,
+    min_count: usize,
+    max_count: Option<usize>,
+}
+
+impl PositionalSpec {
+    /// Creates a new operand specification
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The placeholder of the operand, such as `FILE`
+    pub const fn new(name: &'static str) -> PositionalSpec {
+        PositionalSpec {
+            name,
+            description: "",
+            conflicts: &[],
+            greedy: false,
+            min_count: 0,
+            max_count: None,
+        }
+    }
+
+    /// Sets the description shown in help output
+    ///
+    /// # Arguments
+    ///
+    /// `description`: The description of the operand
+    pub const fn description(mut self, description: &'static str) -> PositionalSpec {
+        self.description = description;
+        self
+    }
+
+    /// Declares arguments that cannot be combined with the operand, as
+    /// when `-stdin` replaces a `FILE`. `Spec::check_operands` rejects
+    /// arguments giving both
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The conflicting keys, without the leading `-`
+    pub const fn conflicts_with(mut self, keys: &'static [&'static str]) -> PositionalSpec {
+        self.conflicts = keys;
+        self
+    }
+
+    /// Makes the operand take every remaining positional, as for a
+    /// list of `OUTPUTS`, so it is usually declared last. Operands
+    /// declared after it never receive any
+    pub const fn greedy(mut self) -> PositionalSpec {
+        self.greedy = true;
+        self
+    }
+
+    /// Sets how many positionals the operand needs, such as at least
+    /// one `FILE`, checked by `Spec::check_operands`. A minimum of one
+    /// makes a single operand required
+    ///
+    /// # Arguments
+    ///
+    /// `min`: The least number of positionals
+    pub const fn min_count(mut self, min: usize) -> PositionalSpec {
+        self.min_count = min;
+        self
+    }
+
+    /// Sets how many positionals a greedy operand accepts at most,
+    /// checked by `Spec::check_operands`
+    ///
+    /// # Arguments
+    ///
+    /// `max`: The greatest number of positionals
+    pub const fn max_count(mut self, max: usize) -> PositionalSpec {
+        self.max_count = Some(max);
+        self
+    }
+
+    /// Returns the placeholder of the operand
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the description of the operand
+    pub const fn get_description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Returns the keys that cannot be combined with the operand
+    pub const fn get_conflicts(&self) -> &'static [&'static str] {
+        self.conflicts
+    }
+
+    /// Checks whether the operand takes every remaining positional
+    pub const fn is_greedy(&self) -> bool {
+        self.greedy
+    }
+
+    /// Returns the least number of positionals the operand needs
+    pub const fn get_min_count(&self) -> usize {
+        self.min_count
+    }
+
+    /// Returns the greatest number of positionals the operand accepts
+    pub const fn get_max_count(&self) -> Option<usize> {
+        self.max_count
+    }
+}
+
+/// Specification of the arguments accepted by a program
+#[derive(Clone, Debug)]
+pub struct Spec {
+    program: Option<Cow<'static, str>>,
+    version: Option<Cow<'static, str>>,
+    description: Cow<'static, str>,
+    args: Cow<'static, [ArgSpec]>,
+    positionals: Vec<PositionalSpec>,
+    examples: Vec<Cow<'static, str>>,
+    subcommands: Vec<Subcommand>,
+    default_subcommand: Option<Cow<'static, str>>,
+    lenient: bool,
+    posix: bool,
+    w_long_options: bool,
+    intermixed: bool,
+    clustered: bool,
+    trim_values: bool,
+    strip_quotes: bool,
+    raw_regions: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    escape_prefix: Option<Cow<'static, str>>,
+    limits: Limits,
+    behavior: Behavior,
+    timed: bool,
+    env_prefix: Option<Cow<'static, str>>,
+    unstable_env: Option<Cow<'static, str>>,
+}
+
+impl Default for Spec {
+    fn default() -> Spec {
+        Spec::new()
+    }
+}
+
+impl Spec {
+    /// Creates an empty specification
+    pub const fn new() -> Spec {
+        Spec::from_static(&[])
+    }
+
+    /// Creates a specification borrowing its arguments from a static
+    /// array, so it can itself be declared as a `static`. Adding more
+    /// arguments later copies the array
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The declared arguments
+    pub const fn from_static(args: &'static [ArgSpec]) -> Spec {
+        Spec {
+            program: None,
+            version: None,
+            description: Cow::Borrowed(""),
+            args: Cow::Borrowed(args),
+            positionals: Vec::new(),
+            examples: Vec::new(),
+            subcommands: Vec::new(),
+            default_subcommand: None,
+            lenient: false,
+            posix: false,
+            w_long_options: false,
+            intermixed: true,
+            clustered: false,
+            trim_values: false,
+            strip_quotes: false,
+            raw_regions: Vec::new(),
+            escape_prefix: None,
+            limits: Limits::new(),
+            behavior: Behavior::V1,
+            timed: false,
+            env_prefix: None,
+            unstable_env: None,
+        }
+    }
+
+    /// Sets the program name shown in usage lines
+    ///
+    /// # Arguments
+    ///
+    /// `program`: The name of the program
+    pub fn program<P: Into<Cow<'static, str>>>(mut self, program: P) -> Spec {
+        self.program = Some(program.into());
+        self
+    }
+
+    /// Sets the version of the program, which makes `help_for` answer
+    /// `-version` and heads the full help. `crate_version!` gives the
+    /// version of the calling crate
+    ///
+    /// # Arguments
+    ///
+    /// `version`: The version of the program
+    pub fn version<V: Into<Cow<'static, str>>>(mut self, version: V) -> Spec {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Sets the description shown at the top of help output
+    ///
+    /// # Arguments
+    ///
+    /// `description`: The description of the program
+    pub fn description<D: Into<Cow<'static, str>>>(mut self, description: D) -> Spec {
+        self.description = description.into();
+        self
+    }
+
+    /// Adds an argument to the specification
+    ///
+    /// # Arguments
+    ///
+    /// `arg`: The argument to add
+    pub fn arg(mut self, arg: ArgSpec) -> Spec {
+        self.args.to_mut().push(arg);
+        self
+    }
+
+    /// Adds every argument declared in another specification, such as a
+    /// group of options shared by several commands
+    ///
+    /// # Arguments
+    ///
+    /// `spec`: The specification to copy the arguments of
+    pub fn args_from(mut self, spec: &Spec) -> Spec {
+        self.args.to_mut().extend_from_slice(spec.args());
+        self
+    }
+
+    /// Adds a positional operand to the specification, after those
+    /// already declared
+    ///
+    /// # Arguments
+    ///
+    /// `positional`: The operand to add
+    pub fn positional(mut self, positional: PositionalSpec) -> Spec {
+        self.positionals.push(positional);
+        self
+    }
+
+    /// Adds an example invocation shown in help output
+    ///
+    /// # Arguments
+    ///
+    /// `example`: The example command line
+    pub fn example<E: Into<Cow<'static, str>>>(mut self, example: E) -> Spec {
+        self.examples.push(example.into());
+        self
+    }
+
+    /// Adds a subcommand to the specification
+    ///
+    /// # Arguments
+    ///
+    /// `subcommand`: The subcommand to add
+    pub fn subcommand(mut self, subcommand: Subcommand) -> Spec {
+        self.subcommands.push(subcommand);
+        self
+    }
+
+    /// Sets the subcommand chosen when the first argument is a flag
+    /// or there are no arguments at all
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The name of the default subcommand
+    pub fn default_subcommand<N: Into<Cow<'static, str>>>(mut self, name: N) -> Spec {
+        self.default_subcommand = Some(name.into());
+        self
+    }
+
+    /// Sets whether small typos are corrected instead of rejected. In
+    /// lenient mode, subcommand names and keys parsed through `parse`
+    /// that are one edit away from exactly one declared name are
+    /// corrected to it, with corrected keys listed by
+    /// `Arguments::corrections`. Lenient mode is off by default
+    ///
+    /// # Arguments
+    ///
+    /// `lenient`: Whether to correct typos
+    pub fn lenient(mut self, lenient: bool) -> Spec {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets whether arguments are parsed following POSIX utility
+    /// conventions. Options are single characters that may be grouped
+    /// as in `-ab`, an option that is not a flag takes the rest of its
+    /// token or the next token as its value even when it starts with
+    /// `-`, and options end at `--` or the first operand. POSIX mode is
+    /// off by default
+    ///
+    /// # Arguments
+    ///
+    /// `posix`: Whether to parse in POSIX mode
+    pub fn posix(mut self, posix: bool) -> Spec {
+        self.posix = posix;
+        self
+    }
+
+    /// Sets whether `-W name` and `-W name=value` are read as the long
+    /// option `name` in POSIX mode, as reserved for vendor extensions
+    ///
+    /// # Arguments
+    ///
+    /// `enabled`: Whether `-W` introduces a long option
+    pub fn w_long_options(mut self, enabled: bool) -> Spec {
+        self.w_long_options = enabled;
+        self
+    }
+
+    /// Sets whether keys are still recognized after the first
+    /// positional, like `argparse`'s `parse_intermixed_args`. When off,
+    /// every token from the first positional on is a positional, as
+    /// wrappers such as `time` or `env` need to pass a command through.
+    /// Intermixing is on by default, and POSIX mode never intermixes
+    ///
+    /// # Arguments
+    ///
+    /// `intermixed`: Whether keys may follow positionals
+    pub fn intermixed(mut self, intermixed: bool) -> Spec {
+        self.intermixed = intermixed;
+        self
+    }
+
+    /// Sets whether a single-dash token grouping declared one-character
+    /// arguments, as in `-xvf`, is expanded into them when parsed
+    /// through `Spec::parse`. Every grouped argument but the last must
+    /// be a flag, and the last takes its value as it would alone or glued
+    /// to it, as with getopt, so `-vofile.txt` is `-v -o file.txt`.
+    /// Declared keys are never expanded, so existing multi-character
+    /// keys keep working. Clustering is off by default
+    ///
+    /// # Arguments
+    ///
+    /// `clustered`: Whether grouped one-character arguments are expanded
+    pub fn clustered(mut self, clustered: bool) -> Spec {
+        self.clustered = clustered;
+        self
+    }
+
+    /// Sets whether leading and trailing whitespace is stripped from
+    /// values parsed through `Spec::parse`, as left by configuration
+    /// files or wrapping scripts. Delimited values are trimmed one by
+    /// one. Trimming is off by default
+    ///
+    /// # Arguments
+    ///
+    /// `trim`: Whether to trim values
+    pub fn trim_values(mut self, trim: bool) -> Spec {
+        self.trim_values = trim;
+        self
+    }
+
+    /// Sets whether a pair of matching `"` or `'` quotes enclosing a
+    /// value parsed through `Spec::parse` is stripped, as left by
+    /// Windows shells that do not remove them, so `-name "a b"` arriving
+    /// with its quotes gives `a b`. Only one pair is stripped, after
+    /// trimming when enabled. Stripping is off by default
+    ///
+    /// # Arguments
+    ///
+    /// `strip`: Whether to strip enclosing quotes
+    pub fn strip_quotes(mut self, strip: bool) -> Spec {
+        self.strip_quotes = strip;
+        self
+    }
+
+    /// Declares a pair of markers enclosing a raw region when parsed
+    /// through `Spec::parse`, such as `--begin-raw` and `--end-raw`. The
+    /// tokens between them are kept untouched in
+    /// `Arguments::raw_regions` rather than read as keys or values, for
+    /// embedding the syntax of another command. A region left open runs
+    /// to the end of the arguments
+    ///
+    /// # Arguments
+    ///
+    /// `begin`: The marker opening the region
+    ///
+    /// `end`: The marker closing the region
+    pub fn raw_region<B: Into<Cow<'static, str>>, E: Into<Cow<'static, str>>>(
+        mut self,
+        begin: B,
+        end: E,
+    ) -> Spec {
+        self.raw_regions.push((begin.into(), end.into()));
+        self
+    }
+
+    /// Sets a prefix that marks a token as a value when parsed through
+    /// `Spec::parse`, even when it starts with `-`. The prefix is
+    /// stripped, so with `\` as the prefix, `-pattern \-v` gives
+    /// `pattern` the value `-v` and a lone `\-file` is the positional
+    /// `-file`
+    ///
+    /// # Arguments
+    ///
+    /// `prefix`: The escape prefix, such as `\` or `--=`
+    pub fn escape_prefix<P: Into<Cow<'static, str>>>(mut self, prefix: P) -> Spec {
+        self.escape_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the limits enforced by `parse_limited` and
+    /// `expand_response_files`
+    ///
+    /// # Arguments
+    ///
+    /// `limits`: The limits on parse input
+    pub fn limits(mut self, limits: Limits) -> Spec {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the version of the parsing behavior followed by `parse`,
+    /// which is `Behavior::V1` by default. From `Behavior::V2` on, `--`
+    /// ends keys, with every following argument kept as a positional,
+    /// and from `Behavior::V3` on, undeclared keys take a following
+    /// negative number as their value
+    ///
+    /// # Arguments
+    ///
+    /// `behavior`: The version of the parsing behavior
+    pub fn behavior(mut self, behavior: Behavior) -> Spec {
+        self.behavior = behavior;
+        self
+    }
+
+    /// Enables timing of parsing, fallbacks and validation, summarized
+    /// by `Arguments::startup_report`, to find what slows startup down.
+    /// Timing is off by default
+    ///
+    /// # Arguments
+    ///
+    /// `timed`: Whether to record timings
+    pub fn timed(mut self, timed: bool) -> Spec {
+        self.timed = timed;
+        self
+    }
+
+    /// Makes every declared argument without its own environment
+    /// variable fall back to one named by the prefix and the key, so
+    /// `-port` reads `MYAPP_PORT` with the prefix `MYAPP`. Keys are
+    /// uppercased with characters other than letters and digits
+    /// replaced by `_`
+    ///
+    /// # Arguments
+    ///
+    /// `prefix`: The prefix of every variable name
+    pub fn env_prefix<P: Into<Cow<'static, str>>>(mut self, prefix: P) -> Spec {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the environment variable unlocking experimental arguments
+    /// when set to a value such as `1`, instead of `PREFIX_UNSTABLE`
+    ///
+    /// # Arguments
+    ///
+    /// `var`: The name of the variable
+    pub fn unstable_env<V: Into<Cow<'static, str>>>(mut self, var: V) -> Spec {
+        self.unstable_env = Some(var.into());
+        self
+    }
+
+    /// Returns the program name
+    pub fn get_program(&self) -> Option<&str> {
+        self.program.as_deref()
+    }
+
+    /// Returns the version of the program
+    pub fn get_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns the description of the program
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the declared arguments in declaration order
+    pub fn args(&self) -> &[ArgSpec] {
+        &self.args
+    }
+
+    /// Returns the declared operands in declaration order
+    pub fn positionals(&self) -> &[PositionalSpec] {
+        &self.positionals
+    }
+
+    /// Returns the example invocations
+    pub fn examples(&self) -> impl Iterator<Item = &str> {
+        self.examples.iter().map(|example| example.as_ref())
+    }
+
+    /// Returns the declared subcommands in declaration order
+    pub fn subcommands(&self) -> &[Subcommand] {
+        &self.subcommands
+    }
+
+    /// Returns the name of the default subcommand
+    pub fn get_default_subcommand(&self) -> Option<&str> {
+        self.default_subcommand.as_deref()
+    }
+
+    /// Checks whether small typos are corrected instead of rejected
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Checks whether arguments are parsed in POSIX mode
+    pub fn is_posix(&self) -> bool {
+        self.posix
+    }
+
+    /// Checks whether `-W` introduces a long option in POSIX mode
+    pub fn is_w_long_options(&self) -> bool {
+        self.w_long_options
+    }
+
+    /// Checks whether keys are recognized after the first positional
+    pub fn is_intermixed(&self) -> bool {
+        self.intermixed
+    }
+
+    /// Checks whether grouped one-character arguments are expanded
+    pub fn is_clustered(&self) -> bool {
+        self.clustered
+    }
+
+    /// Checks whether whitespace around values is stripped
+    pub fn is_trimming_values(&self) -> bool {
+        self.trim_values
+    }
+
+    /// Checks whether quotes enclosing values are stripped
+    pub fn is_stripping_quotes(&self) -> bool {
+        self.strip_quotes
+    }
+
+    /// Returns the markers of raw regions as pairs of the opening and
+    /// closing marker
+    pub fn raw_regions(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.raw_regions
+            .iter()
+            .map(|(begin, end)| (begin.as_ref(), end.as_ref()))
+    }
+
+    /// Returns the prefix that marks a token as a value
+    pub fn get_escape_prefix(&self) -> Option<&str> {
+        self.escape_prefix.as_deref()
+    }
+
+    /// Returns the limits on parse input
+    pub fn get_limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Returns the version of the parsing behavior
+    pub fn get_behavior(&self) -> Behavior {
+        self.behavior
+    }
+
+    /// Checks whether parsing records startup timings
+    pub fn is_timed(&self) -> bool {
+        self.timed
+    }
+
+    /// Returns the prefix of the environment variables arguments fall
+    /// back to
+    pub fn get_env_prefix(&self) -> Option<&str> {
+        self.env_prefix.as_deref()
+    }
+
+    /// Returns the environment variable set with `unstable_env`
+    pub fn get_unstable_env(&self) -> Option<&str> {
+        self.unstable_env.as_deref()
+    }
+
+    /// Returns the environment variable an argument falls back to: its
+    /// own, or the one derived from the prefix of the specification
+    ///
+    /// # Arguments
+    ///
+    /// `arg`: The argument
+    pub fn env_var(&self, arg: &ArgSpec) -> Option<Cow<'static, str>> {
+        match (arg.get_env(), self.get_env_prefix()) {
+            (Some(var), _) => Some(Cow::Borrowed(var)),
+            (None, Some(prefix)) => Some(Cow::Owned(format!(
+                "{}_{}",
+                prefix,
+                env_segment(arg.name())
+            ))),
+            (None, None) => None,
+        }
+    }
+
+    /// Gets the declared argument with the given key, or with the given
+    /// alias when no argument is named so
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The key or alias of the argument
+    pub fn get(&self, name: &str) -> Option<&ArgSpec> {
+        self.args.iter().find(|arg| arg.name() == name).or_else(|| {
+            self.args
+                .iter()
+                .find(|arg| arg.get_aliases().contains(&name))
+        })
+    }
+
+    /// Returns the declared name of a key or alias, or the key itself
+    /// when it is not declared
+    pub(crate) fn canonical<'a>(&self, key: &'a str) -> &'a str {
+        self.get(key).map_or(key, |arg| arg.name())
+    }
+
+    /// Merges lower-precedence arguments into higher-precedence ones,
+    /// following the merge policy of each declared argument. Undeclared
+    /// arguments are appended, like `Arguments::merge`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The higher-precedence arguments to merge into
+    ///
+    /// `lower`: The lower-precedence arguments
+    pub fn merge(&self, args: &mut Arguments, lower: Arguments) {
+        args.merge_where(lower, |key, present| {
+            !present
+                || self
+                    .get(key)
+                    .map_or(MergePolicy::Append, |arg| arg.get_merge_policy())
+                    == MergePolicy::Append
+        })
+    }
+
+    /// Searches the declared arguments, except removed ones, by name and
+    /// description. Matching is case-insensitive and tolerates small
+    /// typos in argument names. Results are ordered from best to worst
+    /// match, with ties kept in declaration order
+    ///
+    /// # Arguments
+    ///
+    /// `query`: The text to search for
+    pub fn search(&self, query: &str) -> Vec<&ArgSpec> {
+        let query = query.to_lowercase();
+        let mut matches: Vec<(usize, &ArgSpec)> = self
+            .visible_args()
+            .filter_map(|arg| Some((arg.search_score(&query)?, arg)))
+            .collect();
+        matches.sort_by_key(|&(score, _)| score);
+        matches.into_iter().map(|(_, arg)| arg).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArgSpec, MergePolicy, Spec};
+    use crate::{Arguments, Source};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(ArgSpec::new("proxy").description("Proxy server to connect through"))
+            .arg(ArgSpec::new("no-proxy").description("Hosts that bypass the proxy"))
+            .arg(ArgSpec::new("timeout").description("Connection timeout in seconds"))
+            .arg(ArgSpec::new("verbose").description("Print more output"))
+    }
+
+    static ARGS: [ArgSpec; 2] = [
+        ArgSpec::new("port")
+            .description("Port to listen on")
+            .default_value("8080"),
+        ArgSpec::new("quiet").flag(),
+    ];
+    static SPEC: Spec = Spec::from_static(&ARGS);
+
+    #[test]
+    fn static_spec() {
+        assert_eq!(SPEC.args().len(), 2);
+        assert_eq!(SPEC.get("port").unwrap().get_default_value(), Some("8080"));
+        assert!(SPEC.get("quiet").unwrap().is_flag());
+        let spec = SPEC.clone().arg(ArgSpec::new("verbose"));
+        assert_eq!(spec.args().len(), 3);
+        assert_eq!(SPEC.args().len(), 2);
+    }
+
+    #[test]
+    fn merge_policies() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("include").merge_policy(MergePolicy::Append))
+            .arg(ArgSpec::new("output"));
+        let mut args = Arguments::parse(&["-include", "a", "-output", "cli"]);
+        let file = Arguments::parse_with_source(
+            &["-include", "b", "-output", "file", "-level", "1", "-extra"],
+            Source::File("app.conf".into()),
+        );
+        spec.merge(&mut args, file);
+        assert_eq!(
+            args.get_vec("include").unwrap(),
+            &vec![Some("a".to_string()), Some("b".to_string())]
+        );
+        assert_eq!(
+            args.get_vec("output").unwrap(),
+            &vec![Some("cli".to_string())]
+        );
+        assert_eq!(args.get("level"), Some(Some("1")));
+        assert!(args.contains("extra"));
+    }
+
+    #[test]
+    fn search_name_and_description() {
+        let spec = spec();
+        let names: Vec<&str> = spec.search("PROXY").iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["proxy", "no-proxy"]);
+        let names: Vec<&str> = spec.search("connect").iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["proxy", "timeout"]);
+    }
+
+    #[test]
+    fn search_typo() {
+        let spec = spec();
+        let names: Vec<&str> = spec.search("verbse").iter().map(|a| a.name()).collect();
+        assert_eq!(names, vec!["verbose"]);
+        assert!(spec.search("color").is_empty());
+    }
+}
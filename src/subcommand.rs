@@ -0,0 +1,481 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::fuzzy::rank;
+use crate::{strip_key, ArgSpec, Arguments, Correction, Spec};
+
+/// Error resolving the subcommand invoked by the arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubcommandError {
+    /// No subcommand was given and there is no default subcommand
+    Missing,
+    /// The subcommand is not declared. Suggestions are the names of
+    /// declared subcommands it may be a typo of, closest first
+    Unknown {
+        name: String,
+        suggestions: Vec<String>,
+    },
+}
+
+impl fmt::Display for SubcommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubcommandError::Missing => write!(f, "missing subcommand"),
+            SubcommandError::Unknown { name, suggestions } => {
+                write!(f, "unknown subcommand '{}'", name)?;
+                match suggestions.split_first() {
+                    Some((first, [])) => write!(f, "; did you mean '{}'?", first),
+                    Some((first, rest)) => {
+                        write!(f, "; did you mean '{}'", first)?;
+                        for suggestion in rest {
+                            write!(f, ", '{}'", suggestion)?;
+                        }
+                        write!(f, "?")
+                    }
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl Error for SubcommandError {}
+
+/// Specification of a subcommand, holding the arguments it accepts
+#[derive(Clone, Debug)]
+pub struct Subcommand {
+    name: Cow<'static, str>,
+    aliases: Vec<Cow<'static, str>>,
+    description: Cow<'static, str>,
+    spec: Spec,
+}
+
+impl Subcommand {
+    /// Creates a new subcommand specification
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The name of the subcommand
+    pub fn new<N: Into<Cow<'static, str>>>(name: N) -> Subcommand {
+        Subcommand {
+            name: name.into(),
+            aliases: Vec::new(),
+            description: Cow::Borrowed(""),
+            spec: Spec::new(),
+        }
+    }
+
+    /// Adds an alternative name the subcommand can be invoked by
+    ///
+    /// # Arguments
+    ///
+    /// `alias`: The alternative name
+    pub fn alias<A: Into<Cow<'static, str>>>(mut self, alias: A) -> Subcommand {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Sets the description shown in help output
+    ///
+    /// # Arguments
+    ///
+    /// `description`: The description of the subcommand
+    pub fn description<D: Into<Cow<'static, str>>>(mut self, description: D) -> Subcommand {
+        self.description = description.into();
+        self
+    }
+
+    /// Adds an argument accepted by the subcommand
+    ///
+    /// # Arguments
+    ///
+    /// `arg`: The argument to add
+    pub fn arg(mut self, arg: ArgSpec) -> Subcommand {
+        self.spec = self.spec.arg(arg);
+        self
+    }
+
+    /// Adds an example invocation shown in the subcommand's help
+    ///
+    /// # Arguments
+    ///
+    /// `example`: The example command line
+    pub fn example<E: Into<Cow<'static, str>>>(mut self, example: E) -> Subcommand {
+        self.spec = self.spec.example(example);
+        self
+    }
+
+    /// Replaces the specification of the subcommand's own arguments
+    ///
+    /// # Arguments
+    ///
+    /// `spec`: The specification
+    pub fn spec(mut self, spec: Spec) -> Subcommand {
+        self.spec = spec;
+        self
+    }
+
+    /// Returns the name of the subcommand
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the alternative names of the subcommand
+    pub fn aliases(&self) -> impl Iterator<Item = &str> {
+        self.aliases.iter().map(|alias| alias.as_ref())
+    }
+
+    /// Returns the description of the subcommand
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the specification of the subcommand's own arguments
+    pub fn get_spec(&self) -> &Spec {
+        &self.spec
+    }
+
+    /// Checks whether the subcommand is invoked by the given name,
+    /// either its own or one of its aliases
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The name to check
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases().any(|alias| alias == name)
+    }
+}
+
+impl Arguments {
+    /// Parses arguments starting with a subcommand out of those given,
+    /// returning the name of the subcommand and the arguments following
+    /// it. Arguments that must come before the subcommand need a
+    /// specification with `Spec::split_at_subcommand`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, starting with the subcommand
+    ///
+    /// `subcommands`: The names of the subcommands
+    pub fn parse_with_subcommands<'n, S: AsRef<str>>(
+        args: &[S],
+        subcommands: &[&'n str],
+    ) -> Result<(&'n str, Arguments), SubcommandError> {
+        let (first, rest) = match args.split_first() {
+            Some((first, rest)) if !first.as_ref().starts_with('-') => (first.as_ref(), rest),
+            _ => return Err(SubcommandError::Missing),
+        };
+        match subcommands.iter().find(|&&name| name == first) {
+            Some(name) => Ok((name, Arguments::parse(rest))),
+            None => Err(SubcommandError::Unknown {
+                name: first.to_string(),
+                suggestions: rank(first, subcommands.iter().map(|&name| (name, name)))
+                    .into_iter()
+                    .map(|(_, name)| name.to_string())
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl Spec {
+    /// Finds the subcommand invoked by the given name or alias
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The name to look up
+    pub fn find_subcommand(&self, name: &str) -> Option<&Subcommand> {
+        self.subcommands().iter().find(|sub| sub.matches(name))
+    }
+
+    /// Composes the specification seen by a subcommand: its own
+    /// arguments and examples followed by the global arguments it
+    /// inherits from this specification, along with the version
+    ///
+    /// # Arguments
+    ///
+    /// `subcommand`: The subcommand to compose the specification for
+    pub fn scoped(&self, subcommand: &Subcommand) -> Spec {
+        let mut spec = subcommand.get_spec().clone();
+        if spec.get_program().is_none() {
+            if let Some(program) = self.get_program() {
+                spec = spec.program(format!("{} {}", program, subcommand.name()));
+            }
+        }
+        if spec.get_description().is_empty() {
+            spec = spec.description(subcommand.get_description().to_string());
+        }
+        if spec.get_version().is_none() {
+            if let Some(version) = self.get_version() {
+                spec = spec.version(version.to_string());
+            }
+        }
+        self.args()
+            .iter()
+            .filter(|arg| arg.is_global())
+            .fold(spec, |spec, &arg| spec.arg(arg))
+    }
+
+    /// Splits the arguments at the first operand, which names the
+    /// subcommand, returning the arguments before it and the arguments
+    /// from it onwards. Operands following an argument are treated as
+    /// its value unless the value is attached with `=` or the argument
+    /// is declared as a flag, including negated flags such as
+    /// `-no-verbose`. The operand following `--` names the subcommand
+    /// even when it looks like a key
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, starting with those of this specification
+    pub fn split_at_subcommand<'a, S: AsRef<str>>(&self, args: &'a [S]) -> (&'a [S], &'a [S]) {
+        let mut takes_value = false;
+        for (i, arg) in args.iter().enumerate() {
+            let arg = arg.as_ref();
+            if arg == "--" {
+                return (&args[..=i], &args[i + 1..]);
+            }
+            match strip_key(arg) {
+                Some(key) => {
+                    takes_value = !key.contains('=')
+                        && !self.get(key).is_some_and(|spec| spec.is_flag())
+                        && self.negated_flag(key).is_none();
+                }
+                None if takes_value => takes_value = false,
+                None => return args.split_at(i),
+            }
+        }
+        (args, &[])
+    }
+
+    /// Ranks the declared subcommands the given name may be a typo of,
+    /// closest first, along with their edit distance
+    fn suggest_subcommands(&self, name: &str) -> Vec<(usize, &str)> {
+        rank(
+            name,
+            self.subcommands().iter().flat_map(|sub| {
+                std::iter::once(sub.name())
+                    .chain(sub.aliases())
+                    .map(move |spelling| (spelling, sub.name()))
+            }),
+        )
+    }
+
+    /// Resolves the subcommand selected by the arguments, returning it
+    /// along with the arguments that follow it. When the first argument
+    /// is a flag or absent, the default subcommand is chosen and receives
+    /// every argument. In lenient mode, a name one edit away from exactly
+    /// one subcommand is corrected to it, which callers can detect with
+    /// `Subcommand::matches` or have recorded by `parse_subcommand`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, starting with the subcommand
+    pub fn resolve_subcommand<'a, S: AsRef<str>>(
+        &self,
+        args: &'a [S],
+    ) -> Result<(&Subcommand, &'a [S]), SubcommandError> {
+        self.resolve_corrected(args)
+            .map(|(sub, rest, _)| (sub, rest))
+    }
+
+    /// Resolves the subcommand selected by the arguments like
+    /// `resolve_subcommand` and parses the arguments following it with
+    /// its scoped specification. A subcommand corrected in lenient mode
+    /// is recorded in `Arguments::corrections`, so callers can warn
+    /// about it
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments, starting with the subcommand
+    pub fn parse_subcommand<S: AsRef<str>>(
+        &self,
+        args: &[S],
+    ) -> Result<(&Subcommand, Arguments), SubcommandError> {
+        let (sub, rest, correction) = self.resolve_corrected(args)?;
+        let mut parsed = self.scoped(sub).parse(rest);
+        if let Some(correction) = correction {
+            parsed.corrections.insert(0, correction);
+        }
+        Ok((sub, parsed))
+    }
+
+    /// Resolves the subcommand selected by the arguments, along with
+    /// the correction of its name in lenient mode
+    fn resolve_corrected<'a, S: AsRef<str>>(
+        &self,
+        args: &'a [S],
+    ) -> Result<(&Subcommand, &'a [S], Option<Correction>), SubcommandError> {
+        let (first, rest) = match args.split_first() {
+            Some((first, rest)) if !first.as_ref().starts_with('-') => (first.as_ref(), rest),
+            _ => {
+                return self
+                    .get_default_subcommand()
+                    .and_then(|name| self.find_subcommand(name))
+                    .map(|sub| (sub, args, None))
+                    .ok_or(SubcommandError::Missing)
+            }
+        };
+        if let Some(sub) = self.find_subcommand(first) {
+            return Ok((sub, rest, None));
+        }
+        let suggestions = self.suggest_subcommands(first);
+        if self.is_lenient() {
+            let mut close = suggestions.iter().filter(|&&(distance, _)| distance == 1);
+            if let (Some(&(_, name)), None) = (close.next(), close.next()) {
+                if let Some(sub) = self.find_subcommand(name) {
+                    let correction = Correction {
+                        typed: first.to_string(),
+                        corrected: sub.name().to_string(),
+                        subcommand: true,
+                    };
+                    return Ok((sub, rest, Some(correction)));
+                }
+            }
+        }
+        Err(SubcommandError::Unknown {
+            name: first.to_string(),
+            suggestions: suggestions
+                .into_iter()
+                .map(|(_, name)| name.to_string())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, Spec, Subcommand, SubcommandError};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .subcommand(Subcommand::new("status"))
+            .subcommand(Subcommand::new("remove").alias("rm"))
+            .default_subcommand("status")
+    }
+
+    #[test]
+    fn aliases() {
+        let spec = spec();
+        let (sub, rest) = spec.resolve_subcommand(&["rm", "file"]).unwrap();
+        assert_eq!(sub.name(), "remove");
+        assert_eq!(rest, &["file"]);
+        let (sub, _) = spec.resolve_subcommand(&["remove"]).unwrap();
+        assert_eq!(sub.name(), "remove");
+        assert_eq!(
+            spec.resolve_subcommand(&["unknown"]).unwrap_err(),
+            SubcommandError::Unknown {
+                name: "unknown".to_string(),
+                suggestions: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn default_subcommand() {
+        let spec = spec();
+        let (sub, rest) = spec.resolve_subcommand(&["-short"]).unwrap();
+        assert_eq!(sub.name(), "status");
+        assert_eq!(rest, &["-short"]);
+        let (sub, rest) = spec.resolve_subcommand::<&str>(&[]).unwrap();
+        assert_eq!(sub.name(), "status");
+        assert!(rest.is_empty());
+        assert_eq!(
+            Spec::new()
+                .subcommand(Subcommand::new("status"))
+                .resolve_subcommand(&["-short"])
+                .unwrap_err(),
+            SubcommandError::Missing
+        );
+    }
+
+    #[test]
+    fn names() {
+        let (name, args) =
+            Arguments::parse_with_subcommands(&["build", "-release", "app"], &["build", "test"])
+                .unwrap();
+        assert_eq!(name, "build");
+        assert_eq!(args.get("release"), Some(Some("app")));
+        assert_eq!(
+            Arguments::parse_with_subcommands(&["-release"], &["build", "test"]).err(),
+            Some(SubcommandError::Missing)
+        );
+        assert_eq!(
+            Arguments::parse_with_subcommands(&["tesst"], &["build", "test"])
+                .err()
+                .unwrap()
+                .to_string(),
+            "unknown subcommand 'tesst'; did you mean 'test'?"
+        );
+    }
+
+    #[test]
+    fn split() {
+        let spec = spec()
+            .arg(ArgSpec::new("verbose").flag())
+            .arg(ArgSpec::new("config"));
+        let args = ["-verbose", "rm", "-force"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..1], &args[1..]));
+        let args = ["-config", "rm", "status"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..2], &args[2..]));
+        let args = ["-verbose"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..], &args[1..]));
+        let args = ["-config=a.toml", "rm", "status"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..1], &args[1..]));
+        let args = ["-no-verbose", "rm", "-verbose=false", "status"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..1], &args[1..]));
+        let args = ["-verbose=false", "rm"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..1], &args[1..]));
+        let args = ["-config", "--", "-rm"];
+        assert_eq!(spec.split_at_subcommand(&args), (&args[..2], &args[2..]));
+    }
+
+    #[test]
+    fn suggestions() {
+        let spec = spec().subcommand(Subcommand::new("stash"));
+        let err = spec.resolve_subcommand(&["stats"]).unwrap_err();
+        assert_eq!(
+            err,
+            SubcommandError::Unknown {
+                name: "stats".to_string(),
+                suggestions: vec!["status".to_string()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "unknown subcommand 'stats'; did you mean 'status'?"
+        );
+        let err = spec.resolve_subcommand(&["stas"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown subcommand 'stas'; did you mean 'stash'?"
+        );
+    }
+
+    #[test]
+    fn lenient_correction() {
+        let spec = spec().subcommand(Subcommand::new("stash")).lenient(true);
+        let (sub, rest) = spec.resolve_subcommand(&["stats", "-short"]).unwrap();
+        assert_eq!(sub.name(), "status");
+        assert!(!sub.matches("stats"));
+        assert_eq!(rest, &["-short"]);
+        let (sub, args) = spec.parse_subcommand(&["stats", "-short"]).unwrap();
+        assert_eq!(sub.name(), "status");
+        assert!(args.contains("short"));
+        assert_eq!(
+            args.corrections()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["unknown subcommand 'stats', assuming 'status'"]
+        );
+        let (_, args) = spec.parse_subcommand(&["status"]).unwrap();
+        assert!(args.corrections().is_empty());
+        // one edit away from both "stage" and "stake"
+        let spec = Spec::new()
+            .subcommand(Subcommand::new("stage"))
+            .subcommand(Subcommand::new("stake"))
+            .lenient(true);
+        assert!(spec.resolve_subcommand(&["stale"]).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A directory for the files of a test, unique to the test and the
+/// process so concurrent runs do not collide, and removed when dropped
+/// so no files are left behind when an assertion fails
+pub(crate) struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Creates an empty directory for a test
+    ///
+    /// # Arguments
+    ///
+    /// `name`: The name of the test
+    pub(crate) fn new(name: &str) -> TestDir {
+        let path =
+            std::env::temp_dir().join(format!("simple-args-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDir { path }
+    }
+
+    /// Returns the path of the directory
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of a file in the directory
+    ///
+    /// # Arguments
+    ///
+    /// `file`: The name of the file
+    pub(crate) fn join<P: AsRef<Path>>(&self, file: P) -> PathBuf {
+        self.path.join(file)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::Arguments;
+
+/// A phase of startup timed when timing is enabled with `Spec::timed`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    /// Parsing the command line
+    Parse,
+    /// Filling in environment variables and defaults, excluding files
+    Fallbacks,
+    /// Reading files, such as `_FILE` fallbacks
+    FileIo,
+    /// Validators, such as operand checks and value pipelines
+    Validation,
+    /// Fetching remote configuration
+    Remote,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Parse => "parse",
+            Phase::Fallbacks => "fallbacks",
+            Phase::FileIo => "file io",
+            Phase::Validation => "validation",
+            Phase::Remote => "remote sources",
+        })
+    }
+}
+
+/// Time spent in each phase of startup, from `Arguments::startup_report`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartupReport {
+    phases: Vec<(Phase, Duration, usize)>,
+}
+
+impl StartupReport {
+    /// Returns every timed phase in the order of `Phase`, with the time
+    /// spent in it and how many times it was entered
+    pub fn phases(&self) -> &[(Phase, Duration, usize)] {
+        &self.phases
+    }
+
+    /// Returns the time spent in a phase
+    ///
+    /// # Arguments
+    ///
+    /// `phase`: The phase
+    pub fn get(&self, phase: Phase) -> Duration {
+        self.phases
+            .iter()
+            .find(|&&(timed, _, _)| timed == phase)
+            .map_or(Duration::ZERO, |&(_, spent, _)| spent)
+    }
+
+    /// Returns the time spent in every phase
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|&(_, spent, _)| spent).sum()
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .phases
+            .iter()
+            .map(|&(phase, spent, count)| (phase.to_string(), spent, count))
+            .chain(std::iter::once((String::from("total"), self.total(), 0)));
+        for (name, spent, count) in rows {
+            write!(f, "{:14}  {:>10.3}ms", name, spent.as_secs_f64() * 1000.0)?;
+            if count > 0 {
+                write!(f, "  ({}x)", count)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Recorded phase timings, behind a lock so validators holding shared
+/// references can record
+#[derive(Debug, Default)]
+pub(crate) struct Timings {
+    spans: Mutex<Vec<(Phase, Duration)>>,
+}
+
+impl Arguments {
+    /// Starts recording startup timings, which parsing through a timed
+    /// specification does automatically
+    pub fn enable_timings(&mut self) {
+        self.timings.get_or_insert_with(Timings::default);
+    }
+
+    /// Runs a closure and records the time it took under a phase when
+    /// timings are enabled, such as for an application's own validators
+    ///
+    /// # Arguments
+    ///
+    /// `phase`: The phase the closure belongs to
+    ///
+    /// `f`: The closure to time
+    pub fn time<T, F: FnOnce() -> T>(&self, phase: Phase, f: F) -> T {
+        let start = Instant::now();
+        let out = f();
+        self.record(phase, start.elapsed());
+        out
+    }
+
+    /// Summarizes where startup time was spent, or `None` when timings
+    /// are not enabled
+    pub fn startup_report(&self) -> Option<StartupReport> {
+        let timings = self.timings.as_ref()?;
+        let spans = timings.spans.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut phases: Vec<(Phase, Duration, usize)> = Vec::new();
+        for &(phase, spent) in spans.iter() {
+            match phases.iter_mut().find(|(timed, _, _)| *timed == phase) {
+                Some((_, total, count)) => {
+                    *total += spent;
+                    *count += 1;
+                }
+                None => phases.push((phase, spent, 1)),
+            }
+        }
+        phases.sort_unstable_by_key(|&(phase, _, _)| phase);
+        Some(StartupReport { phases })
+    }
+
+    /// Records the time spent in a phase when timings are enabled
+    pub(crate) fn record(&self, phase: Phase, spent: Duration) {
+        if let Some(timings) = &self.timings {
+            timings
+                .spans
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((phase, spent));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{ArgSpec, Arguments, Phase, PositionalSpec, Spec};
+
+    #[test]
+    fn report() {
+        assert!(Arguments::parse(&["-x"]).startup_report().is_none());
+        let spec = Spec::new()
+            .arg(ArgSpec::new("port").default_value("80"))
+            .positional(PositionalSpec::new("FILE"))
+            .timed(true);
+        let mut args = spec.parse(&["a.txt"]);
+        spec.apply_fallbacks(&mut args);
+        spec.check_operands(&args).unwrap();
+        args.time(Phase::Validation, || {
+            std::thread::sleep(Duration::from_millis(2))
+        });
+        let report = args.startup_report().unwrap();
+        let phases: Vec<(Phase, usize)> = report
+            .phases()
+            .iter()
+            .map(|&(phase, _, count)| (phase, count))
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                (Phase::Parse, 1),
+                (Phase::Fallbacks, 1),
+                (Phase::Validation, 2)
+            ]
+        );
+        assert!(report.get(Phase::Validation) >= Duration::from_millis(2));
+        assert_eq!(report.get(Phase::Remote), Duration::ZERO);
+        assert!(report.total() >= report.get(Phase::Validation));
+        let text = report.to_string();
+        assert!(text.starts_with("parse    "));
+        assert!(text.contains("validation"));
+        assert!(text.lines().last().unwrap().starts_with("total"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::from_args::convert_first;
+use crate::{Arguments, FromArgsError, Spec};
+
+/// A typed handle to a required argument, resolving to its value
+/// without an `Option`
+pub struct Required<T> {
+    key: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// A typed handle to an optional argument, resolving to its value if
+/// present
+pub struct Optional<T> {
+    key: &'static str,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: FromStr> Required<T>
+where
+    T::Err: Display,
+{
+    /// Returns the key of the argument
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Converts the first value of the argument, failing when it is
+    /// absent or invalid
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn resolve(&self, args: &Arguments) -> Result<T, FromArgsError> {
+        convert_first(args, self.key, |value| Cow::Borrowed(value))?
+            .ok_or_else(|| FromArgsError::Missing(self.key.to_string()))
+    }
+}
+
+impl<T: FromStr> Optional<T>
+where
+    T::Err: Display,
+{
+    /// Returns the key of the argument
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Converts the first value of the argument if present, failing
+    /// when it is invalid
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn resolve(&self, args: &Arguments) -> Result<Option<T>, FromArgsError> {
+        convert_first(args, self.key, |value| Cow::Borrowed(value))
+    }
+}
+
+impl<T> Clone for Required<T> {
+    fn clone(&self) -> Required<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Required<T> {}
+
+impl<T> Clone for Optional<T> {
+    fn clone(&self) -> Optional<T> {
+        *self
+    }
+}
+
+impl<T> Copy for Optional<T> {}
+
+impl<T> fmt::Debug for Required<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Required").field(&self.key).finish()
+    }
+}
+
+impl<T> fmt::Debug for Optional<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Optional").field(&self.key).finish()
+    }
+}
+
+impl Spec {
+    /// Creates a typed handle to a declared argument that must be
+    /// present, whose `resolve` yields the converted value directly.
+    /// Declaring a default value makes it present after fallbacks
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the declared argument
+    ///
+    /// # Panics
+    ///
+    /// Panics when the argument is not declared
+    pub fn required<T: FromStr>(&self, key: &'static str) -> Required<T> {
+        assert!(self.get(key).is_some(), "argument -{} is not declared", key);
+        Required {
+            key,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a typed handle to a declared argument that may be
+    /// absent, whose `resolve` yields an `Option` of the converted value
+    ///
+    /// # Arguments
+    ///
+    /// `key`: The key of the declared argument
+    ///
+    /// # Panics
+    ///
+    /// Panics when the argument is not declared
+    pub fn optional<T: FromStr>(&self, key: &'static str) -> Optional<T> {
+        assert!(self.get(key).is_some(), "argument -{} is not declared", key);
+        Optional {
+            key,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, FromArgsError, Spec};
+
+    #[test]
+    fn typed_handles() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("port"))
+            .arg(ArgSpec::new("threads"));
+        let port = spec.required::<u16>("port");
+        let threads = spec.optional::<usize>("threads");
+        let args = Arguments::parse(&["-port", "8080"]);
+        let value: u16 = port.resolve(&args).unwrap();
+        assert_eq!(value, 8080);
+        assert_eq!(threads.resolve(&args), Ok(None));
+        assert_eq!(
+            port.resolve(&Arguments::parse::<&str>(&[])),
+            Err(FromArgsError::Missing("port".to_string()))
+        );
+        assert!(threads
+            .resolve(&Arguments::parse(&["-threads", "many"]))
+            .is_err());
+    }
+}
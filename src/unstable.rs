@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+use std::env;
+
+use crate::fallback::is_truthy;
+use crate::{Arguments, Spec};
+
+/// The key of the master switch unlocking experimental arguments
+pub const UNSTABLE_SWITCH: &str = "unstable";
+
+impl Spec {
+    /// Returns the environment variable unlocking experimental
+    /// arguments: the one set with `unstable_env`, or `PREFIX_UNSTABLE`
+    /// when an environment prefix is set
+    pub fn unstable_var(&self) -> Option<Cow<'static, str>> {
+        match (self.get_unstable_env(), self.get_env_prefix()) {
+            (Some(var), _) => Some(Cow::Owned(var.to_string())),
+            (None, Some(prefix)) => Some(Cow::Owned(format!("{}_UNSTABLE", prefix))),
+            (None, None) => None,
+        }
+    }
+
+    /// Checks whether experimental arguments are unlocked, by the
+    /// `-unstable` switch or by the variable of `unstable_var` being set
+    /// to a value other than `0`, `false`, `no` or `off`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn is_unstable_unlocked(&self, args: &Arguments) -> bool {
+        self.is_unstable_unlocked_with(args, |var| env::var(var).ok())
+    }
+
+    /// Checks whether experimental arguments are unlocked like
+    /// `is_unstable_unlocked`, reading the variable through a lookup
+    /// function instead of the process environment
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    ///
+    /// `lookup`: Returns the value of an environment variable
+    pub fn is_unstable_unlocked_with<F: Fn(&str) -> Option<String>>(
+        &self,
+        args: &Arguments,
+        lookup: F,
+    ) -> bool {
+        args.contains(UNSTABLE_SWITCH)
+            || self
+                .unstable_var()
+                .and_then(|var| lookup(var.as_ref()))
+                .is_some_and(|value| is_truthy(&value))
+    }
+
+    /// Checks whether the specification declares experimental arguments,
+    /// in which case the `-unstable` switch is implicitly declared
+    pub(crate) fn has_experimental(&self) -> bool {
+        self.args().iter().any(|arg| arg.is_experimental())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Spec, Violation};
+
+    #[test]
+    fn gating() {
+        let spec = Spec::new()
+            .env_prefix("SIMPLE_ARGS_GATING")
+            .arg(ArgSpec::new("jit").flag().experimental())
+            .arg(ArgSpec::new("port"));
+        assert_eq!(spec.unstable_var().unwrap(), "SIMPLE_ARGS_GATING_UNSTABLE");
+        let args = spec.parse(&["-jit", "-port", "1"]);
+        let err = spec.validate(&args).unwrap_err();
+        assert_eq!(
+            err.violations,
+            [Violation::Experimental {
+                key: "jit".to_string(),
+                var: Some("SIMPLE_ARGS_GATING_UNSTABLE".to_string()),
+            }]
+        );
+        assert_eq!(
+            err.to_string(),
+            "-jit is experimental and requires -unstable or SIMPLE_ARGS_GATING_UNSTABLE=1"
+        );
+        assert!(spec.parse_validated(&["-jit", "-unstable"]).is_ok());
+        assert_eq!(spec.usage_entry("jit").unwrap(), "-jit  (experimental)");
+        let env = |value: &'static str| {
+            move |var: &str| (var == "SIMPLE_ARGS_GATING_UNSTABLE").then(|| value.to_string())
+        };
+        assert!(spec.is_unstable_unlocked_with(&args, env("1")));
+        assert!(!spec.is_unstable_unlocked_with(&args, env("0")));
+        assert!(!spec.is_unstable_unlocked(&args));
+        let spec = Spec::new()
+            .arg(ArgSpec::new("jit").flag().experimental())
+            .unstable_env("SIMPLE_ARGS_GATING_OTHER");
+        assert_eq!(spec.unstable_var().unwrap(), "SIMPLE_ARGS_GATING_OTHER");
+        assert!(Spec::new()
+            .validate(&Spec::new().parse(&["-unstable"]))
+            .is_err());
+    }
+}
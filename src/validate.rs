@@ -0,0 +1,460 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::fuzzy::rank;
+use crate::{ArgSpec, Arguments, Lifecycle, Phase, Spec, CI_SWITCH, UNSTABLE_SWITCH};
+
+/// A way parsed arguments do not match their specification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The key is not declared. The suggestion is the declared key it
+    /// is most likely a typo of
+    Unknown {
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// A required argument is absent
+    Missing(String),
+    /// An argument taking a value was given without one
+    MissingValue(String),
+    /// A flag was given a value
+    UnexpectedValue(String),
+    /// An argument appears more often than allowed
+    TooMany {
+        key: String,
+        max: usize,
+        given: usize,
+    },
+    /// An argument removed at the version of the specification was given
+    Removed { key: String, removed_in: String },
+    /// An experimental argument was given without unlocking it. The
+    /// variable is the one unlocking experimental arguments, if any
+    Experimental { key: String, var: Option<String> },
+    /// An argument was given a value outside of its choices
+    InvalidChoice {
+        key: String,
+        value: String,
+        choices: Vec<String>,
+    },
+    /// An argument was given without an argument it requires
+    Requires { key: String, required: String },
+    /// Two arguments that cannot be given together were given
+    Conflict { key: String, other: String },
+}
+
+impl Violation {
+    /// Returns the key the violation is about
+    pub fn key(&self) -> &str {
+        match self {
+            Violation::Unknown { key, .. }
+            | Violation::TooMany { key, .. }
+            | Violation::InvalidChoice { key, .. }
+            | Violation::Removed { key, .. }
+            | Violation::Experimental { key, .. }
+            | Violation::Requires { key, .. }
+            | Violation::Conflict { key, .. } => key,
+            Violation::Missing(key)
+            | Violation::MissingValue(key)
+            | Violation::UnexpectedValue(key) => key,
+        }
+    }
+}
+
+/// Formats a number of occurrences as `1 time` or `n times`
+fn times(count: usize) -> String {
+    match count {
+        1 => String::from("1 time"),
+        count => format!("{} times", count),
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Unknown {
+                key,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "unknown argument -{}; did you mean -{}?",
+                key, suggestion
+            ),
+            Violation::Unknown { key, .. } => write!(f, "unknown argument -{}", key),
+            Violation::Missing(key) => write!(f, "missing required argument -{}", key),
+            Violation::MissingValue(key) => write!(f, "-{} requires a value", key),
+            Violation::UnexpectedValue(key) => write!(f, "-{} does not take a value", key),
+            Violation::TooMany { key, max, given } => write!(
+                f,
+                "-{} may appear at most {}, but appears {}",
+                key,
+                times(*max),
+                times(*given)
+            ),
+            Violation::Experimental { key, var } => {
+                write!(f, "-{} is experimental and requires -unstable", key)?;
+                match var {
+                    Some(var) => write!(f, " or {}=1", var),
+                    None => Ok(()),
+                }
+            }
+            Violation::Removed { key, removed_in } => {
+                write!(f, "-{} was removed in {}", key, removed_in)
+            }
+            Violation::InvalidChoice {
+                key,
+                value,
+                choices,
+            } => write!(
+                f,
+                "'{}' is not a valid value for -{}; expected one of {}",
+                value,
+                key,
+                choices.join(", ")
+            ),
+            Violation::Requires { key, required } => {
+                write!(f, "-{} requires -{}", key, required)
+            }
+            Violation::Conflict { key, other } => {
+                write!(f, "-{} cannot be used with -{}", key, other)
+            }
+        }
+    }
+}
+
+/// Error returned when parsed arguments do not match their
+/// specification, holding every violation found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The violations, for declared arguments in declaration order
+    /// followed by unknown keys in alphabetical order
+    pub violations: Vec<Violation>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Error returned by `Arguments::require` listing every absent key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingArgs {
+    /// The absent keys, in the order they were required
+    pub keys: Vec<String>,
+}
+
+impl fmt::Display for MissingArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.keys.iter().map(|key| format!("-{}", key)).collect();
+        match keys.as_slice() {
+            [key] => write!(f, "missing required argument {}", key),
+            keys => write!(f, "missing required arguments {}", keys.join(", ")),
+        }
+    }
+}
+
+impl Error for MissingArgs {}
+
+impl Arguments {
+    /// Checks that every given key is present, failing with the list of
+    /// those absent, for programs without a specification marking
+    /// arguments as required
+    ///
+    /// # Arguments
+    ///
+    /// `keys`: The required keys
+    pub fn require(&self, keys: &[&str]) -> Result<(), MissingArgs> {
+        let missing: Vec<String> = keys
+            .iter()
+            .filter(|key| !self.contains(key))
+            .map(|key| key.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingArgs { keys: missing })
+        }
+    }
+}
+
+impl Spec {
+    /// Parse arguments like `parse`, failing when they do not match the
+    /// specification. See `validate`
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The arguments
+    pub fn parse_validated<S: AsRef<str>>(&self, args: &[S]) -> Result<Arguments, ValidationError> {
+        let arguments = self.parse(args);
+        self.validate(&arguments)?;
+        Ok(arguments)
+    }
+
+    /// Checks parsed arguments against the specification, reporting
+    /// every undeclared key, absent required argument, missing or
+    /// unexpected value, argument appearing too often, value outside of
+    /// its choices, removed argument, locked experimental argument,
+    /// argument given without one it requires and pair of conflicting
+    /// arguments. Validate after `apply_fallbacks` for fallbacks to
+    /// satisfy required arguments
+    ///
+    /// # Arguments
+    ///
+    /// `args`: The parsed arguments
+    pub fn validate(&self, args: &Arguments) -> Result<(), ValidationError> {
+        let violations = args.time(Phase::Validation, || self.violations(args));
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
+        }
+    }
+
+    /// Lists the violations of parsed arguments like `validate`
+    fn violations(&self, args: &Arguments) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let unlocked = self.is_unstable_unlocked(args);
+        let canonical = |key| self.get(key).map_or(key, ArgSpec::name);
+        let given = |key| match self.get(key) {
+            Some(arg) if arg.is_flag() => args.get_bool(arg.name()) == Some(true),
+            _ => args.contains(key),
+        };
+        for arg in self.args() {
+            let vals = match args.get_vec(arg.name()) {
+                Some(vals) => vals,
+                None => {
+                    if arg.is_required()
+                        && !matches!(self.lifecycle(arg), Lifecycle::Removed { .. })
+                    {
+                        violations.push(Violation::Missing(arg.name().to_string()));
+                    }
+                    continue;
+                }
+            };
+            if let Lifecycle::Removed { removed_in } = self.lifecycle(arg) {
+                violations.push(Violation::Removed {
+                    key: arg.name().to_string(),
+                    removed_in: removed_in.to_string(),
+                });
+                continue;
+            }
+            if arg.is_experimental() && !unlocked {
+                violations.push(Violation::Experimental {
+                    key: arg.name().to_string(),
+                    var: self.unstable_var().map(|var| var.to_string()),
+                });
+            }
+            if arg.is_flag() && vals.iter().any(Option::is_some) {
+                violations.push(Violation::UnexpectedValue(arg.name().to_string()));
+            } else if !arg.is_flag() && !arg.is_optional_value() && vals.iter().any(Option::is_none)
+            {
+                violations.push(Violation::MissingValue(arg.name().to_string()));
+            }
+            if let Some(max) = arg.get_max_occurrences().filter(|&max| vals.len() > max) {
+                violations.push(Violation::TooMany {
+                    key: arg.name().to_string(),
+                    max,
+                    given: vals.len(),
+                });
+            }
+            let choices = arg.get_choices();
+            if let Some(value) = vals
+                .iter()
+                .flatten()
+                .find(|val| !choices.is_empty() && !choices.contains(&val.as_str()))
+            {
+                violations.push(Violation::InvalidChoice {
+                    key: arg.name().to_string(),
+                    value: value.clone(),
+                    choices: choices.iter().map(|choice| choice.to_string()).collect(),
+                });
+            }
+            if !given(arg.name()) {
+                continue;
+            }
+            for &required in arg.get_requires() {
+                if !given(required) {
+                    violations.push(Violation::Requires {
+                        key: arg.name().to_string(),
+                        required: canonical(required).to_string(),
+                    });
+                }
+            }
+            for &other in arg.get_conflicts_with() {
+                let other = canonical(other);
+                let reported = violations.iter().any(|violation| {
+                    matches!(violation, Violation::Conflict { key, other: reported }
+                        if key == other && reported == arg.name())
+                });
+                if given(other) && !reported {
+                    violations.push(Violation::Conflict {
+                        key: arg.name().to_string(),
+                        other: other.to_string(),
+                    });
+                }
+            }
+        }
+        let mut unknown: Vec<&str> = args
+            .arg_map
+            .keys()
+            .filter(|key| self.get(key).is_none())
+            .filter(|key| !(key.as_str() == UNSTABLE_SWITCH && self.has_experimental()))
+            .filter(|key| !(key.as_str() == CI_SWITCH && self.has_ci_defaults()))
+            .filter(|key| self.negated_flag(key).is_none())
+            .map(|key| key.as_str())
+            .collect();
+        unknown.sort_unstable();
+        for key in unknown {
+            let suggestion = rank(key, self.args().iter().map(|arg| (arg.name(), arg.name())))
+                .first()
+                .map(|&(_, name)| name.to_string());
+            violations.push(Violation::Unknown {
+                key: key.to_string(),
+                suggestion,
+            });
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ArgSpec, Arguments, MissingArgs, Spec, Violation};
+
+    fn spec() -> Spec {
+        Spec::new()
+            .arg(ArgSpec::new("host").required())
+            .arg(ArgSpec::new("port").max_occurrences(1))
+            .arg(ArgSpec::new("verbose").flag())
+            .arg(ArgSpec::new("color").choices(&["auto", "always", "never"]))
+    }
+
+    #[test]
+    fn violations() {
+        assert!(spec().parse_validated(&["-host", "a", "-verbose"]).is_ok());
+        let spec = spec();
+        let args = spec.parse(&[
+            "-port",
+            "1",
+            "-port=2",
+            "-verbose=yes",
+            "-color",
+            "blue",
+            "-portt",
+            "3",
+        ]);
+        let err = spec.validate(&args).unwrap_err();
+        assert_eq!(
+            err.violations,
+            [
+                Violation::Missing("host".to_string()),
+                Violation::TooMany {
+                    key: "port".to_string(),
+                    max: 1,
+                    given: 2,
+                },
+                Violation::UnexpectedValue("verbose".to_string()),
+                Violation::InvalidChoice {
+                    key: "color".to_string(),
+                    value: "blue".to_string(),
+                    choices: vec![
+                        "auto".to_string(),
+                        "always".to_string(),
+                        "never".to_string()
+                    ],
+                },
+                Violation::Unknown {
+                    key: "portt".to_string(),
+                    suggestion: Some("port".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing required argument -host\n\
+             -port may appear at most 1 time, but appears 2 times\n\
+             -verbose does not take a value\n\
+             'blue' is not a valid value for -color; expected one of auto, always, never\n\
+             unknown argument -portt; did you mean -port?"
+        );
+        let err = spec.validate(&spec.parse(&["-host"])).unwrap_err();
+        assert_eq!(
+            err.violations,
+            [Violation::MissingValue("host".to_string())]
+        );
+    }
+
+    #[test]
+    fn require() {
+        let args = Arguments::parse(&["-host", "a"]);
+        assert!(args.require(&["host"]).is_ok());
+        let err = args.require(&["host", "port", "user"]).unwrap_err();
+        assert_eq!(
+            err,
+            MissingArgs {
+                keys: vec!["port".to_string(), "user".to_string()],
+            }
+        );
+        assert_eq!(err.to_string(), "missing required arguments -port, -user");
+        assert_eq!(
+            args.require(&["port"]).unwrap_err().to_string(),
+            "missing required argument -port"
+        );
+    }
+
+    #[test]
+    fn fallbacks_satisfy_required() {
+        let spec = Spec::new().arg(ArgSpec::new("host").required().default_value("localhost"));
+        let mut args = spec.parse::<&str>(&[]);
+        assert!(spec.validate(&args).is_err());
+        spec.apply_fallbacks(&mut args);
+        assert!(spec.validate(&args).is_ok());
+    }
+
+    #[test]
+    fn relationships() {
+        let spec = Spec::new()
+            .arg(ArgSpec::new("key-file").requires(&["cert-file"]))
+            .arg(ArgSpec::new("cert-file").aliases(&["cert"]))
+            .arg(ArgSpec::new("quiet").flag().conflicts_with(&["verbose"]))
+            .arg(ArgSpec::new("verbose").flag().conflicts_with(&["quiet"]));
+        let err = spec
+            .try_parse(&["-key-file", "k", "-quiet", "-verbose"])
+            .err()
+            .unwrap();
+        match err {
+            crate::ParseError::Invalid(err) => {
+                assert_eq!(
+                    err.violations,
+                    [
+                        Violation::Requires {
+                            key: "key-file".to_string(),
+                            required: "cert-file".to_string(),
+                        },
+                        Violation::Conflict {
+                            key: "quiet".to_string(),
+                            other: "verbose".to_string(),
+                        },
+                    ]
+                );
+                assert_eq!(
+                    err.to_string(),
+                    "-key-file requires -cert-file\n-quiet cannot be used with -verbose"
+                );
+            }
+            err => panic!("{}", err),
+        }
+        assert!(spec.try_parse(&["-key-file", "k", "-cert", "c"]).is_ok());
+        assert!(spec
+            .try_parse(&["-quiet", "-verbose", "-no-verbose"])
+            .is_ok());
+    }
+}
@@ -0,0 +1,59 @@
+//! Counts heap allocations made while parsing, guarding against
+//! regressions in the number of allocations per parse. Short keys are
+//! stored inline, so each distinct key only costs the vectors holding
+//! its values and sources, while every value is its own `String`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use simple_args::Arguments;
+
+struct Counting;
+
+thread_local! {
+    // counted per thread so tests running in parallel do not interfere
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<F: FnOnce() -> Arguments>(parse: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let args = parse();
+    let count = ALLOCATIONS.with(Cell::get) - before;
+    drop(args);
+    count
+}
+
+#[test]
+fn repeated_keys() {
+    let tokens: Vec<String> = (0..100)
+        .flat_map(|i| vec!["-include".to_string(), format!("dir{}", i)])
+        .collect();
+    let count = allocations(|| Arguments::parse(&tokens));
+    // one allocation per value and the amortized growth of the vectors
+    assert!(count <= 100 + 20, "{} allocations", count);
+}
+
+#[test]
+fn distinct_keys() {
+    let tokens: Vec<String> = (0..20)
+        .flat_map(|i| vec![format!("-flag{}", i), format!("{}", i)])
+        .collect();
+    let count = allocations(|| Arguments::parse(&tokens));
+    // per key a value and the vectors of values and sources, with no
+    // allocation for the key itself, plus the growth of the maps
+    assert!(count <= 20 * 3 + 10, "{} allocations", count);
+}
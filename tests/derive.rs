@@ -0,0 +1,235 @@
+use simple_args::{FromArgsError, SimpleArgs, SubcommandError};
+#[cfg(not(feature = "derive"))]
+use simple_args_derive::SimpleArgs;
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct Cli {
+    verbose: bool,
+    config: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+enum Command {
+    Build(BuildArgs),
+    DryRun,
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct BuildArgs {
+    release: bool,
+    jobs: u32,
+}
+
+#[test]
+fn subcommand_with_args() {
+    let cli = Cli::from_args(&["-verbose", "build", "-release", "-jobs", "4"]).unwrap();
+    assert_eq!(
+        cli,
+        Cli {
+            verbose: true,
+            config: None,
+            command: Command::Build(BuildArgs {
+                release: true,
+                jobs: 4
+            }),
+        }
+    );
+}
+
+#[test]
+fn unit_subcommand() {
+    let cli = Cli::from_args(&["-config", "a.toml", "dry-run"]).unwrap();
+    assert_eq!(cli.config.as_deref(), Some("a.toml"));
+    assert_eq!(cli.command, Command::DryRun);
+    let cli = Cli::from_args(&["-config=a.toml", "dry-run"]).unwrap();
+    assert_eq!(cli.config.as_deref(), Some("a.toml"));
+    assert_eq!(cli.command, Command::DryRun);
+}
+
+#[test]
+fn negated_flag_before_subcommand() {
+    let cli = Cli::from_args(&["-no-verbose", "dry-run"]).unwrap();
+    assert!(!cli.verbose);
+    assert_eq!(cli.command, Command::DryRun);
+    let cli = Cli::from_args(&["-verbose=false", "dry-run"]).unwrap();
+    assert!(!cli.verbose);
+    assert_eq!(cli.command, Command::DryRun);
+}
+
+#[test]
+fn subcommand_errors() {
+    assert_eq!(
+        Cli::from_args(&["-verbose"]).unwrap_err(),
+        FromArgsError::Subcommand(SubcommandError::Missing)
+    );
+    assert_eq!(
+        Cli::from_args(&["buld"]).unwrap_err().to_string(),
+        "unknown subcommand 'buld'; did you mean 'build'?"
+    );
+    assert_eq!(
+        Cli::from_args(&["build", "-jobs", "many"])
+            .unwrap_err()
+            .to_string(),
+        "invalid value 'many' for -jobs: invalid digit found in string"
+    );
+    assert_eq!(
+        Cli::from_args(&["build"]).unwrap_err(),
+        FromArgsError::Missing("jobs".to_string())
+    );
+}
+
+#[test]
+fn spec_lists_subcommands() {
+    let spec = Cli::spec();
+    let names: Vec<&str> = spec.subcommands().iter().map(|sub| sub.name()).collect();
+    assert_eq!(names, vec!["build", "dry-run"]);
+    assert!(spec.get("verbose").unwrap().is_flag());
+    assert!(spec.subcommands()[0].get_spec().get("jobs").is_some());
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct LoggingOpts {
+    quiet: bool,
+    log_file: Option<String>,
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct Serve {
+    #[args(flatten)]
+    logging: LoggingOpts,
+    port: u16,
+}
+
+#[test]
+fn flattened_options() {
+    let serve = Serve::from_args(&["-port", "80", "-quiet", "-log-file", "out.log"]).unwrap();
+    assert_eq!(
+        serve,
+        Serve {
+            logging: LoggingOpts {
+                quiet: true,
+                log_file: Some("out.log".to_string()),
+            },
+            port: 80,
+        }
+    );
+    let spec = Serve::spec();
+    let names: Vec<&str> = spec.args().iter().map(|arg| arg.name()).collect();
+    assert_eq!(names, vec!["quiet", "log-file", "port"]);
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct Server {
+    #[args(default = "8080", env = "SIMPLE_ARGS_TEST_PORT")]
+    port: u16,
+    #[args(default = "localhost")]
+    host: String,
+    #[args(env = "SIMPLE_ARGS_TEST_DEBUG")]
+    debug: bool,
+}
+
+#[test]
+fn defaults_and_env() {
+    let env = |port: Option<&'static str>| {
+        move |var: &str| match var {
+            "SIMPLE_ARGS_TEST_PORT" => port.map(str::to_string),
+            "SIMPLE_ARGS_TEST_DEBUG" => Some("1".to_string()),
+            _ => None,
+        }
+    };
+    let server = Server::from_args_with::<&str, _>(&[], env(None)).unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.host, "localhost");
+    assert!(server.debug);
+
+    let server = Server::from_args_with(&["-host", "example.com"], env(Some("9000"))).unwrap();
+    assert_eq!(server.port, 9000);
+    assert_eq!(server.host, "example.com");
+    let server = Server::from_args_with(&["-port", "1"], env(Some("9000"))).unwrap();
+    assert_eq!(server.port, 1);
+
+    let err = Server::from_args_with::<&str, _>(&[], env(Some("eighty")))
+        .err()
+        .unwrap();
+    assert!(err.to_string().starts_with(
+        "invalid value 'eighty' for -port from environment variable SIMPLE_ARGS_TEST_PORT: "
+    ));
+    let err = Server::from_args_with(&["-port", "x"], env(None))
+        .err()
+        .unwrap();
+    assert!(err.to_string().starts_with("invalid value 'x' for -port: "));
+    let spec = Server::spec();
+    assert_eq!(
+        spec.get("port").unwrap().get_env(),
+        Some("SIMPLE_ARGS_TEST_PORT")
+    );
+}
+
+/// Deploys the site
+#[derive(SimpleArgs)]
+struct Deploy {
+    /// Target environment
+    target: String,
+    /// Skip the confirmation prompt
+    yes: bool,
+}
+
+/// Site tooling
+#[derive(SimpleArgs)]
+enum Site {
+    /// Deploy the site
+    Deploy(Deploy),
+    Clean,
+}
+
+#[test]
+fn doc_comment_help() {
+    let spec = Deploy::spec();
+    assert_eq!(spec.get_description(), "Deploys the site");
+    assert_eq!(
+        spec.get("yes").unwrap().get_description(),
+        "Skip the confirmation prompt"
+    );
+    assert_eq!(
+        Site::spec().subcommands()[0].get_description(),
+        "Deploy the site"
+    );
+    #[cfg(feature = "help")]
+    assert_eq!(
+        Deploy::spec().help(),
+        "Deploys the site\n\n\
+         Arguments:\n  -target <value>  Target environment\n  -yes             Skip the confirmation prompt\n"
+    );
+    match Site::from_args(&["deploy", "-target", "prod", "-yes"]).unwrap() {
+        Site::Deploy(deploy) => assert!(deploy.target == "prod" && deploy.yes),
+        Site::Clean => panic!("expected deploy"),
+    }
+}
+
+#[derive(Debug, PartialEq, SimpleArgs)]
+struct Compile {
+    include: Vec<String>,
+    #[args(default = "2")]
+    opt_level: u8,
+    output: Option<String>,
+}
+
+#[test]
+fn repeated_values() {
+    let compile = Compile::parse(&["cc", "-include", "a", "-include", "b"]).unwrap();
+    assert_eq!(
+        compile,
+        Compile {
+            include: vec!["a".to_string(), "b".to_string()],
+            opt_level: 2,
+            output: None,
+        }
+    );
+    assert!(Compile::parse(&["cc"]).unwrap().include.is_empty());
+    assert_eq!(
+        Compile::from_args(&["-include"]).unwrap_err(),
+        FromArgsError::MissingValue("include".to_string())
+    );
+}